@@ -0,0 +1,7 @@
+pub mod collateral;
+pub mod contract;
+pub mod error;
+pub mod state;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,211 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Addr, Api, BalanceResponse, BankQuery, CanonicalAddr, Coin,
+    ContractResult, Decimal, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError,
+    SystemResult, Uint128, WasmQuery,
+};
+use cosmwasm_storage::to_length_prefixed;
+use cw20::TokenInfoResponse;
+use moneymarket::overseer::{PauseStatusResponse, QueryMsg as OverseerQueryMsg};
+use std::collections::HashMap;
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<TerraQueryWrapper>,
+    token_querier: TokenQuerier,
+    reward_balance: Uint128,
+    other_balance: Uint128,
+    tax_querier: TaxQuerier,
+    pause_status: PauseStatusResponse,
+}
+
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    // this lets us iterate over all pairs that match the first string
+    balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+#[derive(Clone, Default)]
+pub struct TaxQuerier {
+    rate: Decimal,
+    // this lets us iterate over all pairs that match the first string
+    caps: HashMap<String, Uint128>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+        match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if &TerraRoute::Treasury == route {
+                    match query_data {
+                        TerraQuery::TaxRate {} => {
+                            let res = TaxRateResponse {
+                                rate: self.tax_querier.rate,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        TerraQuery::TaxCap { denom } => {
+                            let cap = self
+                                .tax_querier
+                                .caps
+                                .get(denom)
+                                .copied()
+                                .unwrap_or_default();
+                            let res = TaxCapResponse { cap };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
+                let key: &[u8] = key.as_slice();
+
+                let prefix_token_info = to_length_prefixed(b"token_info").to_vec();
+                let prefix_balance = to_length_prefixed(b"balance").to_vec();
+
+                let balances: &HashMap<String, Uint128> =
+                    match self.token_querier.balances.get(contract_addr) {
+                        Some(balances) => balances,
+                        None => {
+                            return SystemResult::Err(SystemError::InvalidRequest {
+                                error: format!(
+                                    "No balance info exists for the contract {}",
+                                    contract_addr
+                                ),
+                                request: key.into(),
+                            })
+                        }
+                    };
+
+                if key.to_vec() == prefix_token_info {
+                    let mut total_supply = Uint128::zero();
+
+                    for balance in balances {
+                        total_supply += *balance.1;
+                    }
+
+                    SystemResult::Ok(ContractResult::from(to_binary(
+                        &to_binary(&TokenInfoResponse {
+                            name: "mAPPL".to_string(),
+                            symbol: "mAPPL".to_string(),
+                            decimals: 6,
+                            total_supply,
+                        })
+                        .unwrap(),
+                    )))
+                } else if key[..prefix_balance.len()].to_vec() == prefix_balance {
+                    let key_address: &[u8] = &key[prefix_balance.len()..];
+                    let address_raw: CanonicalAddr = CanonicalAddr::from(key_address);
+                    let api: MockApi = MockApi::default();
+                    let address: Addr = match api.addr_humanize(&address_raw) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            return SystemResult::Err(SystemError::InvalidRequest {
+                                error: format!("Parsing query request: {}", e),
+                                request: key.into(),
+                            })
+                        }
+                    };
+                    let balance = match balances.get(&address.to_string()) {
+                        Some(v) => v,
+                        None => {
+                            return SystemResult::Err(SystemError::InvalidRequest {
+                                error: "Balance not found".to_string(),
+                                request: key.into(),
+                            })
+                        }
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(
+                        &to_binary(&balance).unwrap(),
+                    )))
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: _,
+                msg,
+            }) => match from_binary(msg).unwrap() {
+                OverseerQueryMsg::PauseStatus {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&self.pause_status)))
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            },
+            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
+                if address == "reward" && denom == "uusd" {
+                    let bank_res = BalanceResponse {
+                        amount: Coin {
+                            amount: self.reward_balance,
+                            denom: denom.to_string(),
+                        },
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(&bank_res)))
+                } else {
+                    let bank_res = BalanceResponse {
+                        amount: Coin {
+                            amount: self.other_balance,
+                            denom: denom.to_string(),
+                        },
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(&bank_res)))
+                }
+            }
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
+        WasmMockQuerier {
+            base,
+            token_querier: TokenQuerier::default(),
+            tax_querier: TaxQuerier::default(),
+            reward_balance: Uint128::zero(),
+            other_balance: Uint128::zero(),
+            pause_status: PauseStatusResponse::default(),
+        }
+    }
+
+    pub fn set_reward_balance(&mut self, balance: Uint128) {
+        self.reward_balance = balance
+    }
+
+    pub fn set_pause_status(&mut self, pause_status: PauseStatusResponse) {
+        self.pause_status = pause_status
+    }
+}
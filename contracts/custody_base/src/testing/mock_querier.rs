@@ -1,11 +1,12 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Addr, Api, BalanceResponse, BankQuery, CanonicalAddr, Coin,
+    from_binary, from_slice, to_binary, Addr, Api, BalanceResponse, BankQuery, CanonicalAddr, Coin,
     ContractResult, Decimal, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError,
     SystemResult, Uint128, WasmQuery,
 };
 use cosmwasm_storage::to_length_prefixed;
 use cw20::TokenInfoResponse;
+use moneymarket::overseer::{PauseStatusResponse, QueryMsg as OverseerQueryMsg};
 use std::collections::HashMap;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -30,6 +31,7 @@ pub struct WasmMockQuerier {
     reward_balance: Uint128,
     other_balance: Uint128,
     tax_querier: TaxQuerier,
+    pause_status: PauseStatusResponse,
 }
 
 #[derive(Clone, Default)]
@@ -154,6 +156,15 @@ impl WasmMockQuerier {
                     panic!("DO NOT ENTER HERE")
                 }
             }
+            QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: _,
+                msg,
+            }) => match from_binary(msg).unwrap() {
+                OverseerQueryMsg::PauseStatus {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&self.pause_status)))
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            },
             QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
                 if address == "reward" && denom == "uusd" {
                     let bank_res = BalanceResponse {
@@ -186,10 +197,15 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             reward_balance: Uint128::zero(),
             other_balance: Uint128::zero(),
+            pause_status: PauseStatusResponse::default(),
         }
     }
 
     pub fn set_reward_balance(&mut self, balance: Uint128) {
         self.reward_balance = balance
     }
+
+    pub fn set_pause_status(&mut self, pause_status: PauseStatusResponse) {
+        self.pause_status = pause_status
+    }
 }
@@ -12,6 +12,9 @@ use moneymarket::custody::{
     BAssetInfo, BorrowerResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
 };
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::overseer::{
+    ExecuteMsg as OverseerExecuteMsg, PausableOperation, PauseStatusResponse,
+};
 
 #[test]
 fn proper_initialization() {
@@ -73,11 +76,25 @@ fn update_config() {
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner2".to_string()),
         liquidation_contract: Some("liquidation2".to_string()),
     };
     let info = mock_info("owner", &[]);
-    execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    // ownership moves once the proposed owner accepts it
+    let propose_msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner2".to_string(),
+        expires_in: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, propose_msg).unwrap();
+    let info = mock_info("owner2", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
 
     let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
@@ -205,6 +222,98 @@ fn deposit_collateral() {
     );
 }
 
+#[test]
+fn deposit_and_lock_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "token".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "token".to_string(),
+            symbol: "token".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositAndLock {
+            borrow_amount: Some(Uint256::from(30u128)),
+            to: Some("addr0001".to_string()),
+        })
+        .unwrap(),
+    });
+
+    // failed; cannot directly execute receive message
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("token", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "100"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "overseer".to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::LockCollateralFor {
+                borrower: "addr0000".to_string(),
+                collaterals: vec![("token".to_string(), Uint256::from(100u128))],
+                borrow_amount: Some(Uint256::from(30u128)),
+                to: Some("addr0001".to_string()),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // the overseer locks the deposit right after
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(100u128),
+    };
+    let info = mock_info("overseer", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0000".to_string(),
+            balance: Uint256::from(100u128),
+            spendable: Uint256::zero(),
+        }
+    );
+}
+
 #[test]
 fn withdraw_collateral() {
     let mut deps = mock_dependencies(&[]);
@@ -340,6 +449,18 @@ fn withdraw_collateral() {
             spendable: Uint256::zero(),
         }
     );
+
+    // withdrawals are halted while paused
+    deps.querier.set_pause_status(PauseStatusResponse {
+        withdraw: true,
+        ..PauseStatusResponse::default()
+    });
+    let msg = ExecuteMsg::WithdrawCollateral { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::OperationPaused(PausableOperation::Withdraw)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
@@ -541,6 +662,110 @@ fn lock_collateral() {
     );
 }
 
+#[test]
+fn unlock_and_withdraw_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "token".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "token".to_string(),
+            symbol: "token".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+    });
+    let info = mock_info("token", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(80u128),
+    };
+    let info = mock_info("overseer", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UnlockAndWithdrawCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u128),
+    };
+
+    // only the overseer can release locked collateral
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_and_withdraw_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "50"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // spendable collateral stays in the custody
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0000".to_string(),
+            balance: Uint256::from(50u128),
+            spendable: Uint256::from(20u128),
+        }
+    );
+
+    // unlocking more than locked (which is 80 - 50 = 30)
+    let msg = ExecuteMsg::UnlockAndWithdrawCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(31u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::UnlockAmountExceedsLocked(30)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn distribute_rewards_is_no_op() {
     let mut deps = mock_dependencies(&[Coin {
@@ -664,3 +889,104 @@ fn liquidate_collateral() {
         }))]
     );
 }
+
+#[test]
+fn settle_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        collateral_token: "token".to_string(),
+        overseer_contract: "overseer".to_string(),
+        market_contract: "market".to_string(),
+        reward_contract: "reward".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        stable_denom: "uusd".to_string(),
+        basset_info: BAssetInfo {
+            name: "token".to_string(),
+            symbol: "token".to_string(),
+            decimals: 6,
+        },
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+    });
+    let info = mock_info("token", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(50u64),
+    };
+    let info = mock_info("overseer", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SettleCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(60u64),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::LiquidationAmountExceedsLocked(50)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SettleCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint256::from(30u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "settle_collateral"),
+            attr("borrower", "addr0000"),
+            attr("amount", "30"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "market".to_string(),
+                amount: Uint128::from(30u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // the settled collateral leaves the borrower balance
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Borrower {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let borrower_res: BorrowerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrower_res,
+        BorrowerResponse {
+            borrower: "addr0000".to_string(),
+            balance: Uint256::from(70u64),
+            spendable: Uint256::from(50u64),
+        }
+    );
+}
@@ -1,4 +1,5 @@
 use cosmwasm_std::{OverflowError, StdError};
+use moneymarket::overseer::PausableOperation;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,6 +13,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Operation is paused: {0}")]
+    OperationPaused(PausableOperation),
+
     #[error("Liquidation amount cannot exceed locked amount: {0}")]
     LiquidationAmountExceedsLocked(u128),
 
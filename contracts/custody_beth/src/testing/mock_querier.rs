@@ -8,6 +8,7 @@ use cosmwasm_std::{
 };
 use cosmwasm_storage::to_length_prefixed;
 use cw20::TokenInfoResponse;
+use moneymarket::overseer::{PauseStatusResponse, QueryMsg as OverseerQueryMsg};
 use std::collections::HashMap;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -33,6 +34,7 @@ pub struct WasmMockQuerier {
     reward_balance: Uint128,
     other_balance: Uint128,
     tax_querier: TaxQuerier,
+    pause_status: PauseStatusResponse,
 }
 
 #[derive(Clone, Default)]
@@ -200,12 +202,18 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart {
                 contract_addr: _,
                 msg,
-            }) => match from_binary(msg).unwrap() {
-                RewardContractQueryMsg::AccruedRewards { address: _ } => SystemResult::Ok(
+            }) => match from_binary(msg) {
+                Ok(RewardContractQueryMsg::AccruedRewards { address: _ }) => SystemResult::Ok(
                     ContractResult::from(to_binary(&BETHAccruedRewardsResponse {
                         rewards: self.accrued_rewards.rewards,
                     })),
                 ),
+                Err(_) => match from_binary(msg).unwrap() {
+                    OverseerQueryMsg::PauseStatus {} => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&self.pause_status)))
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                },
             },
             QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
                 if address == "reward" && denom == "uusd" {
@@ -240,6 +248,7 @@ impl WasmMockQuerier {
             accrued_rewards: BETHAccruedRewardsResponse::default(),
             reward_balance: Uint128::zero(),
             other_balance: Uint128::zero(),
+            pause_status: PauseStatusResponse::default(),
         }
     }
 
@@ -261,6 +270,10 @@ impl WasmMockQuerier {
         self.reward_balance = balance
     }
 
+    pub fn set_pause_status(&mut self, pause_status: PauseStatusResponse) {
+        self.pause_status = pause_status
+    }
+
     pub fn set_other_balances(&mut self, balance: Uint128) {
         self.other_balance = balance
     }
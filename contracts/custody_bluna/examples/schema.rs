@@ -7,6 +7,7 @@ use moneymarket::custody::{
     BorrowerResponse, BorrowersResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
     QueryMsg,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -21,4 +22,5 @@ fn main() {
     export_schema(&schema_for!(BorrowerResponse), &out_dir);
     export_schema(&schema_for!(BorrowersResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
 }
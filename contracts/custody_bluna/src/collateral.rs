@@ -12,6 +12,8 @@ use cosmwasm_std::{
 use cw20::Cw20ExecuteMsg;
 use moneymarket::custody::{BorrowerResponse, BorrowersResponse};
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
+use moneymarket::overseer::{ExecuteMsg as OverseerExecuteMsg, PausableOperation};
+use moneymarket::querier::query_pause_status;
 use terra_cosmwasm::TerraMsgWrapper;
 
 /// Deposit new collateral
@@ -37,6 +39,35 @@ pub fn deposit_collateral(
     ]))
 }
 
+/// Deposit new collateral and lock it in the overseer,
+/// optionally borrowing stable against it
+/// Executor: bAsset token contract
+pub fn deposit_and_lock_collateral(
+    deps: DepsMut,
+    borrower: Addr,
+    amount: Uint256,
+    borrow_amount: Option<Uint256>,
+    to: Option<Addr>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let collateral_token = deps.api.addr_humanize(&config.collateral_token)?;
+
+    let res = deposit_collateral(deps, borrower.clone(), amount)?;
+
+    // the overseer sends back LockCollateral for the deposited amount
+    Ok(res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: overseer.to_string(),
+        funds: vec![],
+        msg: to_binary(&OverseerExecuteMsg::LockCollateralFor {
+            borrower: borrower.to_string(),
+            collaterals: vec![(collateral_token.to_string(), amount)],
+            borrow_amount,
+            to: to.map(|to| to.to_string()),
+        })?,
+    })))
+}
+
 /// Withdraw spendable collateral or a specified amount of collateral
 /// Executor: borrower
 pub fn withdraw_collateral(
@@ -46,6 +77,11 @@ pub fn withdraw_collateral(
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::Withdraw) {
+        return Err(ContractError::OperationPaused(PausableOperation::Withdraw));
+    }
+
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
@@ -151,6 +187,61 @@ pub fn unlock_collateral(
     ]))
 }
 
+/// Unlock specified amount of collateral token
+/// and transfer it back to the borrower
+/// Executor: overseer
+pub fn unlock_and_withdraw_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::Withdraw) {
+        return Err(ContractError::OperationPaused(PausableOperation::Withdraw));
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let borrowed_amt = borrower_info.balance - borrower_info.spendable;
+    if amount > borrowed_amt {
+        return Err(ContractError::UnlockAmountExceedsLocked(
+            borrowed_amt.into(),
+        ));
+    }
+
+    // the unlocked collateral leaves the custody right away
+    borrower_info.balance = borrower_info.balance - amount;
+    if borrower_info.balance == Uint256::zero() {
+        remove_borrower_info(deps.storage, &borrower_raw);
+    } else {
+        store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.collateral_token)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: borrower.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "unlock_and_withdraw_collateral"),
+            attr("borrower", borrower.as_str()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
 pub fn liquidate_collateral(
     deps: DepsMut,
     info: MessageInfo,
@@ -209,6 +300,51 @@ pub fn liquidate_collateral(
         ]))
 }
 
+/// Seize locked collateral of a loan left unpaid after the shutdown
+/// deadline; the market pays it out to the depositors
+/// Executor: overseer
+pub fn settle_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let borrowed_amt = borrower_info.balance - borrower_info.spendable;
+    if amount > borrowed_amt {
+        return Err(ContractError::LiquidationAmountExceedsLocked(
+            borrowed_amt.into(),
+        ));
+    }
+
+    borrower_info.balance = borrower_info.balance - amount;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.collateral_token)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "settle_collateral"),
+            attr("borrower", borrower),
+            attr("amount", amount),
+        ]))
+}
+
 pub fn query_borrower(deps: Deps, borrower: Addr) -> StdResult<BorrowerResponse> {
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
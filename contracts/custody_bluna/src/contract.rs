@@ -6,7 +6,8 @@ use cosmwasm_std::{
 };
 
 use crate::collateral::{
-    deposit_collateral, liquidate_collateral, lock_collateral, query_borrower, query_borrowers,
+    deposit_and_lock_collateral, deposit_collateral, liquidate_collateral, lock_collateral,
+    query_borrower, query_borrowers, settle_collateral, unlock_and_withdraw_collateral,
     unlock_collateral, withdraw_collateral,
 };
 use crate::distribution::{distribute_hook, distribute_rewards, swap_to_stable_denom};
@@ -18,6 +19,7 @@ use moneymarket::common::optional_addr_validate;
 use moneymarket::custody::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
 };
+use moneymarket::ownership;
 use terra_cosmwasm::TerraMsgWrapper;
 
 pub const CLAIM_REWARDS_OPERATION: u64 = 1u64;
@@ -56,17 +58,21 @@ pub fn execute(
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
         ExecuteMsg::UpdateConfig {
-            owner,
             liquidation_contract,
         } => {
             let api = deps.api;
             update_config(
                 deps,
                 info,
-                optional_addr_validate(api, owner)?,
                 optional_addr_validate(api, liquidation_contract)?,
             )
         }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
         ExecuteMsg::LockCollateral { borrower, amount } => {
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             lock_collateral(deps, info, borrower_addr, amount)
@@ -75,6 +81,10 @@ pub fn execute(
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             unlock_collateral(deps, info, borrower_addr, amount)
         }
+        ExecuteMsg::UnlockAndWithdrawCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            unlock_and_withdraw_collateral(deps, info, borrower_addr, amount)
+        }
         ExecuteMsg::DistributeRewards {} => distribute_rewards(deps, env, info),
         ExecuteMsg::WithdrawCollateral { amount } => withdraw_collateral(deps, info, amount),
         ExecuteMsg::LiquidateCollateral {
@@ -86,6 +96,10 @@ pub fn execute(
             let borrower_addr = deps.api.addr_validate(&borrower)?;
             liquidate_collateral(deps, info, liquidator_addr, borrower_addr, amount)
         }
+        ExecuteMsg::SettleCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            settle_collateral(deps, info, borrower_addr, amount)
+        }
     }
 }
 
@@ -122,6 +136,23 @@ pub fn receive_cw20(
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
             deposit_collateral(deps, cw20_sender_addr, cw20_msg.amount.into())
         }
+        Ok(Cw20HookMsg::DepositAndLock { borrow_amount, to }) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.collateral_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let api = deps.api;
+            let cw20_sender_addr = api.addr_validate(&cw20_msg.sender)?;
+            deposit_and_lock_collateral(
+                deps,
+                cw20_sender_addr,
+                cw20_msg.amount.into(),
+                borrow_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
         _ => Err(ContractError::MissingDepositCollateralHook {}),
     }
 }
@@ -129,7 +160,6 @@ pub fn receive_cw20(
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner: Option<Addr>,
     liquidation_contract: Option<Addr>,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
@@ -138,10 +168,6 @@ pub fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
-    }
-
     if let Some(liquidation_contract) = liquidation_contract {
         config.liquidation_contract = deps.api.addr_canonicalize(liquidation_contract.as_str())?;
     }
@@ -150,10 +176,59 @@ pub fn update_config(
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
         QueryMsg::Borrower { address } => {
             let addr = deps.api.addr_validate(&address)?;
             to_binary(&query_borrower(deps, addr)?)
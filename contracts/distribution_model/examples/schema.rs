@@ -6,6 +6,7 @@ use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use moneymarket::distribution_model::{
     AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,5 +18,6 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
     export_schema(&schema_for!(AncEmissionRateResponse), &out_dir);
 }
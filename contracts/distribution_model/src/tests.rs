@@ -50,16 +50,28 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // update config and owner
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
         emission_cap: None,
         emission_floor: None,
         increment_multiplier: None,
         decrement_multiplier: None,
     };
 
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // owner changes once the proposed owner accepts
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
@@ -75,7 +87,6 @@ fn update_config() {
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         emission_cap: Some(Decimal256::from_uint256(100u64)),
         emission_floor: Some(Decimal256::from_uint256(10u64)),
         increment_multiplier: Some(Decimal256::percent(110)),
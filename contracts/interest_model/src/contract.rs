@@ -5,11 +5,13 @@ use cosmwasm_std::entry_point;
 
 use cosmwasm_bignumber::Decimal256;
 use cosmwasm_bignumber::Uint256;
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use moneymarket::common::optional_addr_validate;
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
 use moneymarket::interest_model::{
     BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
 };
+use moneymarket::ownership;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -33,32 +35,27 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::UpdateConfig {
-            owner,
             base_rate,
             interest_multiplier,
-        } => {
+        } => update_config(deps, info, base_rate, interest_multiplier),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let api = deps.api;
-            update_config(
-                deps,
-                info,
-                optional_addr_validate(api, owner)?,
-                base_rate,
-                interest_multiplier,
-            )
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
         }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
     }
 }
 
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner: Option<Addr>,
     base_rate: Option<Decimal256>,
     interest_multiplier: Option<Decimal256>,
 ) -> Result<Response, ContractError> {
@@ -67,10 +64,6 @@ pub fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
-    }
-
     if let Some(base_rate) = base_rate {
         config.base_rate = base_rate;
     }
@@ -83,10 +76,59 @@ pub fn update_config(
     Ok(Response::default())
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
         QueryMsg::BorrowRate {
             market_balance,
             total_liabilities,
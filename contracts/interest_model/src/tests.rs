@@ -64,14 +64,26 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // update config and owner
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
         base_rate: None,
         interest_multiplier: None,
     };
 
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // owner changes once the proposed owner accepts
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
@@ -85,7 +97,6 @@ fn update_config() {
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         base_rate: Some(Decimal256::percent(1)),
         interest_multiplier: Some(Decimal256::percent(1)),
     };
@@ -0,0 +1,216 @@
+use crate::error::ContractError;
+use crate::state::{read_config, store_config, Config};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+use moneymarket::kinked_interest_model::{
+    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+};
+use moneymarket::ownership;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if msg.kink > Decimal256::one() {
+        return Err(ContractError::InvalidKink {});
+    }
+
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            base_rate: msg.base_rate,
+            interest_multiplier: msg.interest_multiplier,
+            kink: msg.kink,
+            jump_multiplier: msg.jump_multiplier,
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig {
+            base_rate,
+            interest_multiplier,
+            kink,
+            jump_multiplier,
+        } => update_config(
+            deps,
+            info,
+            base_rate,
+            interest_multiplier,
+            kink,
+            jump_multiplier,
+        ),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    base_rate: Option<Decimal256>,
+    interest_multiplier: Option<Decimal256>,
+    kink: Option<Decimal256>,
+    jump_multiplier: Option<Decimal256>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(base_rate) = base_rate {
+        config.base_rate = base_rate;
+    }
+
+    if let Some(interest_multiplier) = interest_multiplier {
+        config.interest_multiplier = interest_multiplier;
+    }
+
+    if let Some(kink) = kink {
+        if kink > Decimal256::one() {
+            return Err(ContractError::InvalidKink {});
+        }
+
+        config.kink = kink;
+    }
+
+    if let Some(jump_multiplier) = jump_multiplier {
+        config.jump_multiplier = jump_multiplier;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::default())
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
+        QueryMsg::BorrowRate {
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        } => to_binary(&query_borrow_rate(
+            deps,
+            market_balance,
+            total_liabilities,
+            total_reserves,
+        )?),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        base_rate: state.base_rate,
+        interest_multiplier: state.interest_multiplier,
+        kink: state.kink,
+        jump_multiplier: state.jump_multiplier,
+    };
+
+    Ok(resp)
+}
+
+fn query_borrow_rate(
+    deps: Deps,
+    market_balance: Uint256,
+    total_liabilities: Decimal256,
+    total_reserves: Decimal256,
+) -> StdResult<BorrowRateResponse> {
+    let config: Config = read_config(deps.storage)?;
+
+    // ignore decimal parts
+    let total_value_in_market =
+        Decimal256::from_uint256(market_balance) + total_liabilities - total_reserves;
+
+    let utilization_ratio = if total_value_in_market.is_zero() {
+        Decimal256::zero()
+    } else {
+        total_liabilities / total_value_in_market
+    };
+
+    // below the kink, the rate grows linearly with utilization;
+    // above it, the excess utilization is charged with the jump multiplier
+    let rate = if utilization_ratio <= config.kink {
+        utilization_ratio * config.interest_multiplier + config.base_rate
+    } else {
+        config.kink * config.interest_multiplier
+            + (utilization_ratio - config.kink) * config.jump_multiplier
+            + config.base_rate
+    };
+
+    Ok(BorrowRateResponse { rate })
+}
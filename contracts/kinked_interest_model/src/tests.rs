@@ -0,0 +1,192 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::from_binary;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use moneymarket::kinked_interest_model::{
+    BorrowRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+};
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(10),
+        interest_multiplier: Decimal256::percent(10),
+        kink: Decimal256::percent(50),
+        jump_multiplier: Decimal256::percent(100),
+    };
+
+    let info = mock_info("addr0000", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner0000", value.owner.as_str());
+    assert_eq!("0.1", &value.base_rate.to_string());
+    assert_eq!("0.1", &value.interest_multiplier.to_string());
+    assert_eq!("0.5", &value.kink.to_string());
+    assert_eq!("1", &value.jump_multiplier.to_string());
+
+    // below the kink
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(1000000u128),
+        total_liabilities: Decimal256::from_uint256(500000u128),
+        total_reserves: Decimal256::from_uint256(100000u128),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    // utilization_ratio = 0.35714285714285714
+    // borrow_rate = 0.035714285 + 0.1
+    assert_eq!("0.135714285714285714", &value.rate.to_string());
+
+    // at the kink
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(500000u128),
+        total_liabilities: Decimal256::from_uint256(500000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    // utilization_ratio = 0.5
+    // borrow_rate = 0.05 + 0.1
+    assert_eq!("0.15", &value.rate.to_string());
+
+    // above the kink
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::from(100000u128),
+        total_liabilities: Decimal256::from_uint256(900000u128),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    // utilization_ratio = 0.9
+    // borrow_rate = 0.5 * 0.1 + 0.4 * 1 + 0.1
+    assert_eq!("0.55", &value.rate.to_string());
+
+    let query_msg = QueryMsg::BorrowRate {
+        market_balance: Uint256::zero(),
+        total_liabilities: Decimal256::zero(),
+        total_reserves: Decimal256::zero(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let value: BorrowRateResponse = from_binary(&res).unwrap();
+    assert_eq!("0.1", &value.rate.to_string());
+
+    // kink bigger than 1 is not allowed
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(10),
+        interest_multiplier: Decimal256::percent(10),
+        kink: Decimal256::percent(101),
+        jump_multiplier: Decimal256::percent(100),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidKink {}) => (),
+        _ => panic!("Must return invalid kink error"),
+    }
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(10),
+        interest_multiplier: Decimal256::percent(10),
+        kink: Decimal256::percent(50),
+        jump_multiplier: Decimal256::percent(100),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // update config and owner
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        base_rate: None,
+        interest_multiplier: None,
+        kink: None,
+        jump_multiplier: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // owner changes once the proposed owner accepts
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner0001", value.owner.as_str());
+    assert_eq!("0.1", &value.base_rate.to_string());
+    assert_eq!("0.1", &value.interest_multiplier.to_string());
+    assert_eq!("0.5", &value.kink.to_string());
+    assert_eq!("1", &value.jump_multiplier.to_string());
+
+    // update kink params
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        base_rate: None,
+        interest_multiplier: None,
+        kink: Some(Decimal256::percent(80)),
+        jump_multiplier: Some(Decimal256::percent(300)),
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("0.8", &value.kink.to_string());
+    assert_eq!("3", &value.jump_multiplier.to_string());
+
+    // kink bigger than 1 is not allowed
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        base_rate: None,
+        interest_multiplier: None,
+        kink: Some(Decimal256::percent(101)),
+        jump_multiplier: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidKink {}) => (),
+        _ => panic!("Must return invalid kink error"),
+    }
+
+    // Unauthorized err
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        base_rate: Some(Decimal256::percent(1)),
+        interest_multiplier: Some(Decimal256::percent(1)),
+        kink: None,
+        jump_multiplier: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+}
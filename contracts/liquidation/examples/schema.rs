@@ -3,10 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
+use moneymarket::access::RoleMembersResponse;
 use moneymarket::liquidation::{
     BidResponse, BidsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
     LiquidationAmountResponse, QueryMsg,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -19,6 +21,8 @@ fn main() {
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(RoleMembersResponse), &out_dir);
     export_schema(&schema_for!(BidResponse), &out_dir);
     export_schema(&schema_for!(BidsResponse), &out_dir);
     export_schema(&schema_for!(LiquidationAmountResponse), &out_dir);
@@ -9,13 +9,16 @@ use crate::state::{read_config, store_config, Config};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    attr, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult,
 };
 use cw20::Cw20ReceiveMsg;
+use moneymarket::access::{self, Role};
 use moneymarket::common::optional_addr_validate;
 use moneymarket::liquidation::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, LiquidationAmountResponse, QueryMsg,
 };
+use moneymarket::ownership;
 use moneymarket::querier::query_tax_rate;
 use moneymarket::tokens::TokensHuman;
 
@@ -53,7 +56,6 @@ pub fn execute(
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig {
-            owner,
             oracle_contract,
             stable_denom,
             safe_ratio,
@@ -66,7 +68,6 @@ pub fn execute(
             update_config(
                 deps,
                 info,
-                optional_addr_validate(api, owner)?,
                 optional_addr_validate(api, oracle_contract)?,
                 stable_denom,
                 safe_ratio,
@@ -76,6 +77,20 @@ pub fn execute(
                 price_timeframe,
             )
         }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::GrantRole { role, address } => {
+            let api = deps.api;
+            grant_role(deps, info, role, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let api = deps.api;
+            revoke_role(deps, info, role, api.addr_validate(&address)?)
+        }
         ExecuteMsg::SubmitBid {
             collateral_token,
             premium_rate,
@@ -135,7 +150,6 @@ pub fn receive_cw20(
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner: Option<Addr>,
     oracle_contract: Option<Addr>,
     stable_denom: Option<String>,
     safe_ratio: Option<Decimal256>,
@@ -145,14 +159,16 @@ pub fn update_config(
     price_timeframe: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+    // the risk admin can update every field but the oracle and the stable denom
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner
+        && (oracle_contract.is_some()
+            || stable_denom.is_some()
+            || !access::has_role(deps.storage, &Role::RiskAdmin, &sender_raw)?)
+    {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner.to_string())?;
-    }
-
     if let Some(oracle_contract) = oracle_contract {
         config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract.to_string())?;
     }
@@ -185,10 +201,97 @@ pub fn update_config(
     Ok(Response::default())
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::grant_role(deps.storage, deps.api, role, address)?)
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::revoke_role(deps.storage, deps.api, role, address)?)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
+        QueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_binary(&access::query_role_members(
+            deps,
+            role,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
         QueryMsg::LiquidationAmount {
             borrow_amount,
             borrow_limit,
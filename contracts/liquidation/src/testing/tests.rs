@@ -70,10 +70,9 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // update config and owner
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
         oracle_contract: None,
         stable_denom: None,
         safe_ratio: None,
@@ -83,6 +82,19 @@ fn update_config() {
         price_timeframe: None,
     };
 
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // owner changes once the proposed owner accepts
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
@@ -106,7 +118,6 @@ fn update_config() {
     // Update left items
     let info = mock_info("owner0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         oracle_contract: Some("oracle0001".to_string()),
         stable_denom: Some("ukrw".to_string()),
         safe_ratio: Some(Decimal256::percent(15)),
@@ -139,7 +150,6 @@ fn update_config() {
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         oracle_contract: Some("oracle0001".to_string()),
         stable_denom: Some("ukrw".to_string()),
         safe_ratio: Some(Decimal256::percent(1)),
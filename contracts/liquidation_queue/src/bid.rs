@@ -13,7 +13,8 @@ use cosmwasm_std::{
 };
 use cw20::Cw20ExecuteMsg;
 use moneymarket::oracle::PriceResponse;
-use moneymarket::querier::{deduct_tax, query_price, TimeConstraints};
+use moneymarket::overseer::PausableOperation;
+use moneymarket::querier::{deduct_tax, query_pause_status, query_price, TimeConstraints};
 
 /// Stable asset is submitted to create a bid record. If available bids for the collateral is under
 /// the threshold, the bid is activated. Bids are not used for liquidations until activated
@@ -25,6 +26,14 @@ pub fn submit_bid(
     premium_slot: u8,
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
+    let overseer = deps.api.addr_humanize(&config.overseer)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::SubmitBid) {
+        return Err(StdError::generic_err(format!(
+            "Operation is paused: {}",
+            PausableOperation::SubmitBid
+        )));
+    }
+
     let collateral_token_raw: CanonicalAddr = deps.api.addr_canonicalize(&collateral_token)?;
     let collateral_info: CollateralInfo =
         read_collateral_info(deps.storage, &collateral_token_raw)?;
@@ -14,10 +14,14 @@ use crate::state::{
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    attr, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult,
 };
 use cw20::Cw20ReceiveMsg;
+use moneymarket::access::{self, Role};
+use moneymarket::common::optional_addr_validate;
 use moneymarket::liquidation_queue::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+use moneymarket::ownership;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -52,7 +56,6 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig {
-            owner,
             oracle_contract,
             safe_ratio,
             bid_fee,
@@ -64,7 +67,6 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         } => update_config(
             deps,
             info,
-            owner,
             oracle_contract,
             safe_ratio,
             bid_fee,
@@ -74,6 +76,20 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             waiting_period,
             overseer,
         ),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::GrantRole { role, address } => {
+            let api = deps.api;
+            grant_role(deps, info, role, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let api = deps.api;
+            revoke_role(deps, info, role, api.addr_validate(&address)?)
+        }
         ExecuteMsg::WhitelistCollateral {
             collateral_token,
             bid_threshold,
@@ -143,7 +159,6 @@ pub fn receive_cw20(
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
-    owner: Option<String>,
     oracle_contract: Option<String>,
     safe_ratio: Option<Decimal256>,
     bid_fee: Option<Decimal256>,
@@ -154,14 +169,16 @@ pub fn update_config(
     overseer: Option<String>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+    // the risk admin can update every field but the contract addresses
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner
+        && (oracle_contract.is_some()
+            || overseer.is_some()
+            || !access::has_role(deps.storage, &Role::RiskAdmin, &sender_raw)?)
+    {
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
-    }
-
     if let Some(oracle_contract) = oracle_contract {
         config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract)?;
     }
@@ -200,6 +217,69 @@ pub fn update_config(
     Ok(Response::new())
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    ownership::propose_new_owner(deps.storage, deps.api, &env, owner, expires_in)
+}
+
+pub fn accept_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    ownership::cancel_ownership_proposal(deps.storage)
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    access::grant_role(deps.storage, deps.api, role, address)
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    access::revoke_role(deps.storage, deps.api, role, address)
+}
+
 pub fn whitelist_collateral(
     deps: DepsMut,
     info: MessageInfo,
@@ -254,7 +334,12 @@ pub fn update_collateral_info(
 ) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner,
+        &Role::RiskAdmin,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
         return Err(StdError::generic_err("unauthorized"));
     }
 
@@ -283,6 +368,17 @@ pub fn update_collateral_info(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
+        QueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_binary(&access::query_role_members(
+            deps,
+            role,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
         QueryMsg::LiquidationAmount {
             borrow_amount,
             borrow_limit,
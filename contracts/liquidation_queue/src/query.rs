@@ -55,9 +55,24 @@ pub fn query_liquidation_amount(
     }
 
     // calculate value of all collaterals and weights
-    let (collaterals_value, total_weight, collateral_weights, max_ltvs) =
+    let (collaterals_value, total_weight, collateral_weights, max_ltvs, liquidation_thresholds) =
         compute_collateral_weights(deps, overseer, &collaterals, &collateral_prices)?;
 
+    // Liquidation threshold check; the borrow limit is computed with max_ltv,
+    // so it is scaled up by liquidation_threshold / max_ltv of the collaterals
+    let liquidation_limit = compute_liquidation_limit(
+        borrow_limit,
+        &collaterals,
+        &collateral_prices,
+        &max_ltvs,
+        &liquidation_thresholds,
+    );
+    if borrow_amount <= liquidation_limit {
+        return Ok(LiquidationAmountResponse {
+            collaterals: vec![],
+        });
+    }
+
     // check partial liquidation condition
     let safe_ratio = if collaterals_value <= config.liquidation_threshold {
         Decimal256::zero()
@@ -151,28 +166,35 @@ pub fn query_liquidation_amount(
 
 /// The portion of collateral that liquidated from the available set is calculated
 /// based on weight = min(collateral_value, available_bids) / max_ltv
-#[allow(clippy::ptr_arg)]
+#[allow(clippy::ptr_arg, clippy::type_complexity)]
 fn compute_collateral_weights(
     deps: Deps,
     overseer: String,
     collaterals: &TokensHuman,
     collateral_prices: &Vec<Decimal256>,
-) -> StdResult<(Uint256, Uint256, Vec<Uint256>, Vec<Decimal256>)> {
+) -> StdResult<(
+    Uint256,
+    Uint256,
+    Vec<Uint256>,
+    Vec<Decimal256>,
+    Vec<Decimal256>,
+)> {
     let mut collaterals_value = Uint256::zero();
     let mut total_weight = Uint256::zero();
     let mut collateral_weights: Vec<Uint256> = vec![];
     let mut max_ltvs: Vec<Decimal256> = vec![];
+    let mut liquidation_thresholds: Vec<Decimal256> = vec![];
 
     for (collateral, price) in collaterals.iter().zip(collateral_prices.iter()) {
         let collateral_available_bids =
             read_total_bids(deps.storage, &deps.api.addr_canonicalize(&collateral.0)?)
                 .unwrap_or_default();
-        let max_ltv = query_collateral_whitelist_info(
+        let whitelist_elem = query_collateral_whitelist_info(
             &deps.querier,
             overseer.to_string(),
             collateral.0.to_string(),
-        )?
-        .max_ltv;
+        )?;
+        let max_ltv = whitelist_elem.max_ltv;
 
         let collateral_value = collateral.1 * *price;
         let weigth = collateral_value.min(collateral_available_bids) / max_ltv;
@@ -181,6 +203,7 @@ fn compute_collateral_weights(
         collaterals_value += collateral_value;
         collateral_weights.push(weigth);
         max_ltvs.push(max_ltv);
+        liquidation_thresholds.push(whitelist_elem.liquidation_threshold);
     }
 
     Ok((
@@ -188,9 +211,35 @@ fn compute_collateral_weights(
         total_weight,
         collateral_weights,
         max_ltvs,
+        liquidation_thresholds,
     ))
 }
 
+/// Loan amount above which the collaterals get liquidated
+#[allow(clippy::ptr_arg)]
+fn compute_liquidation_limit(
+    borrow_limit: Uint256,
+    collaterals: &TokensHuman,
+    collateral_prices: &Vec<Decimal256>,
+    max_ltvs: &[Decimal256],
+    liquidation_thresholds: &[Decimal256],
+) -> Uint256 {
+    let mut max_borrow_value = Decimal256::zero();
+    let mut max_liquidation_value = Decimal256::zero();
+    for (i, collateral) in collaterals.iter().enumerate() {
+        let collateral_value = Decimal256::from_uint256(collateral.1) * collateral_prices[i];
+        max_borrow_value += collateral_value * max_ltvs[i];
+        max_liquidation_value += collateral_value * liquidation_thresholds[i];
+    }
+
+    if max_borrow_value.is_zero() {
+        return borrow_limit;
+    }
+
+    Decimal256::from_uint256(borrow_limit) * max_liquidation_value / max_borrow_value
+        * Uint256::one()
+}
+
 pub fn query_bid(deps: Deps, bid_idx: Uint128) -> StdResult<BidResponse> {
     let bid: Bid = read_bid(deps.storage, bid_idx)?;
     let bid_pool: BidPool = read_bid_pool(deps.storage, &bid.collateral_token, bid.premium_slot)?;
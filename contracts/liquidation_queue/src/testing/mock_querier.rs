@@ -1,4 +1,4 @@
-use moneymarket::overseer::{WhitelistResponse, WhitelistResponseElem};
+use moneymarket::overseer::{PauseStatusResponse, WhitelistResponse, WhitelistResponseElem};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +23,8 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Query pause flags to overseer contract
+    PauseStatus {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -46,17 +48,20 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     oracle_price_querier: OraclePriceQuerier,
     collateral_querier: CollateralQuerier,
+    pause_status: PauseStatusResponse,
 }
 
 #[derive(Clone, Default)]
 pub struct CollateralQuerier {
     collaterals: HashMap<String, Decimal256>,
+    liquidation_thresholds: HashMap<String, Decimal256>,
 }
 
 impl CollateralQuerier {
     pub fn new(collaterals: &[(&String, &Decimal256)]) -> Self {
         CollateralQuerier {
             collaterals: collaterals_to_map(collaterals),
+            liquidation_thresholds: HashMap::new(),
         }
     }
 }
@@ -190,17 +195,20 @@ impl WasmMockQuerier {
                     start_after: _,
                     limit: _,
                 } => {
-                    match self
-                        .collateral_querier
-                        .collaterals
-                        .get(&collateral_token.unwrap())
-                    {
+                    let collateral_token = collateral_token.unwrap();
+                    match self.collateral_querier.collaterals.get(&collateral_token) {
                         Some(v) => {
                             SystemResult::Ok(ContractResult::from(to_binary(&WhitelistResponse {
                                 elems: vec![WhitelistResponseElem {
                                     name: "name".to_string(),
                                     symbol: "symbol".to_string(),
                                     max_ltv: *v,
+                                    liquidation_threshold: *self
+                                        .collateral_querier
+                                        .liquidation_thresholds
+                                        .get(&collateral_token)
+                                        .unwrap_or(v),
+                                    debt_ceiling: None,
                                     custody_contract: "custody0000".to_string(),
                                     collateral_token: "token0000".to_string(),
                                 }],
@@ -212,6 +220,9 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                QueryMsg::PauseStatus {} => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&self.pause_status)))
+                }
             },
             _ => self.base.handle_query(request),
         }
@@ -225,6 +236,7 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             oracle_price_querier: OraclePriceQuerier::default(),
             collateral_querier: CollateralQuerier::default(),
+            pause_status: PauseStatusResponse::default(),
         }
     }
 
@@ -244,4 +256,16 @@ impl WasmMockQuerier {
     pub fn with_collateral_max_ltv(&mut self, collaterals: &[(&String, &Decimal256)]) {
         self.collateral_querier = CollateralQuerier::new(collaterals);
     }
+
+    // liquidation thresholds default to max_ltv when not set
+    pub fn with_collateral_liquidation_threshold(
+        &mut self,
+        collaterals: &[(&String, &Decimal256)],
+    ) {
+        self.collateral_querier.liquidation_thresholds = collaterals_to_map(collaterals);
+    }
+
+    pub fn with_pause_status(&mut self, pause_status: PauseStatusResponse) {
+        self.pause_status = pause_status;
+    }
 }
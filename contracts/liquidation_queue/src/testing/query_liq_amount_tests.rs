@@ -114,6 +114,93 @@ fn partial_one_collateral_one_slot_high_ltv() {
     );
 }
 
+#[test]
+fn liquidation_threshold_above_max_ltv() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(0),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier
+        .with_collateral_max_ltv(&[(&"token0000".to_string(), &Decimal256::percent(90))]);
+    deps.querier.with_collateral_liquidation_threshold(&[(
+        &"token0000".to_string(),
+        &Decimal256::percent(95),
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(80),
+        bid_fee: Decimal256::percent(0),
+        liquidator_fee: Decimal256::percent(0),
+        liquidation_threshold: Uint256::zero(),
+        price_timeframe: 60u64,
+        waiting_period: 60u64,
+        overseer: "overseer0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::WhitelistCollateral {
+        collateral_token: "token0000".to_string(),
+        max_slot: 30u8,
+        bid_threshold: Uint256::from(10000u128), // to get instant activation
+        premium_rate_per_slot: Decimal256::percent(1),
+    };
+    let info = mock_info("owner0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "token0000".to_string(),
+        premium_slot: 0u8,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // value 20000, borrow limit = 18,000 (LTV 90%), liquidation limit = 19,000 (95%)
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(19000u64),
+        borrow_limit: Uint256::from(18000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(20000u64))],
+        collateral_prices: vec![Decimal256::percent(100)],
+    };
+
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![]
+        }
+    );
+
+    // above the liquidation limit, liquidates back to the safe ratio of the borrow limit
+    let msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(19001u64),
+        borrow_limit: Uint256::from(18000u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(20000u64))],
+        collateral_prices: vec![Decimal256::percent(100)],
+    };
+
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(16436u64))],
+        }
+    );
+}
+
 #[test]
 fn partial_one_collateral_one_slot() {
     let mut deps = mock_dependencies(&[]);
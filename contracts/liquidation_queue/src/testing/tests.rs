@@ -11,6 +11,7 @@ use moneymarket::liquidation_queue::{
     BidResponse, CollateralInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
     QueryMsg,
 };
+use moneymarket::overseer::PauseStatusResponse;
 
 #[test]
 fn proper_initialization() {
@@ -75,10 +76,9 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // update config and owner
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
         oracle_contract: None,
         safe_ratio: None,
         bid_fee: None,
@@ -89,6 +89,19 @@ fn update_config() {
         overseer: None,
     };
 
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // owner changes once the proposed owner accepts
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
@@ -114,7 +127,6 @@ fn update_config() {
     // Update left items
     let info = mock_info("owner0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         oracle_contract: Some("oracle0001".to_string()),
         safe_ratio: Some(Decimal256::percent(15)),
         bid_fee: Some(Decimal256::percent(2)),
@@ -150,7 +162,6 @@ fn update_config() {
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         oracle_contract: Some("oracle0001".to_string()),
         safe_ratio: Some(Decimal256::percent(1)),
         bid_fee: Some(Decimal256::percent(2)),
@@ -277,6 +288,28 @@ fn submit_bid() {
             scale_snapshot: Uint128::zero(),
         }
     );
+
+    // bids are rejected while paused
+    deps.querier.with_pause_status(PauseStatusResponse {
+        submit_bid: true,
+        ..PauseStatusResponse::default()
+    });
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "asset0000".to_string(),
+        premium_slot: 1u8,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Operation is paused: submit_bid")
+    );
 }
 
 #[test]
@@ -3,10 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
+use moneymarket::access::RoleMembersResponse;
 use moneymarket::market::{
-    BorrowerInfoResponse, BorrowerInfosResponse, ConfigResponse, Cw20HookMsg, EpochStateResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    BadDebtsResponse, BorrowerInfoResponse, BorrowerInfosResponse, ConfigResponse, Cw20HookMsg,
+    DepositorInfoResponse, EpochStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 use moneymarket_market::state::State;
 
 fn main() {
@@ -20,8 +22,12 @@ fn main() {
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(RoleMembersResponse), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(EpochStateResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfoResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfosResponse), &out_dir);
+    export_schema(&schema_for!(BadDebtsResponse), &out_dir);
+    export_schema(&schema_for!(DepositorInfoResponse), &out_dir);
 }
@@ -1,34 +1,55 @@
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, Storage, Uint128, WasmMsg,
 };
+use cw20::Cw20ExecuteMsg;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
-use moneymarket::overseer::BorrowLimitResponse;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::market::{
+    BadDebtsResponse, BadDebtsResponseElem, BorrowerInfoResponse, BorrowerInfosResponse, ExecuteMsg,
+};
+use moneymarket::overseer::{
+    BorrowLimitResponse, ExecuteMsg as OverseerExecuteMsg, PausableOperation,
+};
+use moneymarket::querier::{deduct_tax, query_balance, query_pause_status, query_supply};
+use moneymarket::tokens::TokensHuman;
 
-use crate::deposit::compute_exchange_rate_raw;
+use crate::deposit::{
+    compute_depositor_reward, compute_exchange_rate, compute_exchange_rate_raw,
+    compute_staked_reward,
+};
 use crate::error::ContractError;
 use crate::querier::{query_borrow_limit, query_borrow_rate, query_target_deposit_rate};
 use crate::state::{
-    read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
-    store_state, BorrowerInfo, Config, State,
+    read_bad_debts, read_borrower_info, read_borrower_infos, read_config, read_depositor_info,
+    read_depositor_state, read_flash_loan, read_isolated_borrower, read_settled_collaterals,
+    read_shutdown_height, read_state, remove_flash_loan, store_bad_debt, store_borrower_info,
+    store_depositor_info, store_depositor_state, store_flash_loan, store_isolated_borrower,
+    store_settled_collaterals, store_state, BadDebt, BorrowerInfo, Config, DepositorInfo,
+    DepositorState, FlashLoan, State,
 };
 
 pub fn borrow_stable(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    borrower: Addr,
     borrow_amount: Uint256,
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
+    if read_shutdown_height(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::Borrow) {
+        return Err(ContractError::OperationPaused(PausableOperation::Borrow));
+    }
+
     let mut state: State = read_state(deps.storage)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
@@ -37,13 +58,13 @@ pub fn borrow_stable(
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
-    compute_reward(&mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
 
     let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
     let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
         deps.as_ref(),
-        overseer,
+        overseer.clone(),
         borrower.clone(),
         Some(env.block.time.seconds()),
     )?;
@@ -68,6 +89,15 @@ pub fn borrow_stable(
     store_state(deps.storage, &state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
 
+    let isolated_debt_msg = update_isolated_debt_msg(
+        deps.storage,
+        overseer,
+        &borrower_raw,
+        &borrower,
+        &liability,
+        Some(borrow_limit_res.isolated),
+    )?;
+
     Ok(Response::new()
         .add_message(CosmosMsg::Bank(BankMsg::Send {
             to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
@@ -79,6 +109,7 @@ pub fn borrow_stable(
                 },
             )?],
         }))
+        .add_messages(isolated_debt_msg)
         .add_attributes(vec![
             attr("action", "borrow_stable"),
             attr("borrower", borrower),
@@ -86,6 +117,25 @@ pub fn borrow_stable(
         ]))
 }
 
+/// Borrow on behalf of the borrower whose collaterals
+/// were just locked through its custody contract
+/// Executor: overseer
+pub fn borrow_stable_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    borrow_amount: Uint256,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    borrow_stable(deps, env, borrower, borrow_amount, to)
+}
+
 pub fn repay_stable_from_liquidation(
     deps: DepsMut,
     env: Env,
@@ -113,10 +163,17 @@ pub fn repay_stable_from_liquidation(
         amount: (cur_balance - prev_balance).into(),
     }];
 
-    repay_stable(deps, env, info)
+    repay_stable(deps, env, info, None)
 }
 
-pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+/// Repay the loan of the borrower, the sender itself when not given;
+/// any amount above the loan is refunded to the sender
+pub fn repay_stable(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Option<Addr>,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
     // Check stable denom deposit
@@ -134,7 +191,8 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
 
     let mut state: State = read_state(deps.storage)?;
 
-    let borrower = info.sender;
+    let payer = info.sender;
+    let borrower = borrower.unwrap_or_else(|| payer.clone());
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
@@ -149,7 +207,7 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
-    compute_reward(&mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
 
     let repay_amount: Uint256;
@@ -160,7 +218,7 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
 
         // Payback left repay amount to sender
         messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: borrower.to_string(),
+            to_address: payer.to_string(),
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
@@ -179,13 +237,485 @@ pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
     store_state(deps.storage, &state)?;
 
+    messages.extend(update_isolated_debt_msg(
+        deps.storage,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        &borrower_raw,
+        &borrower,
+        &liability,
+        None,
+    )?);
+
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "repay_stable"),
+        attr("payer", payer),
+        attr("borrower", borrower),
+        attr("repay_amount", repay_amount),
+    ]))
+}
+
+/// Repay with aterra instead of stable coins. The aterra is burned
+/// at the current exchange rate, so depositors keep the same share
+/// of the pool; aterra exceeding the loan is returned to `refund_to`
+pub fn repay_stable_with_aterra(
+    deps: DepsMut,
+    env: Env,
+    payer: Addr,
+    borrower: Addr,
+    refund_to: Addr,
+    aterra_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if aterra_amount.is_zero() {
+        return Err(ContractError::ZeroRepay("aterra".to_string()));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&config, &mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    let exchange_rate = if read_shutdown_height(deps.storage)?.is_some() {
+        // after shutdown, aterra is worth its share of the stable actually held
+        let aterra_supply = query_supply(
+            deps.as_ref(),
+            deps.api.addr_humanize(&config.aterra_contract)?,
+        )?;
+        let current_balance = query_balance(
+            deps.as_ref(),
+            env.contract.address.clone(),
+            config.stable_denom.to_string(),
+        )?;
+        Decimal256::from_uint256(current_balance) / Decimal256::from_uint256(aterra_supply)
+    } else {
+        compute_exchange_rate(deps.as_ref(), &config, &state, None)?
+    };
+    let amount = Uint256::from(aterra_amount) * exchange_rate;
+
+    let repay_amount: Uint256;
+    let burn_amount: Uint256;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let aterra = deps.api.addr_humanize(&config.aterra_contract)?;
+    if liability.loan_amount < amount {
+        repay_amount = liability.loan_amount;
+        liability.loan_amount = Uint256::zero();
+
+        // round the burn up, so the payer never repays with less than the loan is worth
+        let mut burn = Uint256::one() * (Decimal256::from_uint256(repay_amount) / exchange_rate);
+        if burn * exchange_rate < repay_amount {
+            burn += Uint256::one();
+        }
+        burn_amount = burn;
+
+        // Payback left aterra
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: aterra.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: refund_to.to_string(),
+                amount: (Uint256::from(aterra_amount) - burn_amount).into(),
+            })?,
+        }));
+    } else {
+        repay_amount = amount;
+        burn_amount = Uint256::from(aterra_amount);
+        liability.loan_amount = liability.loan_amount - repay_amount;
+    }
+
+    state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
+    state.prev_aterra_supply = state.prev_aterra_supply - burn_amount;
+
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_state(deps.storage, &state)?;
+
+    if !burn_amount.is_zero() {
+        messages.insert(
+            0,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: aterra.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: burn_amount.into(),
+                })?,
+            }),
+        );
+    }
+    messages.extend(update_isolated_debt_msg(
+        deps.storage,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        &borrower_raw,
+        &borrower,
+        &liability,
+        None,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "repay_stable_with_aterra"),
+        attr("payer", payer),
         attr("borrower", borrower),
+        attr("burn_amount", burn_amount),
         attr("repay_amount", repay_amount),
     ]))
 }
 
+/// Repay stable, then have the overseer unlock the given collaterals
+/// and the custodies transfer them back to the borrower
+pub fn repay_stable_and_unlock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collaterals: TokensHuman,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let borrower = info.sender.clone();
+
+    // unlock is executed after the liability got reduced
+    Ok(
+        repay_stable(deps, env, info, None)?.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer.to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::UnlockCollateralFor {
+                borrower: borrower.to_string(),
+                collaterals,
+            })?,
+        })),
+    )
+}
+
+/// Write off the loan of a borrower the overseer found without collaterals.
+/// The stable sent by the overseer out of its interest buffer covers
+/// the loan first, then the reserves; what is left stays uncovered
+/// and lowers the aterra exchange rate
+pub fn write_off_bad_debt(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let funds: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    compute_interest(
+        deps.as_ref(),
+        &config,
+        &mut state,
+        env.block.height,
+        Some(funds),
+    )?;
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&config, &mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    let loan_amount = liability.loan_amount;
+    if loan_amount.is_zero() {
+        return Err(ContractError::NoBadDebt {});
+    }
+
+    // Any fund above the loan stays in the market for the depositors
+    let buffer_amount = std::cmp::min(funds, loan_amount);
+    let reserve_amount = std::cmp::min(
+        loan_amount - buffer_amount,
+        state.total_reserves * Uint256::one(),
+    );
+    let socialized_amount = loan_amount - buffer_amount - reserve_amount;
+
+    liability.loan_amount = Uint256::zero();
+    state.total_liabilities = if state.total_liabilities > Decimal256::from_uint256(loan_amount) {
+        state.total_liabilities - Decimal256::from_uint256(loan_amount)
+    } else {
+        Decimal256::zero()
+    };
+    state.total_reserves = state.total_reserves - Decimal256::from_uint256(reserve_amount);
+
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_state(deps.storage, &state)?;
+
+    let isolated_debt_msg = update_isolated_debt_msg(
+        deps.storage,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        &borrower_raw,
+        &borrower,
+        &liability,
+        None,
+    )?;
+
+    let bad_debt_id = store_bad_debt(
+        deps.storage,
+        &BadDebt {
+            borrower: borrower_raw,
+            loan_amount,
+            buffer_amount,
+            reserve_amount,
+            socialized_amount,
+            height: env.block.height,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(isolated_debt_msg)
+        .add_attributes(vec![
+            attr("action", "write_off_bad_debt"),
+            attr("borrower", borrower),
+            attr("bad_debt_id", bad_debt_id.to_string()),
+            attr("loan_amount", loan_amount),
+            attr("buffer_amount", buffer_amount),
+            attr("reserve_amount", reserve_amount),
+            attr("socialized_amount", socialized_amount),
+        ]))
+}
+
+/// Clear the loan of a borrower the overseer settled after shutdown. The
+/// collaterals seized at the frozen prices were sent to the market ahead
+/// of this message; aterra is burned and the rest is kept for depositors
+pub fn settle_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collaterals: TokensHuman,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if read_shutdown_height(deps.storage)?.is_none() {
+        return Err(ContractError::NotShutdown {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&config, &mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    let loan_amount = liability.loan_amount;
+    liability.loan_amount = Uint256::zero();
+    state.total_liabilities = if state.total_liabilities > Decimal256::from_uint256(loan_amount) {
+        state.total_liabilities - Decimal256::from_uint256(loan_amount)
+    } else {
+        Decimal256::zero()
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut settled_collaterals = read_settled_collaterals(deps.storage)?;
+    for (collateral_token, amount) in collaterals {
+        let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+        if collateral_token_raw == config.aterra_contract {
+            state.prev_aterra_supply = state.prev_aterra_supply - amount;
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token,
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: amount.into(),
+                })?,
+            }));
+        } else if !settled_collaterals.contains(&collateral_token_raw) {
+            settled_collaterals.push(collateral_token_raw);
+        }
+    }
+
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_state(deps.storage, &state)?;
+    store_settled_collaterals(deps.storage, &settled_collaterals)?;
+
+    messages.extend(update_isolated_debt_msg(
+        deps.storage,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        &borrower_raw,
+        &borrower,
+        &liability,
+        None,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "settle_loan"),
+        attr("borrower", borrower),
+        attr("loan_amount", loan_amount),
+    ]))
+}
+
+/// Keeps the loan attributed to isolated collaterals up to date in the overseer.
+/// Only borrowers holding an isolated collateral, as last told by the borrow
+/// limit query, are reported; the report stops once the loan is cleared
+fn update_isolated_debt_msg(
+    storage: &mut dyn Storage,
+    overseer: Addr,
+    borrower_raw: &CanonicalAddr,
+    borrower: &Addr,
+    liability: &BorrowerInfo,
+    isolated: Option<bool>,
+) -> StdResult<Option<CosmosMsg>> {
+    let reported = read_isolated_borrower(storage, borrower_raw);
+    let isolated = isolated.unwrap_or(reported);
+    if !isolated && !reported {
+        return Ok(None);
+    }
+
+    store_isolated_borrower(
+        storage,
+        borrower_raw,
+        isolated && !liability.loan_amount.is_zero(),
+    )?;
+
+    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: overseer.to_string(),
+        funds: vec![],
+        msg: to_binary(&OverseerExecuteMsg::UpdateIsolatedDebt {
+            borrower: borrower.to_string(),
+            loan_amount: liability.loan_amount,
+            interest_index: liability.interest_index,
+        })?,
+    })))
+}
+
+/// Lend stable to the sender for the duration of its callback. The loan
+/// never outlives the transaction, so total_liabilities is left untouched
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    if read_shutdown_height(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::Borrow) {
+        return Err(ContractError::OperationPaused(PausableOperation::Borrow));
+    }
+
+    if amount.is_zero() {
+        return Err(ContractError::ZeroFlashLoan(config.stable_denom));
+    }
+
+    // Settle interest while the balance is still untouched
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_reward(&config, &mut state, env.block.height);
+    store_state(deps.storage, &state)?;
+
+    let current_balance = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom.to_string(),
+    )?;
+
+    assert_max_borrow_factor(&config, &state, current_balance, amount)?;
+
+    let fee = amount * config.flash_loan_fee.unwrap_or_else(Decimal256::zero);
+    store_flash_loan(
+        deps.storage,
+        &FlashLoan {
+            prev_balance: current_balance,
+            amount,
+            fee,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: info.sender.to_string(),
+                funds: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: config.stable_denom,
+                        amount: amount.into(),
+                    },
+                )?],
+                msg,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {})?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "flash_loan"),
+            attr("borrower", info.sender),
+            attr("amount", amount),
+            attr("fee", fee),
+        ]))
+}
+
+pub fn assert_flash_loan_repaid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let flash_loan = match read_flash_loan(deps.storage)? {
+        Some(flash_loan) => flash_loan,
+        None => return Err(ContractError::Unauthorized {}),
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let current_balance = query_balance(
+        deps.as_ref(),
+        env.contract.address,
+        config.stable_denom.to_string(),
+    )?;
+    if current_balance < flash_loan.prev_balance + flash_loan.fee {
+        return Err(ContractError::FlashLoanNotRepaid(
+            (flash_loan.amount + flash_loan.fee).into(),
+        ));
+    }
+
+    remove_flash_loan(deps.storage);
+
+    let mut state: State = read_state(deps.storage)?;
+    state.total_reserves += Decimal256::from_uint256(flash_loan.fee);
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "assert_flash_loan_repaid"),
+        attr("fee", flash_loan.fee),
+    ]))
+}
+
 pub fn claim_rewards(
     deps: DepsMut,
     env: Env,
@@ -194,24 +724,37 @@ pub fn claim_rewards(
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let mut state: State = read_state(deps.storage)?;
+    let mut depositor_state: DepositorState = read_depositor_state(deps.storage)?;
 
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    let mut depositor_info: DepositorInfo = read_depositor_info(deps.storage, &borrower_raw);
 
     // Compute interest
     compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
-    compute_reward(&mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
+    compute_depositor_reward(&config, &state, &mut depositor_state, env.block.height);
+    compute_staked_reward(&depositor_state, &mut depositor_info);
 
-    let claim_amount = liability.pending_rewards * Uint256::one();
-    liability.pending_rewards = liability.pending_rewards - Decimal256::from_uint256(claim_amount);
+    let borrower_claim_amount = liability.pending_rewards * Uint256::one();
+    liability.pending_rewards =
+        liability.pending_rewards - Decimal256::from_uint256(borrower_claim_amount);
+
+    let depositor_claim_amount = depositor_info.pending_rewards * Uint256::one();
+    depositor_info.pending_rewards =
+        depositor_info.pending_rewards - Decimal256::from_uint256(depositor_claim_amount);
+
+    let claim_amount = borrower_claim_amount + depositor_claim_amount;
 
     store_state(deps.storage, &state)?;
+    store_depositor_state(deps.storage, &depositor_state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    store_depositor_info(deps.storage, &borrower_raw, &depositor_info)?;
 
     let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
         vec![CosmosMsg::Wasm(WasmMsg::Execute {
@@ -248,7 +791,9 @@ pub fn compute_interest(
     block_height: u64,
     deposit_amount: Option<Uint256>,
 ) -> StdResult<()> {
-    if state.last_interest_updated >= block_height {
+    // interest stops accruing once the market is shut down
+    if state.last_interest_updated >= block_height || read_shutdown_height(deps.storage)?.is_some()
+    {
         return Ok(());
     }
 
@@ -337,14 +882,19 @@ pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerI
     liability.interest_index = state.global_interest_index;
 }
 
-/// Compute distributed reward and update global index
-pub fn compute_reward(state: &mut State, block_height: u64) {
+/// Compute distributed reward and update global index;
+/// the depositor share of the emission is left out
+pub fn compute_reward(config: &Config, state: &mut State, block_height: u64) {
     if state.last_reward_updated >= block_height {
         return;
     }
 
+    let depositor_reward_ratio = config
+        .depositor_reward_ratio
+        .unwrap_or_else(Decimal256::zero);
     let passed_blocks = Decimal256::from_uint256(block_height - state.last_reward_updated);
-    let reward_accrued = passed_blocks * state.anc_emission_rate;
+    let reward_accrued =
+        passed_blocks * state.anc_emission_rate * (Decimal256::one() - depositor_reward_ratio);
     let borrow_amount = state.total_liabilities / state.global_interest_index;
 
     if !reward_accrued.is_zero() && !borrow_amount.is_zero() {
@@ -385,7 +935,7 @@ pub fn query_borrower_info(
     compute_interest(deps, &config, &mut state, block_height, None)?;
     compute_borrower_interest(&state, &mut borrower_info);
 
-    compute_reward(&mut state, block_height);
+    compute_reward(&config, &mut state, block_height);
     compute_borrower_reward(&state, &mut borrower_info);
 
     Ok(BorrowerInfoResponse {
@@ -412,6 +962,15 @@ pub fn query_borrower_infos(
     Ok(BorrowerInfosResponse { borrower_infos })
 }
 
+pub fn query_bad_debts(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<BadDebtsResponse> {
+    let bad_debts: Vec<BadDebtsResponseElem> = read_bad_debts(deps, start_after, limit)?;
+    Ok(BadDebtsResponse { bad_debts })
+}
+
 fn assert_max_borrow_factor(
     config: &Config,
     state: &State,
@@ -2,14 +2,25 @@
 use cosmwasm_std::entry_point;
 
 use crate::borrow::{
-    borrow_stable, claim_rewards, compute_interest, compute_interest_raw, compute_reward,
-    query_borrower_info, query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    assert_flash_loan_repaid, borrow_stable, borrow_stable_for, claim_rewards, compute_interest,
+    compute_interest_raw, compute_reward, flash_loan, query_bad_debts, query_borrower_info,
+    query_borrower_infos, repay_stable, repay_stable_and_unlock, repay_stable_from_liquidation,
+    repay_stable_with_aterra, settle_loan, write_off_bad_debt,
+};
+use crate::deposit::{
+    compute_depositor_reward, compute_exchange_rate_raw, deposit_stable, query_depositor_info,
+    redeem_stable, stake_aterra, unstake_aterra,
 };
-use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
 use crate::error::ContractError;
-use crate::querier::{query_borrow_rate, query_target_deposit_rate};
+use crate::querier::{
+    query_anc_emission_rate, query_borrow_rate, query_custody_contract, query_target_deposit_rate,
+};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+use crate::state::{
+    read_config, read_depositor_state, read_flash_loan, read_shutdown_height, read_state,
+    store_config, store_depositor_state, store_shutdown_height, store_state, Config,
+    DepositorState, State,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
@@ -18,12 +29,14 @@ use cosmwasm_std::{
 };
 use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
 
+use moneymarket::access::{self, Role};
 use moneymarket::common::optional_addr_validate;
 use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::market::{
     ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
     QueryMsg, StateResponse,
 };
+use moneymarket::ownership;
 use moneymarket::querier::{deduct_tax, query_balance, query_supply};
 use protobuf::Message;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
@@ -64,6 +77,8 @@ pub fn instantiate(
             distributor_contract: CanonicalAddr::from(vec![]),
             stable_denom: msg.stable_denom.clone(),
             max_borrow_factor: msg.max_borrow_factor,
+            flash_loan_fee: None,
+            depositor_reward_ratio: None,
         },
     )?;
 
@@ -118,6 +133,14 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // the market is locked until the flash loan got repaid, so the lent
+    // stable cannot come back as a deposit or a repay
+    if read_flash_loan(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::AssertFlashLoanRepaid {})
+    {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::RegisterContracts {
@@ -138,22 +161,39 @@ pub fn execute(
             )
         }
         ExecuteMsg::UpdateConfig {
-            owner_addr,
             interest_model,
             distribution_model,
             max_borrow_factor,
+            flash_loan_fee,
+            depositor_reward_ratio,
         } => {
             let api = deps.api;
             update_config(
                 deps,
                 env,
                 info,
-                optional_addr_validate(api, owner_addr)?,
                 optional_addr_validate(api, interest_model)?,
                 optional_addr_validate(api, distribution_model)?,
                 max_borrow_factor,
+                flash_loan_fee,
+                depositor_reward_ratio,
             )
         }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::GrantRole { role, address } => {
+            let api = deps.api;
+            grant_role(deps, info, role, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let api = deps.api;
+            revoke_role(deps, info, role, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::Shutdown {} => shutdown(deps, env, info),
         ExecuteMsg::ExecuteEpochOperations {
             deposit_rate,
             target_deposit_rate,
@@ -172,14 +212,35 @@ pub fn execute(
         ExecuteMsg::BorrowStable { borrow_amount, to } => {
             let api = deps.api;
             borrow_stable(
+                deps,
+                env,
+                info.sender,
+                borrow_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::BorrowStableFor {
+            borrower,
+            borrow_amount,
+            to,
+        } => {
+            let api = deps.api;
+            borrow_stable_for(
                 deps,
                 env,
                 info,
+                api.addr_validate(&borrower)?,
                 borrow_amount,
                 optional_addr_validate(api, to)?,
             )
         }
-        ExecuteMsg::RepayStable {} => repay_stable(deps, env, info),
+        ExecuteMsg::RepayStable { borrower } => {
+            let api = deps.api;
+            repay_stable(deps, env, info, optional_addr_validate(api, borrower)?)
+        }
+        ExecuteMsg::RepayStableAndUnlock { collaterals } => {
+            repay_stable_and_unlock(deps, env, info, collaterals)
+        }
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
@@ -193,10 +254,24 @@ pub fn execute(
                 prev_balance,
             )
         }
+        ExecuteMsg::WriteOffBadDebt { borrower } => {
+            let api = deps.api;
+            write_off_bad_debt(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::SettleLoan {
+            borrower,
+            collaterals,
+        } => {
+            let api = deps.api;
+            settle_loan(deps, env, info, api.addr_validate(&borrower)?, collaterals)
+        }
         ExecuteMsg::ClaimRewards { to } => {
             let api = deps.api;
             claim_rewards(deps, env, info, optional_addr_validate(api, to)?)
         }
+        ExecuteMsg::UnstakeATerra { amount } => unstake_aterra(deps, env, info, amount),
+        ExecuteMsg::FlashLoan { amount, msg } => flash_loan(deps, env, info, amount, msg),
+        ExecuteMsg::AssertFlashLoanRepaid {} => assert_flash_loan_repaid(deps, env, info),
     }
 }
 
@@ -240,6 +315,63 @@ pub fn receive_cw20(
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
             redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount)
         }
+        Ok(Cw20HookMsg::RepayStable { borrower }) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            let borrower = optional_addr_validate(deps.api, borrower)?
+                .unwrap_or_else(|| cw20_sender_addr.clone());
+            repay_stable_with_aterra(
+                deps,
+                env,
+                cw20_sender_addr.clone(),
+                borrower,
+                cw20_sender_addr,
+                cw20_msg.amount,
+            )
+        }
+        Ok(Cw20HookMsg::StakeATerra {}) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            stake_aterra(deps, env, cw20_sender_addr, cw20_msg.amount.into())
+        }
+        Ok(Cw20HookMsg::RepayStableFromLiquidation { borrower }) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            // only the custody of the aterra collateral can repay from a liquidation
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            let custody_contract = query_custody_contract(
+                deps.as_ref(),
+                deps.api.addr_humanize(&config.overseer_contract)?,
+                contract_addr,
+            )?;
+            if cw20_sender_addr != custody_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let borrower = deps.api.addr_validate(&borrower)?;
+            repay_stable_with_aterra(
+                deps,
+                env,
+                cw20_sender_addr,
+                borrower.clone(),
+                borrower,
+                cw20_msg.amount,
+            )
+        }
         _ => Err(ContractError::MissingRedeemStableHook {}),
     }
 }
@@ -284,26 +416,31 @@ pub fn register_contracts(
     Ok(Response::default())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
     interest_model: Option<Addr>,
     distribution_model: Option<Addr>,
     max_borrow_factor: Option<Decimal256>,
+    flash_loan_fee: Option<Decimal256>,
+    depositor_reward_ratio: Option<Decimal256>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
-    // permission check
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+    // permission check; the risk admin can only update
+    // max_borrow_factor and flash_loan_fee
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr
+        && (interest_model.is_some()
+            || distribution_model.is_some()
+            || depositor_reward_ratio.is_some()
+            || !access::has_role(deps.storage, &Role::RiskAdmin, &sender_raw)?)
+    {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
-    }
-
     if interest_model.is_some() {
         let mut state: State = read_state(deps.storage)?;
         compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
@@ -322,17 +459,141 @@ pub fn update_config(
         config.max_borrow_factor = max_borrow_factor;
     }
 
+    if let Some(flash_loan_fee) = flash_loan_fee {
+        config.flash_loan_fee = Some(flash_loan_fee);
+    }
+
+    if let Some(depositor_reward_ratio) = depositor_reward_ratio {
+        if depositor_reward_ratio > Decimal256::one() {
+            return Err(ContractError::InvalidDepositorRewardRatio {});
+        }
+
+        // rewards accrued so far are split with the previous ratio
+        let mut state: State = read_state(deps.storage)?;
+        let mut depositor_state: DepositorState = read_depositor_state(deps.storage)?;
+        compute_reward(&config, &mut state, env.block.height);
+        compute_depositor_reward(&config, &state, &mut depositor_state, env.block.height);
+        store_state(deps.storage, &state)?;
+        store_depositor_state(deps.storage, &depositor_state)?;
+
+        config.depositor_reward_ratio = Some(depositor_reward_ratio);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner_addr = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::grant_role(deps.storage, deps.api, role, address)?)
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::revoke_role(deps.storage, deps.api, role, address)?)
+}
+
+/// Settle the interest up to now and freeze the market for good;
+/// the reserves are handed over to depositors
+pub fn shutdown(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if read_shutdown_height(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_reward(&config, &mut state, env.block.height);
+
+    let released_reserves = state.total_reserves;
+    state.total_reserves = Decimal256::zero();
+
+    store_state(deps.storage, &state)?;
+    store_shutdown_height(deps.storage, env.block.height)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "shutdown"),
+        attr("released_reserves", released_reserves.to_string()),
+    ]))
+}
+
 pub fn execute_epoch_operations(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    _deposit_rate: Decimal256,
+    deposit_rate: Decimal256,
     target_deposit_rate: Decimal256,
-    _threshold_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
     distributed_interest: Uint256,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
@@ -374,7 +635,23 @@ pub fn execute_epoch_operations(
     state.prev_exchange_rate =
         compute_exchange_rate_raw(&state, aterra_supply, balance + distributed_interest);
 
-    compute_reward(&mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height);
+
+    let mut depositor_state: DepositorState = read_depositor_state(deps.storage)?;
+    compute_depositor_reward(&config, &state, &mut depositor_state, env.block.height);
+    store_depositor_state(deps.storage, &depositor_state)?;
+
+    // Query updated anc_emission_rate
+    let prev_anc_emission_rate = state.anc_emission_rate;
+    state.anc_emission_rate = query_anc_emission_rate(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.distribution_model)?,
+        deposit_rate,
+        target_deposit_rate,
+        threshold_deposit_rate,
+        state.anc_emission_rate,
+    )?
+    .emission_rate;
 
     // Compute total_reserves to fund collector contract
     // Update total_reserves and send it to collector contract
@@ -405,6 +682,7 @@ pub fn execute_epoch_operations(
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "execute_epoch_operations"),
         attr("total_reserves", total_reserves),
+        attr("prev_anc_emission_rate", prev_anc_emission_rate.to_string()),
         attr("anc_emission_rate", state.anc_emission_rate.to_string()),
     ]))
 }
@@ -413,6 +691,17 @@ pub fn execute_epoch_operations(
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
+        QueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_binary(&access::query_role_members(
+            deps,
+            role,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
         QueryMsg::State { block_height } => to_binary(&query_state(deps, env, block_height)?),
         QueryMsg::EpochState {
             block_height,
@@ -431,11 +720,23 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             deps.api.addr_validate(&borrower)?,
             block_height,
         )?),
+        QueryMsg::DepositorInfo {
+            depositor,
+            block_height,
+        } => to_binary(&query_depositor_info(
+            deps,
+            env,
+            deps.api.addr_validate(&depositor)?,
+            block_height,
+        )?),
         QueryMsg::BorrowerInfos { start_after, limit } => to_binary(&query_borrower_infos(
             deps,
             optional_addr_validate(deps.api, start_after)?,
             limit,
         )?),
+        QueryMsg::BadDebts { start_after, limit } => {
+            to_binary(&query_bad_debts(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -463,6 +764,10 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .to_string(),
         stable_denom: config.stable_denom,
         max_borrow_factor: config.max_borrow_factor,
+        flash_loan_fee: config.flash_loan_fee.unwrap_or_else(Decimal256::zero),
+        depositor_reward_ratio: config
+            .depositor_reward_ratio
+            .unwrap_or_else(Decimal256::zero),
     })
 }
 
@@ -493,7 +798,7 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
     compute_interest(deps, &config, &mut state, block_height, None)?;
 
     // Compute reward rate with given block height
-    compute_reward(&mut state, block_height);
+    compute_reward(&config, &mut state, block_height);
 
     Ok(StateResponse {
         total_liabilities: state.total_liabilities,
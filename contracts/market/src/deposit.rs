@@ -1,15 +1,23 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Attribute, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
+    Response, StdResult, Uint128, WasmMsg,
 };
 
 use crate::borrow::{compute_interest, compute_reward};
 use crate::error::ContractError;
-use crate::state::{read_config, read_state, store_state, Config, State};
+use crate::state::{
+    read_config, read_depositor_info, read_depositor_state, read_settled_collaterals,
+    read_shutdown_height, read_state, store_depositor_info, store_depositor_state, store_state,
+    Config, DepositorInfo, DepositorState, State,
+};
 
 use cw20::Cw20ExecuteMsg;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::market::DepositorInfoResponse;
+use moneymarket::overseer::PausableOperation;
+use moneymarket::querier::{
+    deduct_tax, query_balance, query_pause_status, query_supply, query_token_balance,
+};
 
 pub fn deposit_stable(
     deps: DepsMut,
@@ -18,6 +26,15 @@ pub fn deposit_stable(
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
+    if read_shutdown_height(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::Deposit) {
+        return Err(ContractError::OperationPaused(PausableOperation::Deposit));
+    }
+
     // Check base denom deposit
     let deposit_amount: Uint256 = info
         .funds
@@ -40,7 +57,7 @@ pub fn deposit_stable(
         env.block.height,
         Some(deposit_amount),
     )?;
-    compute_reward(&mut state, env.block.height);
+    compute_reward(&config, &mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
     let exchange_rate =
@@ -74,53 +91,248 @@ pub fn redeem_stable(
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    if query_pause_status(deps.as_ref(), overseer)?.is_paused(&PausableOperation::Redeem) {
+        return Err(ContractError::OperationPaused(PausableOperation::Redeem));
+    }
+
     // Update interest related state
     let mut state: State = read_state(deps.storage)?;
     compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
-    compute_reward(&mut state, env.block.height);
-
-    // Load anchor token exchange rate with updated state
-    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
-    let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
+    compute_reward(&config, &mut state, env.block.height);
 
     let current_balance = query_balance(
         deps.as_ref(),
-        env.contract.address,
+        env.contract.address.clone(),
         config.stable_denom.to_string(),
     )?;
 
-    // Assert redeem amount
-    assert_redeem_amount(&config, &state, current_balance, redeem_amount)?;
+    let mut collateral_messages: Vec<CosmosMsg> = vec![];
+    let mut collateral_attributes: Vec<Attribute> = vec![];
+    let redeem_amount = if read_shutdown_height(deps.storage)?.is_some() {
+        // after shutdown, aterra is worth its share of the stable and of the
+        // settled collaterals actually held; later repayments go to the remaining holders
+        let aterra_supply = query_supply(
+            deps.as_ref(),
+            deps.api.addr_humanize(&config.aterra_contract)?,
+        )?;
+        let share = Decimal256::from_uint256(Uint256::from(burn_amount))
+            / Decimal256::from_uint256(aterra_supply);
+
+        for collateral_token in read_settled_collaterals(deps.storage)? {
+            let collateral_token = deps.api.addr_humanize(&collateral_token)?;
+            let collateral_amount = query_token_balance(
+                deps.as_ref(),
+                collateral_token.clone(),
+                env.contract.address.clone(),
+            )? * share;
+            if collateral_amount.is_zero() {
+                continue;
+            }
+
+            collateral_messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: collateral_token.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: sender.to_string(),
+                    amount: collateral_amount.into(),
+                })?,
+            }));
+            collateral_attributes.push(attr(
+                "redeem_collateral",
+                format!("{}{}", collateral_amount, collateral_token),
+            ));
+        }
+
+        current_balance * share
+    } else {
+        // Load anchor token exchange rate with updated state
+        let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
+        let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
+
+        // Assert redeem amount
+        assert_redeem_amount(&config, &state, current_balance, redeem_amount)?;
+        redeem_amount
+    };
 
     state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
     store_state(deps.storage, &state)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: burn_amount,
+        })?,
+    })];
+    // nothing but settled collaterals may be left to redeem after shutdown
+    if !redeem_amount.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: sender.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom,
+                    amount: redeem_amount.into(),
+                },
+            )?],
+        }));
+    }
+
     Ok(Response::new()
-        .add_messages(vec![
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Burn {
-                    amount: burn_amount,
-                })?,
-            }),
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: sender.to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom,
-                        amount: redeem_amount.into(),
-                    },
-                )?],
-            }),
-        ])
+        .add_messages(messages)
+        .add_messages(collateral_messages)
         .add_attributes(vec![
             attr("action", "redeem_stable"),
             attr("burn_amount", burn_amount),
             attr("redeem_amount", redeem_amount),
+        ])
+        .add_attributes(collateral_attributes))
+}
+
+pub fn stake_aterra(
+    deps: DepsMut,
+    env: Env,
+    depositor: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    let mut depositor_state: DepositorState = read_depositor_state(deps.storage)?;
+
+    let depositor_raw = deps.api.addr_canonicalize(depositor.as_str())?;
+    let mut depositor_info: DepositorInfo = read_depositor_info(deps.storage, &depositor_raw);
+
+    // Compute ANC reward with the staked amount before this stake
+    compute_depositor_reward(&config, &state, &mut depositor_state, env.block.height);
+    compute_staked_reward(&depositor_state, &mut depositor_info);
+
+    depositor_state.total_staked += amount;
+    depositor_info.staked_amount += amount;
+
+    store_depositor_state(deps.storage, &depositor_state)?;
+    store_depositor_info(deps.storage, &depositor_raw, &depositor_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "stake_aterra"),
+        attr("depositor", depositor),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn unstake_aterra(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    let mut depositor_state: DepositorState = read_depositor_state(deps.storage)?;
+
+    let depositor_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut depositor_info: DepositorInfo = read_depositor_info(deps.storage, &depositor_raw);
+    if amount > depositor_info.staked_amount {
+        return Err(ContractError::UnstakeAmountExceedsStaked(
+            depositor_info.staked_amount.into(),
+        ));
+    }
+
+    // Compute ANC reward with the staked amount before this unstake
+    compute_depositor_reward(&config, &state, &mut depositor_state, env.block.height);
+    compute_staked_reward(&depositor_state, &mut depositor_info);
+
+    depositor_state.total_staked = depositor_state.total_staked - amount;
+    depositor_info.staked_amount = depositor_info.staked_amount - amount;
+
+    store_depositor_state(deps.storage, &depositor_state)?;
+    store_depositor_info(deps.storage, &depositor_raw, &depositor_info)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "unstake_aterra"),
+            attr("depositor", info.sender),
+            attr("amount", amount),
         ]))
 }
 
+/// Compute the depositor share of the distributed reward
+/// and update the staked aterra reward index
+pub fn compute_depositor_reward(
+    config: &Config,
+    state: &State,
+    depositor_state: &mut DepositorState,
+    block_height: u64,
+) {
+    if depositor_state.last_reward_updated >= block_height {
+        return;
+    }
+
+    let depositor_reward_ratio = config
+        .depositor_reward_ratio
+        .unwrap_or_else(Decimal256::zero);
+    let passed_blocks =
+        Decimal256::from_uint256(block_height - depositor_state.last_reward_updated);
+    let reward_accrued = passed_blocks * state.anc_emission_rate * depositor_reward_ratio;
+
+    if !reward_accrued.is_zero() && !depositor_state.total_staked.is_zero() {
+        depositor_state.global_reward_index +=
+            reward_accrued / Decimal256::from_uint256(depositor_state.total_staked);
+    }
+
+    depositor_state.last_reward_updated = block_height;
+}
+
+/// Compute reward amount a depositor received for the staked aterra
+pub(crate) fn compute_staked_reward(
+    depositor_state: &DepositorState,
+    depositor_info: &mut DepositorInfo,
+) {
+    depositor_info.pending_rewards += Decimal256::from_uint256(depositor_info.staked_amount)
+        * (depositor_state.global_reward_index - depositor_info.reward_index);
+    depositor_info.reward_index = depositor_state.global_reward_index;
+}
+
+pub fn query_depositor_info(
+    deps: Deps,
+    env: Env,
+    depositor: Addr,
+    block_height: Option<u64>,
+) -> StdResult<DepositorInfoResponse> {
+    let mut depositor_info: DepositorInfo = read_depositor_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(depositor.as_str())?,
+    );
+
+    let block_height = if let Some(block_height) = block_height {
+        block_height
+    } else {
+        env.block.height
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let state: State = read_state(deps.storage)?;
+    let mut depositor_state: DepositorState = read_depositor_state(deps.storage)?;
+
+    compute_depositor_reward(&config, &state, &mut depositor_state, block_height);
+    compute_staked_reward(&depositor_state, &mut depositor_info);
+
+    Ok(DepositorInfoResponse {
+        depositor: depositor.to_string(),
+        staked_amount: depositor_info.staked_amount,
+        reward_index: depositor_info.reward_index,
+        pending_rewards: depositor_info.pending_rewards,
+    })
+}
+
 fn assert_redeem_amount(
     config: &Config,
     state: &State,
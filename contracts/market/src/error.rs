@@ -1,4 +1,5 @@
 use cosmwasm_std::{OverflowError, StdError};
+use moneymarket::overseer::PausableOperation;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,12 +13,30 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Operation is paused: {0}")]
+    OperationPaused(PausableOperation),
+
+    #[error("Market is shut down")]
+    Shutdown {},
+
+    #[error("Market is not shut down")]
+    NotShutdown {},
+
     #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
     BorrowExceedsLimit(u128),
 
     #[error("Must deposit initial funds {0}{0}")]
     InitialFundsNotDeposited(u128, String),
 
+    #[error("Flash loan in progress")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan not repaid; {0} must be returned")]
+    FlashLoanNotRepaid(u128),
+
+    #[error("Depositor reward ratio cannot exceed 1")]
+    InvalidDepositorRewardRatio {},
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
@@ -27,12 +46,21 @@ pub enum ContractError {
     #[error("Invalid request: \"redeem stable\" message not included in request")]
     MissingRedeemStableHook {},
 
+    #[error("Borrower has no loan to write off")]
+    NoBadDebt {},
+
     #[error("Not enough {0} available; borrow demand too high")]
     NoStableAvailable(String),
 
+    #[error("Unstake amount cannot exceed the staked amount: {0}")]
+    UnstakeAmountExceedsStaked(u128),
+
     #[error("Deposit amount must be greater than 0 {0}")]
     ZeroDeposit(String),
 
     #[error("Repay amount must be greater than 0 {0}")]
     ZeroRepay(String),
+
+    #[error("Flash loan amount must be greater than 0 {0}")]
+    ZeroFlashLoan(String),
 }
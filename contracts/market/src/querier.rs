@@ -1,9 +1,11 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
+use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdError, StdResult, WasmQuery};
 
 use moneymarket::distribution_model::{AncEmissionRateResponse, QueryMsg as DistributionQueryMsg};
 use moneymarket::interest_model::{BorrowRateResponse, QueryMsg as InterestQueryMsg};
-use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse, QueryMsg as OverseerQueryMsg};
+use moneymarket::overseer::{
+    BorrowLimitResponse, ConfigResponse, QueryMsg as OverseerQueryMsg, WhitelistResponse,
+};
 
 pub fn query_borrow_rate(
     deps: Deps,
@@ -43,6 +45,27 @@ pub fn query_borrow_limit(
     Ok(borrow_limit)
 }
 
+pub fn query_custody_contract(
+    deps: Deps,
+    overseer_addr: Addr,
+    collateral_token: Addr,
+) -> StdResult<Addr> {
+    let whitelist: WhitelistResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer_addr.to_string(),
+            msg: to_binary(&OverseerQueryMsg::Whitelist {
+                collateral_token: Some(collateral_token.to_string()),
+                start_after: None,
+                limit: None,
+            })?,
+        }))?;
+
+    match whitelist.elems.first() {
+        Some(elem) => deps.api.addr_validate(&elem.custody_contract),
+        None => Err(StdError::generic_err("Collateral is not whitelisted")),
+    }
+}
+
 pub fn query_anc_emission_rate(
     deps: Deps,
     distribution_model: Addr,
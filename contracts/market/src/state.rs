@@ -3,14 +3,22 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cosmwasm_storage::{bucket, bucket_read, Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::market::{BadDebtsResponseElem, BorrowerInfoResponse};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
+const KEY_BAD_DEBT_ID: &[u8] = b"bad_debt_id";
+const KEY_SHUTDOWN_HEIGHT: &[u8] = b"shutdown_height";
+const KEY_SETTLED_COLLATERALS: &[u8] = b"settled_collaterals";
+const KEY_FLASH_LOAN: &[u8] = b"flash_loan";
+const KEY_DEPOSITOR_STATE: &[u8] = b"depositor_state";
 
 const PREFIX_LIABILITY: &[u8] = b"liability";
+const PREFIX_BAD_DEBT: &[u8] = b"bad_debt";
+const PREFIX_DEPOSITOR: &[u8] = b"depositor";
+const PREFIX_ISOLATED_BORROWER: &[u8] = b"isolated_borrower";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -24,6 +32,8 @@ pub struct Config {
     pub distributor_contract: CanonicalAddr,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub flash_loan_fee: Option<Decimal256>,
+    pub depositor_reward_ratio: Option<Decimal256>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,6 +57,41 @@ pub struct BorrowerInfo {
     pub pending_rewards: Decimal256,
 }
 
+/// ANC reward state of the staked aterra
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct DepositorState {
+    pub total_staked: Uint256,
+    pub last_reward_updated: u64,
+    pub global_reward_index: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositorInfo {
+    pub staked_amount: Uint256,
+    pub reward_index: Decimal256,
+    pub pending_rewards: Decimal256,
+}
+
+/// Flash loan waiting for its repay check
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoan {
+    pub prev_balance: Uint256,
+    pub amount: Uint256,
+    pub fee: Uint256,
+}
+
+/// Loan written off after the borrower ran out of collaterals,
+/// with how each part of it got covered
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadDebt {
+    pub borrower: CanonicalAddr,
+    pub loan_amount: Uint256,
+    pub buffer_amount: Uint256,
+    pub reserve_amount: Uint256,
+    pub socialized_amount: Uint256,
+    pub height: u64,
+}
+
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
     Singleton::new(storage, KEY_CONFIG).save(data)
 }
@@ -63,6 +108,50 @@ pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
     ReadonlySingleton::new(storage, KEY_STATE).load()
 }
 
+pub fn store_depositor_state(storage: &mut dyn Storage, data: &DepositorState) -> StdResult<()> {
+    Singleton::new(storage, KEY_DEPOSITOR_STATE).save(data)
+}
+
+pub fn read_depositor_state(storage: &dyn Storage) -> StdResult<DepositorState> {
+    Ok(ReadonlySingleton::new(storage, KEY_DEPOSITOR_STATE)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_shutdown_height(storage: &mut dyn Storage, height: u64) -> StdResult<()> {
+    Singleton::new(storage, KEY_SHUTDOWN_HEIGHT).save(&height)
+}
+
+pub fn read_shutdown_height(storage: &dyn Storage) -> StdResult<Option<u64>> {
+    ReadonlySingleton::new(storage, KEY_SHUTDOWN_HEIGHT).may_load()
+}
+
+/// Collateral tokens the market received from settled loans after shutdown
+pub fn store_settled_collaterals(
+    storage: &mut dyn Storage,
+    collaterals: &[CanonicalAddr],
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_SETTLED_COLLATERALS).save(&collaterals.to_vec())
+}
+
+pub fn read_settled_collaterals(storage: &dyn Storage) -> StdResult<Vec<CanonicalAddr>> {
+    Ok(ReadonlySingleton::new(storage, KEY_SETTLED_COLLATERALS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_flash_loan(storage: &mut dyn Storage, flash_loan: &FlashLoan) -> StdResult<()> {
+    Singleton::new(storage, KEY_FLASH_LOAN).save(flash_loan)
+}
+
+pub fn read_flash_loan(storage: &dyn Storage) -> StdResult<Option<FlashLoan>> {
+    ReadonlySingleton::new(storage, KEY_FLASH_LOAN).may_load()
+}
+
+pub fn remove_flash_loan(storage: &mut dyn Storage) {
+    Singleton::<FlashLoan>::new(storage, KEY_FLASH_LOAN).remove()
+}
+
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
@@ -83,6 +172,46 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
     }
 }
 
+/// Borrowers whose loan is reported to the overseer
+/// against their isolated collateral
+pub fn store_isolated_borrower(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    isolated: bool,
+) -> StdResult<()> {
+    if isolated {
+        bucket(storage, PREFIX_ISOLATED_BORROWER).save(borrower.as_slice(), &true)
+    } else {
+        bucket::<bool>(storage, PREFIX_ISOLATED_BORROWER).remove(borrower.as_slice());
+        Ok(())
+    }
+}
+
+pub fn read_isolated_borrower(storage: &dyn Storage, borrower: &CanonicalAddr) -> bool {
+    bucket_read(storage, PREFIX_ISOLATED_BORROWER)
+        .load(borrower.as_slice())
+        .unwrap_or(false)
+}
+
+pub fn store_depositor_info(
+    storage: &mut dyn Storage,
+    depositor: &CanonicalAddr,
+    depositor_info: &DepositorInfo,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_DEPOSITOR).save(depositor.as_slice(), depositor_info)
+}
+
+pub fn read_depositor_info(storage: &dyn Storage, depositor: &CanonicalAddr) -> DepositorInfo {
+    match bucket_read(storage, PREFIX_DEPOSITOR).load(depositor.as_slice()) {
+        Ok(v) => v,
+        _ => DepositorInfo {
+            staked_amount: Uint256::zero(),
+            reward_index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+        },
+    }
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -114,6 +243,47 @@ pub fn read_borrower_infos(
         .collect()
 }
 
+pub fn store_bad_debt(storage: &mut dyn Storage, bad_debt: &BadDebt) -> StdResult<u64> {
+    let mut bad_debt_id_singleton: Singleton<u64> = Singleton::new(storage, KEY_BAD_DEBT_ID);
+    let bad_debt_id = bad_debt_id_singleton.may_load()?.unwrap_or(0) + 1;
+    bad_debt_id_singleton.save(&bad_debt_id)?;
+
+    let mut bad_debt_bucket: Bucket<BadDebt> = Bucket::new(storage, PREFIX_BAD_DEBT);
+    bad_debt_bucket.save(&bad_debt_id.to_be_bytes(), bad_debt)?;
+
+    Ok(bad_debt_id)
+}
+
+pub fn read_bad_debts(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BadDebtsResponseElem>> {
+    let bad_debt_bucket: ReadonlyBucket<BadDebt> = bucket_read(deps.storage, PREFIX_BAD_DEBT);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|bad_debt_id| (bad_debt_id + 1).to_be_bytes().to_vec());
+
+    bad_debt_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut bad_debt_id = [0u8; 8];
+            bad_debt_id.copy_from_slice(&k);
+            Ok(BadDebtsResponseElem {
+                bad_debt_id: u64::from_be_bytes(bad_debt_id),
+                borrower: deps.api.addr_humanize(&v.borrower)?.to_string(),
+                loan_amount: v.loan_amount,
+                buffer_amount: v.buffer_amount,
+                reserve_amount: v.reserve_amount,
+                socialized_amount: v.socialized_amount,
+                height: v.height,
+            })
+        })
+        .collect()
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
     start_after.map(|addr| {
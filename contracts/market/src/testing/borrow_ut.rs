@@ -86,6 +86,8 @@ fn proper_compute_interest() {
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: None,
+        depositor_reward_ratio: None,
     };
 
     deps.querier
@@ -24,6 +24,8 @@ fn proper_compute_exchange_rate() {
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
         max_borrow_factor: Decimal256::one(),
+        flash_loan_fee: None,
+        depositor_reward_ratio: None,
     };
     deps.querier.with_token_balances(&[(
         &"at-uusd".to_string(),
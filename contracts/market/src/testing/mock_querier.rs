@@ -10,10 +10,13 @@ use cosmwasm_std::{
 use cosmwasm_storage::to_length_prefixed;
 use std::collections::HashMap;
 
-use cw20::TokenInfoResponse;
+use cw20::{BalanceResponse, TokenInfoResponse};
 use moneymarket::distribution_model::AncEmissionRateResponse;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse};
+use moneymarket::overseer::{
+    BorrowLimitResponse, ConfigResponse, PauseStatusResponse, WhitelistResponse,
+    WhitelistResponseElem,
+};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,6 +44,16 @@ pub enum QueryMsg {
     Config {},
     /// Query cw20 Token Info
     TokenInfo {},
+    /// Query cw20 Balance
+    Balance { address: String },
+    /// Query pause flags to overseer contract
+    PauseStatus {},
+    /// Query collateral whitelist to overseer contract
+    Whitelist {
+        collateral_token: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -64,6 +77,8 @@ pub struct WasmMockQuerier {
     tax_querier: TaxQuerier,
     borrow_rate_querier: BorrowRateQuerier,
     borrow_limit_querier: BorrowLimitQuerier,
+    pause_status: PauseStatusResponse,
+    custody_contracts: HashMap<String, String>,
 }
 
 #[derive(Clone, Default)]
@@ -147,12 +162,14 @@ pub(crate) fn borrower_rate_to_map(
 pub struct BorrowLimitQuerier {
     // this lets us iterate over all pairs that match the first string
     borrow_limit: HashMap<String, Uint256>,
+    isolated_borrowers: Vec<String>,
 }
 
 impl BorrowLimitQuerier {
     pub fn new(borrow_limit: &[(&String, &Uint256)]) -> Self {
         BorrowLimitQuerier {
             borrow_limit: borrow_limit_to_map(borrow_limit),
+            isolated_borrowers: vec![],
         }
     }
 }
@@ -234,6 +251,10 @@ impl WasmMockQuerier {
                     } => match self.borrow_limit_querier.borrow_limit.get(&borrower) {
                         Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
                             &BorrowLimitResponse {
+                                isolated: self
+                                    .borrow_limit_querier
+                                    .isolated_borrowers
+                                    .contains(&borrower),
                                 borrower,
                                 borrow_limit: *v,
                             },
@@ -267,6 +288,8 @@ impl WasmMockQuerier {
                             stable_denom: "uusd".to_string(),
                             epoch_period: 100u64,
                             price_timeframe: 100u64,
+                            price_twap_window: None,
+                            timelock_period: 0,
                             dyn_rate_epoch: 8600u64,
                             dyn_rate_maxchange: Decimal256::permille(5),
                             dyn_rate_yr_increase_expectation: Decimal256::permille(1),
@@ -280,6 +303,35 @@ impl WasmMockQuerier {
                             ),
                         })))
                     }
+                    QueryMsg::PauseStatus {} => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&self.pause_status)))
+                    }
+                    QueryMsg::Whitelist {
+                        collateral_token,
+                        start_after: _,
+                        limit: _,
+                    } => {
+                        let collateral_token = collateral_token.unwrap();
+                        match self.custody_contracts.get(&collateral_token) {
+                            Some(custody_contract) => SystemResult::Ok(ContractResult::from(
+                                to_binary(&WhitelistResponse {
+                                    elems: vec![WhitelistResponseElem {
+                                        name: collateral_token.clone(),
+                                        symbol: collateral_token.clone(),
+                                        max_ltv: Decimal256::percent(50),
+                                        liquidation_threshold: Decimal256::percent(50),
+                                        custody_contract: custody_contract.clone(),
+                                        collateral_token,
+                                        debt_ceiling: None,
+                                    }],
+                                }),
+                            )),
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No whitelist element exists".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
                     QueryMsg::TokenInfo {} => {
                         let balances: HashMap<String, Uint128> =
                             match self.token_querier.balances.get(contract_addr) {
@@ -300,6 +352,16 @@ impl WasmMockQuerier {
                             total_supply,
                         })))
                     }
+                    QueryMsg::Balance { address } => {
+                        let balance = match self.token_querier.balances.get(contract_addr) {
+                            Some(balances) => balances.get(&address).copied().unwrap_or_default(),
+                            None => Uint128::zero(),
+                        };
+
+                        SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                            balance,
+                        })))
+                    }
                 }
             }
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
@@ -353,6 +415,8 @@ impl WasmMockQuerier {
             tax_querier: TaxQuerier::default(),
             borrow_rate_querier: BorrowRateQuerier::default(),
             borrow_limit_querier: BorrowLimitQuerier::default(),
+            pause_status: PauseStatusResponse::default(),
+            custody_contracts: HashMap::new(),
         }
     }
 
@@ -382,4 +446,22 @@ impl WasmMockQuerier {
     pub fn with_borrow_limit(&mut self, borrow_limit: &[(&String, &Uint256)]) {
         self.borrow_limit_querier = BorrowLimitQuerier::new(borrow_limit);
     }
+
+    pub fn with_isolated_borrowers(&mut self, borrowers: &[&String]) {
+        self.borrow_limit_querier.isolated_borrowers =
+            borrowers.iter().map(|b| (*b).clone()).collect();
+    }
+
+    pub fn with_pause_status(&mut self, pause_status: PauseStatusResponse) {
+        self.pause_status = pause_status;
+    }
+
+    pub fn with_custody_contracts(&mut self, custody_contracts: &[(&String, &String)]) {
+        self.custody_contracts = custody_contracts
+            .iter()
+            .map(|(collateral_token, custody_contract)| {
+                ((*collateral_token).clone(), (*custody_contract).clone())
+            })
+            .collect();
+    }
 }
@@ -1,21 +1,27 @@
 use crate::contract::{execute, instantiate, query, reply, INITIAL_DEPOSIT_AMOUNT};
 use crate::error::ContractError;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_borrower_infos, read_state, store_state, State};
+use crate::state::{
+    read_borrower_infos, read_state, store_borrower_info, store_state, BorrowerInfo, State,
+};
 use crate::testing::mock_querier::mock_dependencies;
 
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply,
-    SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Decimal, Reply, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use moneymarket::market::{
-    BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    BadDebtsResponse, BadDebtsResponseElem, BorrowerInfoResponse, ConfigResponse, Cw20HookMsg,
+    DepositorInfoResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+use moneymarket::overseer::{
+    ExecuteMsg as OverseerExecuteMsg, PausableOperation, PauseStatusResponse,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 use moneymarket::querier::deduct_tax;
 use protobuf::Message;
 use std::str::FromStr;
@@ -121,6 +127,8 @@ fn proper_initialization() {
     assert_eq!("overseer".to_string(), config_res.overseer_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
     assert_eq!(Decimal256::one(), config_res.max_borrow_factor);
+    assert_eq!(Decimal256::zero(), config_res.flash_loan_fee);
+    assert_eq!(Decimal256::zero(), config_res.depositor_reward_ratio);
 
     let query_res = query(
         deps.as_ref(),
@@ -189,18 +197,30 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // propose new owner
     let info = mock_info("owner", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: Some("owner1".to_string()),
-        interest_model: None,
-        distribution_model: None,
-        max_borrow_factor: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner1".to_string(),
+        expires_in: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending_owner_res: PendingOwnerResponse = from_binary(&res).unwrap();
+    assert_eq!(Some("owner1".to_string()), pending_owner_res.proposed_owner);
+
+    // owner is not changed until the proposal is accepted
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner".to_string(), config_res.owner_addr);
+
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&res).unwrap();
@@ -209,10 +229,11 @@ fn update_config() {
     // update left items
     let info = mock_info("owner1", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         interest_model: Some("interest2".to_string()),
         distribution_model: Some("distribution2".to_string()),
         max_borrow_factor: Some(Decimal256::percent(100)),
+        flash_loan_fee: Some(Decimal256::permille(9)),
+        depositor_reward_ratio: Some(Decimal256::percent(20)),
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -225,14 +246,33 @@ fn update_config() {
     assert_eq!("interest2".to_string(), config_res.interest_model);
     assert_eq!("distribution2".to_string(), config_res.distribution_model);
     assert_eq!(Decimal256::percent(100), config_res.max_borrow_factor);
+    assert_eq!(Decimal256::permille(9), config_res.flash_loan_fee);
+    assert_eq!(Decimal256::percent(20), config_res.depositor_reward_ratio);
+
+    // depositor reward ratio cannot exceed 1
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: None,
+        depositor_reward_ratio: Some(Decimal256::percent(101)),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidDepositorRewardRatio {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
     // Unauthorized err
     let info = mock_info("owner", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         interest_model: None,
         distribution_model: None,
         max_borrow_factor: None,
+        flash_loan_fee: None,
+        depositor_reward_ratio: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -887,6 +927,8 @@ fn borrow_stable() {
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier
+        .with_isolated_borrowers(&[&"addr0000".to_string()]);
 
     store_state(
         deps.as_mut().storage,
@@ -932,17 +974,29 @@ fn borrow_stable() {
 
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(500000u128),
-                }
-            )
-            .unwrap()],
-        }))]
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(500000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "overseer".to_string(),
+                funds: vec![],
+                msg: to_binary(&OverseerExecuteMsg::UpdateIsolatedDebt {
+                    borrower: "addr0000".to_string(),
+                    loan_amount: Uint256::from(500000u64),
+                    interest_index: Decimal256::from_uint256(2u128),
+                })
+                .unwrap(),
+            }))
+        ]
     );
 
     assert_eq!(
@@ -1074,6 +1128,119 @@ fn borrow_stable() {
     }
 }
 
+#[test]
+fn borrow_stable_for() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStableFor {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(500000u64),
+        to: Some("addr0001".to_string()),
+    };
+
+    // only the overseer can borrow on behalf of the borrower
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable"),
+            attr("borrower", "addr0000"),
+            attr("borrow_amount", "500000")
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000u128),
+                }
+            )
+            .unwrap()],
+        }))]
+    );
+
+    // the borrower carries the liability
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(500000u64));
+}
+
 #[test]
 fn assert_max_borrow_factor() {
     let mut deps = mock_dependencies(&[Coin {
@@ -1242,6 +1409,8 @@ fn repay_stable() {
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier
+        .with_isolated_borrowers(&[&"addr0000".to_string()]);
 
     store_state(
         deps.as_mut().storage,
@@ -1267,7 +1436,7 @@ fn repay_stable() {
     env.block.height += 100;
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let msg = ExecuteMsg::RepayStable {};
+    let msg = ExecuteMsg::RepayStable { borrower: None };
     info.funds = vec![Coin {
         denom: "ukrw".to_string(),
         amount: Uint128::from(100000u128),
@@ -1308,6 +1477,7 @@ fn repay_stable() {
         res.attributes,
         vec![
             attr("action", "repay_stable"),
+            attr("payer", "addr0000"),
             attr("borrower", "addr0000"),
             attr("repay_amount", "100000"),
         ]
@@ -1329,11 +1499,12 @@ fn repay_stable() {
         denom: "uusd".to_string(),
         amount: Uint128::from(500000u128),
     }];
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
             attr("action", "repay_stable"),
+            attr("payer", "addr0000"),
             attr("borrower", "addr0000"),
             attr("repay_amount", "400000"),
         ]
@@ -1351,15 +1522,71 @@ fn repay_stable() {
         Decimal256::from_uint256(2000000u128)
     );
 
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(100000u128),
+                    }
+                )
+                .unwrap()]
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "overseer".to_string(),
+                funds: vec![],
+                msg: to_binary(&OverseerExecuteMsg::UpdateIsolatedDebt {
+                    borrower: "addr0000".to_string(),
+                    loan_amount: Uint256::zero(),
+                    interest_index: Decimal256::from_uint256(2u128),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    // repay on behalf of another borrower; the refund goes to the payer.
+    // addr0000 no longer holds an isolated collateral, so its loan is not reported
+    deps.querier.with_isolated_borrowers(&[]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::RepayStable {
+        borrower: Some("addr0000".to_string()),
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(150000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("payer", "addr0001"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+        ]
+    );
     assert_eq!(
         res.messages,
         vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
+            to_address: "addr0001".to_string(),
             amount: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
                     denom: "uusd".to_string(),
-                    amount: Uint128::from(100000u128),
+                    amount: Uint128::from(50000u128),
                 }
             )
             .unwrap()]
@@ -1368,15 +1595,11 @@ fn repay_stable() {
 }
 
 #[test]
-fn repay_stable_from_liquidation() {
+fn repay_stable_with_aterra() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
@@ -1385,7 +1608,6 @@ fn repay_stable_from_liquidation() {
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
     };
-
     let info = mock_info(
         "addr0000",
         &[Coin {
@@ -1393,8 +1615,6 @@ fn repay_stable_from_liquidation() {
             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
-
-    // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // Register anchor token contract
@@ -1417,43 +1637,507 @@ fn repay_stable_from_liquidation() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
     let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
     deps.querier
         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
 
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // make exchange rate to 200%
     store_state(
         deps.as_mut().storage,
         &State {
             total_liabilities: Decimal256::from_uint256(1000000u128),
             total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(1000000u64),
+            prev_exchange_rate: Decimal256::from_ratio(2, 1),
         },
     )
     .unwrap();
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
-    };
-
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    // repay 200000 on behalf of addr0000 with 100000 aterra
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::RepayStable {
+            borrower: Some("addr0000".to_string()),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // update balance to make repay
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![
+    let info = mock_info("at-uusd", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable_with_aterra"),
+            attr("payer", "addr0001"),
+            attr("borrower", "addr0000"),
+            attr("burn_amount", "100000"),
+            attr("repay_amount", "200000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(100000u128),
+            })
+            .unwrap()
+        }))]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(800000u128)
+    );
+    assert_eq!(state.prev_aterra_supply, Uint256::from(900000u64));
+
+    // overpay; the aterra left is returned to the payer
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(900000u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(200001u128),
+        msg: to_binary(&Cw20HookMsg::RepayStable { borrower: None }).unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable_with_aterra"),
+            attr("payer", "addr0000"),
+            attr("borrower", "addr0000"),
+            attr("burn_amount", "150000"),
+            attr("repay_amount", "300000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "at-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(150000u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "at-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(50001u128),
+                })
+                .unwrap()
+            }))
+        ]
+    );
+
+    // aterra seized by a liquidation; what is left goes back to the borrower
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(800000u128))],
+    )]);
+    deps.querier
+        .with_custody_contracts(&[(&"at-uusd".to_string(), &"custody".to_string())]);
+
+    // only the aterra custody can repay from a liquidation
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::RepayStableFromLiquidation {
+            borrower: "addr0000".to_string(),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::RepayStableFromLiquidation {
+            borrower: "addr0000".to_string(),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable_with_aterra"),
+            attr("payer", "custody"),
+            attr("borrower", "addr0000"),
+            attr("burn_amount", "50000"),
+            attr("repay_amount", "100000"),
+        ]
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(50000u128),
+            })
+            .unwrap()
+        }))
+    );
+}
+
+#[test]
+fn pause() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // the guardian paused every operation on the overseer
+    deps.querier.with_pause_status(PauseStatusResponse {
+        deposit: true,
+        redeem: true,
+        borrow: true,
+        lock: true,
+        unlock: true,
+        withdraw: true,
+        liquidate: true,
+        submit_bid: true,
+    });
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::DepositStable {},
+    );
+    match res {
+        Err(ContractError::OperationPaused(PausableOperation::Deposit)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::OperationPaused(PausableOperation::Borrow)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("at-uusd", &[]), msg);
+    match res {
+        Err(ContractError::OperationPaused(PausableOperation::Redeem)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // repay is never paused
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::RepayStable { borrower: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("payer", "addr0000"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+        ]
+    );
+}
+
+#[test]
+fn repay_stable_and_unlock() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RepayStableAndUnlock {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+    };
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(500000u128),
+    }];
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("payer", "addr0000"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "500000"),
+        ]
+    );
+
+    // collaterals are released once the loan is repaid
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "overseer".to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::UnlockCollateralFor {
+                borrower: "addr0000".to_string(),
+                collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn repay_stable_from_liquidation() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![
             Coin {
                 denom: "ukrw".to_string(),
                 amount: Uint128::from(100000u128),
@@ -1465,77 +2149,715 @@ fn repay_stable_from_liquidation() {
         ],
     );
 
-    let msg = ExecuteMsg::RepayStableFromLiquidation {
-        borrower: "addr0000".to_string(),
-        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    let msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::ZeroRepay(__uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("payer", "addr0000"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+        ]
+    );
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("payer", "addr0000"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "400000"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+}
+
+#[test]
+fn write_off_bad_debt() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.total_reserves = Decimal256::from_uint256(100000u128);
+    store_state(deps.as_mut().storage, &state).unwrap();
+    let total_liabilities = state.total_liabilities;
+
+    let msg = ExecuteMsg::WriteOffBadDebt {
+        borrower: "addr0000".to_string(),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the buffer covers 300000, the reserves 100000 and the rest is socialized
+    let info = mock_info(
+        "overseer",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(300000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "write_off_bad_debt"),
+            attr("borrower", "addr0000"),
+            attr("bad_debt_id", "1"),
+            attr("loan_amount", "500000"),
+            attr("buffer_amount", "300000"),
+            attr("reserve_amount", "100000"),
+            attr("socialized_amount", "100000"),
+        ]
+    );
+    assert_eq!(res.messages, vec![]);
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        total_liabilities - Decimal256::from_uint256(500000u128)
+    );
+    assert_eq!(state.total_reserves, Decimal256::zero());
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+    let borrower_info: BorrowerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower_info.loan_amount, Uint256::zero());
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BadDebts {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let bad_debts: BadDebtsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        bad_debts,
+        BadDebtsResponse {
+            bad_debts: vec![BadDebtsResponseElem {
+                bad_debt_id: 1,
+                borrower: "addr0000".to_string(),
+                loan_amount: Uint256::from(500000u64),
+                buffer_amount: Uint256::from(300000u64),
+                reserve_amount: Uint256::from(100000u64),
+                socialized_amount: Uint256::from(100000u64),
+                height: env.block.height,
+            }]
+        }
+    );
+
+    // nothing left to write off
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::NoBadDebt {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn shutdown() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier.with_token_balances(&[(
+        &"at-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(100000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(2000000u64),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::Shutdown {};
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "shutdown"),
+            attr("released_reserves", "100000"),
+        ]
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::Shutdown {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // interest stops accruing
+    env.block.height += 100;
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::State { block_height: None },
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(1000000u128)
+    );
+    assert_eq!(state.total_reserves, Decimal256::zero());
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::DepositStable {},
+    );
+    match res {
+        Err(ContractError::Shutdown {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(1000u64),
+        to: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::Shutdown {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // aterra is redeemed for its share of the stable held by the market
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(500000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("at-uusd", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "redeem_stable"),
+            attr("burn_amount", "500000"),
+            attr("redeem_amount", "250000"),
+        ]
+    );
+
+    // the overseer settles an unpaid loan with its collaterals
+    let borrower_raw = deps.api.addr_canonicalize("addr0001").unwrap();
+    store_borrower_info(
+        deps.as_mut().storage,
+        &borrower_raw,
+        &BorrowerInfo {
+            interest_index: Decimal256::one(),
+            reward_index: Decimal256::zero(),
+            loan_amount: Uint256::from(400000u64),
+            pending_rewards: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+
+    let settle_msg = ExecuteMsg::SettleLoan {
+        borrower: "addr0001".to_string(),
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(3000u64)),
+            ("at-uusd".to_string(), Uint256::from(1000u64)),
+        ],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        settle_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("overseer", &[]),
+        settle_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "settle_loan"),
+            attr("borrower", "addr0001"),
+            attr("loan_amount", "400000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::State { block_height: None },
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(600000u128)
+    );
+
+    // aterra is also redeemed for its share of the settled collaterals
+    deps.querier.with_token_balances(&[
+        (
+            &"at-uusd".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+        ),
+        (
+            &"bluna".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(3000u128))],
+        ),
+    ]);
+    let res = execute(deps.as_mut(), env, mock_info("at-uusd", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "redeem_stable"),
+            attr("burn_amount", "500000"),
+            attr("redeem_amount", "250000"),
+            attr("redeem_collateral", "750bluna"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "at-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(500000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(250000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(750u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn flash_loan() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: Some(Decimal256::percent(1)),
+        depositor_reward_ratio: None,
     };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    let info = mock_info("flash_borrower", &[]);
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::zero(),
+        msg: Binary::from(b"callback".to_vec()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
     match res {
-        Err(ContractError::Unauthorized {}) => (),
+        Err(ContractError::ZeroFlashLoan(_)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let info = mock_info("overseer", &[]);
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1),
+        msg: Binary::from(b"callback".to_vec()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
     match res {
-        Err(ContractError::ZeroRepay(__uusd_string)) => (),
+        Err(ContractError::MaxBorrowFactorReached(_)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // update balance to make repay
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
-        }],
-    );
-
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(100000u64),
+        msg: Binary::from(b"callback".to_vec()),
+    };
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "100000"),
+            attr("action", "flash_loan"),
+            attr("borrower", "flash_borrower"),
+            attr("amount", "100000"),
+            attr("fee", "1000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "flash_borrower".to_string(),
+                funds: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(100000u128),
+                    }
+                )
+                .unwrap()],
+                msg: Binary::from(b"callback".to_vec()),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {}).unwrap(),
+            })),
         ]
     );
 
+    // the market is locked until the loan got repaid
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::FlashLoanInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::AssertFlashLoanRepaid {};
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the loan came back, without the fee
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::FlashLoanNotRepaid(amount)) => assert_eq!(amount, 101000u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000u128),
         }],
     );
-
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "400000"),
+            attr("action", "assert_flash_loan_repaid"),
+            attr("fee", "1000"),
         ]
     );
 
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(100000u128),
-                }
-            )
-            .unwrap()]
-        }))]
-    );
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_liabilities, Decimal256::zero());
+    assert_eq!(state.total_reserves, Decimal256::from_uint256(1000u64));
+
+    // the market is unlocked again
+    let msg = ExecuteMsg::FlashLoan {
+        amount: Uint256::from(100000u64),
+        msg: Binary::from(b"callback".to_vec()),
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("flash_borrower", &[]), msg).unwrap();
 }
 
 #[test]
@@ -1669,6 +2991,208 @@ fn claim_rewards() {
     );
 }
 
+#[test]
+fn stake_aterra() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("at-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // only the owner can set the depositor reward ratio
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        flash_loan_fee: None,
+        depositor_reward_ratio: Some(Decimal256::percent(20)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // only aterra can be staked
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakeATerra {}).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("token", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("at-uusd", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "stake_aterra"),
+            attr("depositor", "addr0000"),
+            attr("amount", "100"),
+        ]
+    );
+
+    // 100 blocks passed; 20% of the emission goes to the staked aterra
+    env.block.height += 100;
+    let res: DepositorInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::DepositorInfo {
+                depositor: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DepositorInfoResponse {
+            depositor: "addr0000".to_string(),
+            staked_amount: Uint256::from(100u64),
+            reward_index: Decimal256::percent(20),
+            pending_rewards: Decimal256::from_uint256(20u64),
+        }
+    );
+
+    // cannot unstake more than staked
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UnstakeATerra {
+        amount: Uint256::from(101u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnstakeAmountExceedsStaked(100)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UnstakeATerra {
+        amount: Uint256::from(50u64),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "at-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // 100 more blocks passed with half the stake
+    env.block.height += 100;
+    let msg = ExecuteMsg::ClaimRewards { to: None };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "distributor".to_string(),
+            funds: vec![],
+            msg: to_binary(&FaucetExecuteMsg::Spend {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(40u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res: DepositorInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::DepositorInfo {
+                depositor: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.staked_amount, Uint256::from(50u64));
+    assert_eq!(res.reward_index, Decimal256::percent(60));
+    assert_eq!(res.pending_rewards, Decimal256::zero());
+}
+
 #[test]
 fn execute_epoch_operations() {
     let mut deps = mock_dependencies(&[Coin {
@@ -1773,6 +3297,15 @@ fn execute_epoch_operations() {
             }],
         }))]
     );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_epoch_operations"),
+            attr("total_reserves", "3000"),
+            attr("prev_anc_emission_rate", "1"),
+            attr("anc_emission_rate", "5"),
+        ]
+    );
 
     let state = read_state(deps.as_ref().storage).unwrap();
     assert_eq!(
@@ -1784,7 +3317,7 @@ fn execute_epoch_operations() {
             last_reward_updated: env.block.height,
             global_interest_index: Decimal256::from_uint256(2u64),
             global_reward_index: Decimal256::from_str("0.0001").unwrap(),
-            anc_emission_rate: Decimal256::one(),
+            anc_emission_rate: Decimal256::from_uint256(5u64),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -1841,7 +3374,7 @@ fn execute_epoch_operations() {
             last_reward_updated: env.block.height,
             global_interest_index: Decimal256::from_uint256(2u64),
             global_reward_index: Decimal256::from_str("0.0001").unwrap(),
-            anc_emission_rate: Decimal256::one(),
+            anc_emission_rate: Decimal256::from_uint256(5u64),
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
         }
@@ -1,16 +1,29 @@
 use crate::error::ContractError;
 use crate::state::{
-    read_config, read_feeder, read_price, read_prices, store_config, store_feeder, store_price,
-    Config, PriceInfo,
+    migrate_feeders, read_asset_config, read_config, read_feeder_price, read_feeders,
+    read_frozen_price, read_last_price_snapshot, read_price, read_prices, remove_feeder_price,
+    remove_frozen_price, remove_price_snapshots_before, store_asset_config, store_config,
+    store_feeder_price, store_feeders, store_frozen_price, store_price, store_price_snapshot,
+    AssetConfig, Config, PriceInfo, PriceSnapshot,
 };
 use cosmwasm_bignumber::Decimal256;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Storage,
+};
+use moneymarket::access::{self, Role};
+use moneymarket::common::optional_addr_validate;
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    AssetConfigResponse, ConfigResponse, ExecuteMsg, FeederPricesResponse,
+    FeederPricesResponseElem, FeederResponse, FeedersResponse, FrozenPriceResponse, InstantiateMsg,
+    MigrateMsg, PriceResponse, PricesResponse, PricesResponseElem, QueryMsg,
 };
+use moneymarket::ownership;
+
+/// Longest TWAP window supported; older price snapshots are pruned
+pub const MAX_TWAP_WINDOW: u64 = 86400;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -38,28 +51,120 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::GrantRole { role, address } => {
+            let api = deps.api;
+            grant_role(deps, info, role, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let api = deps.api;
+            revoke_role(deps, info, role, api.addr_validate(&address)?)
+        }
         ExecuteMsg::RegisterFeeder { asset, feeder } => register_feeder(deps, info, asset, feeder),
+        ExecuteMsg::DeregisterFeeder { asset, feeder } => {
+            deregister_feeder(deps, info, asset, feeder)
+        }
+        ExecuteMsg::UpdateAssetConfig {
+            asset,
+            quorum,
+            max_staleness,
+            max_price_change,
+            min_price,
+            max_price,
+        } => update_asset_config(
+            deps,
+            info,
+            asset,
+            quorum,
+            max_staleness,
+            max_price_change,
+            min_price,
+            max_price,
+        ),
         ExecuteMsg::FeedPrice { prices } => feed_prices(deps, env, info, prices),
+        ExecuteMsg::ConfirmPrice { asset } => confirm_price(deps, env, info, asset),
     }
 }
 
-pub fn update_config(
+pub fn propose_new_owner(
     deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    owner: Option<String>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
+    config.owner = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    store_config(deps.storage, &config)?;
-    Ok(Response::default())
+    Ok(access::grant_role(deps.storage, deps.api, role, address)?)
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::revoke_role(deps.storage, deps.api, role, address)?)
 }
 
 pub fn register_feeder(
@@ -69,11 +174,23 @@ pub fn register_feeder(
     feeder: String,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner,
+        &Role::FeederAdmin,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
         return Err(ContractError::Unauthorized {});
     }
 
-    store_feeder(deps.storage, &asset, &deps.api.addr_canonicalize(&feeder)?)?;
+    let feeder_raw = deps.api.addr_canonicalize(&feeder)?;
+    let mut feeders = read_feeders(deps.storage, &asset);
+    if feeders.contains(&feeder_raw) {
+        return Err(ContractError::FeederAlreadyRegistered {});
+    }
+
+    feeders.push(feeder_raw);
+    store_feeders(deps.storage, &asset, &feeders)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register_feeder"),
@@ -82,6 +199,101 @@ pub fn register_feeder(
     ]))
 }
 
+pub fn deregister_feeder(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    feeder: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner,
+        &Role::FeederAdmin,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let feeder_raw = deps.api.addr_canonicalize(&feeder)?;
+    let mut feeders = read_feeders(deps.storage, &asset);
+    if !feeders.contains(&feeder_raw) {
+        return Err(ContractError::FeederNotRegistered {});
+    }
+
+    feeders.retain(|f| *f != feeder_raw);
+    store_feeders(deps.storage, &asset, &feeders)?;
+    remove_feeder_price(deps.storage, &asset, &feeder_raw);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deregister_feeder"),
+        attr("asset", asset),
+        attr("feeder", feeder),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_asset_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    quorum: Option<u32>,
+    max_staleness: Option<Option<u64>>,
+    max_price_change: Option<Option<Decimal256>>,
+    min_price: Option<Option<Decimal256>>,
+    max_price: Option<Option<Decimal256>>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner,
+        &Role::RiskAdmin,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut asset_config: AssetConfig = read_asset_config(deps.storage, &asset)?;
+
+    if let Some(quorum) = quorum {
+        if quorum == 0 {
+            return Err(ContractError::InvalidQuorum {});
+        }
+
+        asset_config.quorum = quorum;
+    }
+
+    if let Some(max_staleness) = max_staleness {
+        asset_config.max_staleness = max_staleness;
+    }
+
+    if let Some(max_price_change) = max_price_change {
+        asset_config.max_price_change = max_price_change;
+    }
+
+    if let Some(min_price) = min_price {
+        asset_config.min_price = min_price;
+    }
+
+    if let Some(max_price) = max_price {
+        asset_config.max_price = max_price;
+    }
+
+    if let (Some(min_price), Some(max_price)) = (asset_config.min_price, asset_config.max_price) {
+        if min_price > max_price {
+            return Err(ContractError::InvalidPriceBounds {});
+        }
+    }
+
+    store_asset_config(deps.storage, &asset, &asset_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_asset_config"),
+        attr("asset", asset),
+        attr("quorum", asset_config.quorum.to_string()),
+    ]))
+}
+
 pub fn feed_prices(
     deps: DepsMut,
     env: Env,
@@ -95,36 +307,229 @@ pub fn feed_prices(
         let price: Decimal256 = price.1;
 
         // Check feeder permission
-        let feeder = read_feeder(deps.storage, &asset)?;
-        if feeder != sender_raw {
+        let feeders = read_feeders(deps.storage, &asset);
+        if !feeders.contains(&sender_raw) {
             return Err(ContractError::Unauthorized {});
         }
 
+        if price.is_zero() {
+            return Err(ContractError::InvalidZeroPrice {});
+        }
+
         attributes.push(attr("asset", asset.to_string()));
         attributes.push(attr("price", price.to_string()));
 
-        store_price(
+        store_feeder_price(
             deps.storage,
             &asset,
+            &sender_raw,
             &PriceInfo {
                 last_updated_time: env.block.time.seconds(),
                 price,
             },
         )?;
+
+        // Update the asset price only when enough fresh submissions exist,
+        // otherwise the last aggregated price is left to go stale
+        if let Some(price_info) =
+            aggregate_price(deps.storage, &asset, &feeders, env.block.time.seconds())?
+        {
+            // Prices breaking the asset bounds are held back until
+            // the owner or a guardian confirms them
+            if is_price_out_of_bounds(deps.storage, &asset, price_info.price)? {
+                store_frozen_price(deps.storage, &asset, &price_info)?;
+                attributes.push(attr("frozen", asset.to_string()));
+            } else if read_frozen_price(deps.storage, &asset).is_some() {
+                store_frozen_price(deps.storage, &asset, &price_info)?;
+            } else {
+                accept_price(deps.storage, &asset, &price_info, env.block.time.seconds())?;
+            }
+        }
     }
 
     Ok(Response::new().add_attributes(attributes))
 }
 
+pub fn confirm_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !access::is_owner_or_role(deps.storage, &config.owner, &Role::Guardian, &sender_raw)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let price_info = match read_frozen_price(deps.storage, &asset) {
+        Some(v) => v,
+        None => return Err(ContractError::PriceNotFrozen {}),
+    };
+
+    accept_price(deps.storage, &asset, &price_info, env.block.time.seconds())?;
+    remove_frozen_price(deps.storage, &asset);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "confirm_price"),
+        attr("asset", asset),
+        attr("price", price_info.price.to_string()),
+    ]))
+}
+
+/// Store the accepted price of the asset and accumulate
+/// the previous price over the time it was effective
+fn accept_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    price_info: &PriceInfo,
+    block_time: u64,
+) -> StdResult<()> {
+    let cumulative_price = match read_last_price_snapshot(storage, asset, None)? {
+        Some((time, snapshot)) => {
+            snapshot.cumulative_price + snapshot.price * Decimal256::from_uint256(block_time - time)
+        }
+        None => Decimal256::zero(),
+    };
+
+    store_price(storage, asset, price_info)?;
+    store_price_snapshot(
+        storage,
+        asset,
+        block_time,
+        &PriceSnapshot {
+            cumulative_price,
+            price: price_info.price,
+        },
+    )?;
+
+    if block_time > MAX_TWAP_WINDOW {
+        remove_price_snapshots_before(storage, asset, block_time - MAX_TWAP_WINDOW)?;
+    }
+
+    Ok(())
+}
+
+/// Check the aggregated price against the absolute bounds and
+/// the max change ratio from the last accepted price of the asset
+fn is_price_out_of_bounds(
+    storage: &dyn Storage,
+    asset: &str,
+    price: Decimal256,
+) -> StdResult<bool> {
+    let asset_config: AssetConfig = read_asset_config(storage, asset)?;
+
+    if let Some(min_price) = asset_config.min_price {
+        if price < min_price {
+            return Ok(true);
+        }
+    }
+
+    if let Some(max_price) = asset_config.max_price {
+        if price > max_price {
+            return Ok(true);
+        }
+    }
+
+    if let (Some(max_price_change), Ok(last_price)) =
+        (asset_config.max_price_change, read_price(storage, asset))
+    {
+        let price_change = if price > last_price.price {
+            price - last_price.price
+        } else {
+            last_price.price - price
+        };
+
+        if price_change > last_price.price * max_price_change {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Compute the median of the fresh feeder submissions of the asset.
+/// The aggregated price is timestamped with the oldest submission forming the median,
+/// so a stuck feeder outside the median does not age the price.
+/// Returns `None` when the # of fresh submissions is below the quorum.
+fn aggregate_price(
+    storage: &dyn Storage,
+    asset: &str,
+    feeders: &[CanonicalAddr],
+    block_time: u64,
+) -> StdResult<Option<PriceInfo>> {
+    let asset_config: AssetConfig = read_asset_config(storage, asset)?;
+
+    let mut submissions: Vec<PriceInfo> = feeders
+        .iter()
+        .filter_map(|feeder| read_feeder_price(storage, asset, feeder))
+        .filter(|submission| match asset_config.max_staleness {
+            Some(max_staleness) => submission.last_updated_time + max_staleness >= block_time,
+            None => true,
+        })
+        .collect();
+
+    if submissions.is_empty() || (submissions.len() as u32) < asset_config.quorum {
+        return Ok(None);
+    }
+
+    submissions.sort_by_key(|submission| submission.price);
+
+    let mid = submissions.len() / 2;
+    let (price, last_updated_time) = if submissions.len() % 2 == 1 {
+        (submissions[mid].price, submissions[mid].last_updated_time)
+    } else {
+        (
+            (submissions[mid - 1].price + submissions[mid].price) / Decimal256::from_uint256(2u64),
+            std::cmp::min(
+                submissions[mid - 1].last_updated_time,
+                submissions[mid].last_updated_time,
+            ),
+        )
+    };
+
+    Ok(Some(PriceInfo {
+        price,
+        last_updated_time,
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_feeders(deps.storage)?;
+
+    Ok(Response::default())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
+        QueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_binary(&access::query_role_members(
+            deps,
+            role,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
         QueryMsg::Feeder { asset } => to_binary(&query_feeder(deps, asset)?),
+        QueryMsg::Feeders { asset } => to_binary(&query_feeders(deps, asset)?),
+        QueryMsg::FeederPrices { asset } => to_binary(&query_feeder_prices(deps, asset)?),
+        QueryMsg::AssetConfig { asset } => to_binary(&query_asset_config(deps, asset)?),
+        QueryMsg::FrozenPrice { asset } => to_binary(&query_frozen_price(deps, asset)?),
         QueryMsg::Price { base, quote } => to_binary(&query_price(deps, base, quote)?),
         QueryMsg::Prices { start_after, limit } => {
             to_binary(&query_prices(deps, start_after, limit)?)
         }
+        QueryMsg::Twap {
+            base,
+            quote,
+            window_seconds,
+        } => to_binary(&query_twap(deps, env, base, quote, window_seconds)?),
     }
 }
 
@@ -139,17 +544,77 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 }
 
 fn query_feeder(deps: Deps, asset: String) -> StdResult<FeederResponse> {
-    let feeder = read_feeder(deps.storage, &asset)?;
-    let resp = FeederResponse {
+    let feeder = match read_feeders(deps.storage, &asset).first() {
+        Some(v) => deps.api.addr_humanize(v)?.to_string(),
+        None => {
+            return Err(StdError::generic_err(
+                "No feeder data for the specified asset exist",
+            ))
+        }
+    };
+
+    Ok(FeederResponse { asset, feeder })
+}
+
+fn query_feeders(deps: Deps, asset: String) -> StdResult<FeedersResponse> {
+    let feeders = read_feeders(deps.storage, &asset);
+    let resp = FeedersResponse {
+        feeders: feeders
+            .iter()
+            .map(|feeder| Ok(deps.api.addr_humanize(feeder)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
         asset,
-        feeder: deps.api.addr_humanize(&feeder)?.to_string(),
     };
 
     Ok(resp)
 }
 
+fn query_feeder_prices(deps: Deps, asset: String) -> StdResult<FeederPricesResponse> {
+    let feeders = read_feeders(deps.storage, &asset);
+    let mut prices: Vec<FeederPricesResponseElem> = vec![];
+    for feeder in feeders.iter() {
+        if let Some(price_info) = read_feeder_price(deps.storage, &asset, feeder) {
+            prices.push(FeederPricesResponseElem {
+                feeder: deps.api.addr_humanize(feeder)?.to_string(),
+                price: price_info.price,
+                last_updated_time: price_info.last_updated_time,
+            });
+        }
+    }
+
+    Ok(FeederPricesResponse { asset, prices })
+}
+
+fn query_asset_config(deps: Deps, asset: String) -> StdResult<AssetConfigResponse> {
+    let asset_config: AssetConfig = read_asset_config(deps.storage, &asset)?;
+    Ok(AssetConfigResponse {
+        asset,
+        quorum: asset_config.quorum,
+        max_staleness: asset_config.max_staleness,
+        max_price_change: asset_config.max_price_change,
+        min_price: asset_config.min_price,
+        max_price: asset_config.max_price,
+    })
+}
+
+fn query_frozen_price(deps: Deps, asset: String) -> StdResult<FrozenPriceResponse> {
+    let price_info = match read_frozen_price(deps.storage, &asset) {
+        Some(v) => v,
+        None => return Err(StdError::generic_err("Price of the asset is not frozen")),
+    };
+
+    Ok(FrozenPriceResponse {
+        asset,
+        price: price_info.price,
+        last_updated_time: price_info.last_updated_time,
+    })
+}
+
 fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceResponse> {
     let config: Config = read_config(deps.storage)?;
+    assert_not_frozen(deps, &base)?;
+    assert_not_frozen(deps, &quote)?;
+
     let quote_price = if config.base_asset == quote {
         PriceInfo {
             price: Decimal256::one(),
@@ -175,6 +640,101 @@ fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceRespon
     })
 }
 
+fn query_twap(
+    deps: Deps,
+    env: Env,
+    base: String,
+    quote: String,
+    window_seconds: u64,
+) -> StdResult<PriceResponse> {
+    if window_seconds == 0 || window_seconds > MAX_TWAP_WINDOW {
+        return Err(StdError::generic_err(format!(
+            "TWAP window must be between 1 and {} seconds",
+            MAX_TWAP_WINDOW
+        )));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    assert_not_frozen(deps, &base)?;
+    assert_not_frozen(deps, &quote)?;
+
+    let block_time = env.block.time.seconds();
+    let quote_price = if config.base_asset == quote {
+        PriceInfo {
+            price: Decimal256::one(),
+            last_updated_time: 9999999999,
+        }
+    } else {
+        compute_twap(deps, &quote, block_time, window_seconds)?
+    };
+
+    let base_price = if config.base_asset == base {
+        PriceInfo {
+            price: Decimal256::one(),
+            last_updated_time: 9999999999,
+        }
+    } else {
+        compute_twap(deps, &base, block_time, window_seconds)?
+    };
+
+    Ok(PriceResponse {
+        rate: base_price.price / quote_price.price,
+        last_updated_base: base_price.last_updated_time,
+        last_updated_quote: quote_price.last_updated_time,
+    })
+}
+
+/// Average the accepted prices of the asset over [block_time - window_seconds, block_time]
+/// using the cumulative prices of the snapshots surrounding the window
+fn compute_twap(
+    deps: Deps,
+    asset: &str,
+    block_time: u64,
+    window_seconds: u64,
+) -> StdResult<PriceInfo> {
+    let window_start = block_time.saturating_sub(window_seconds);
+    let (start_time, start_snapshot) =
+        match read_last_price_snapshot(deps.storage, asset, Some(window_start))? {
+            Some(v) => v,
+            None => {
+                return Err(StdError::generic_err(
+                    "Not enough price history for the TWAP window",
+                ))
+            }
+        };
+
+    let (last_time, last_snapshot) = match read_last_price_snapshot(deps.storage, asset, None)? {
+        Some(v) => v,
+        None => {
+            return Err(StdError::generic_err(
+                "No price data for the specified asset exist",
+            ))
+        }
+    };
+
+    let cumulative_price_end = last_snapshot.cumulative_price
+        + last_snapshot.price * Decimal256::from_uint256(block_time - last_time);
+    let cumulative_price_start = start_snapshot.cumulative_price
+        + start_snapshot.price * Decimal256::from_uint256(window_start - start_time);
+
+    Ok(PriceInfo {
+        price: (cumulative_price_end - cumulative_price_start)
+            / Decimal256::from_uint256(window_seconds),
+        last_updated_time: read_price(deps.storage, asset)?.last_updated_time,
+    })
+}
+
+fn assert_not_frozen(deps: Deps, asset: &str) -> StdResult<()> {
+    if read_frozen_price(deps.storage, asset).is_some() {
+        return Err(StdError::generic_err(format!(
+            "Price of {} is frozen until confirmed",
+            asset
+        )));
+    }
+
+    Ok(())
+}
+
 fn query_prices(
     deps: Deps,
     start_after: Option<String>,
@@ -11,4 +11,22 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Feeder is already registered for the asset")]
+    FeederAlreadyRegistered {},
+
+    #[error("Feeder is not registered for the asset")]
+    FeederNotRegistered {},
+
+    #[error("Quorum must be greater than 0")]
+    InvalidQuorum {},
+
+    #[error("Min price cannot be greater than max price")]
+    InvalidPriceBounds {},
+
+    #[error("Price must be greater than 0")]
+    InvalidZeroPrice {},
+
+    #[error("Price of the asset is not frozen")]
+    PriceNotFrozen {},
 }
@@ -9,6 +9,11 @@ use moneymarket::oracle::PricesResponseElem;
 
 static PREFIX_PRICE: &[u8] = b"price";
 static PREFIX_FEEDER: &[u8] = b"feeder";
+static PREFIX_FEEDERS: &[u8] = b"feeders";
+static PREFIX_FEEDER_PRICE: &[u8] = b"feeder_price";
+static PREFIX_ASSET_CONFIG: &[u8] = b"asset_config";
+static PREFIX_FROZEN_PRICE: &[u8] = b"frozen_price";
+static PREFIX_PRICE_SNAPSHOT: &[u8] = b"price_snapshot";
 
 static KEY_CONFIG: &[u8] = b"config";
 
@@ -61,8 +66,16 @@ pub fn read_prices(
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after);
 
+    // frozen prices are left out until confirmed
     price_bucket
         .range(start.as_deref(), None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((k, _)) => match std::str::from_utf8(k) {
+                Ok(asset) => read_frozen_price(storage, asset).is_none(),
+                Err(_) => true,
+            },
+            Err(_) => true,
+        })
         .take(limit)
         .map(|item| {
             let (k, v) = item?;
@@ -77,24 +90,216 @@ pub fn read_prices(
         .collect()
 }
 
-pub fn store_feeder(
+pub fn store_feeders(
+    storage: &mut dyn Storage,
+    asset: &str,
+    feeders: &[CanonicalAddr],
+) -> StdResult<()> {
+    let mut feeders_bucket: Bucket<Vec<CanonicalAddr>> = Bucket::new(storage, PREFIX_FEEDERS);
+    if feeders.is_empty() {
+        feeders_bucket.remove(asset.as_bytes());
+        Ok(())
+    } else {
+        feeders_bucket.save(asset.as_bytes(), &feeders.to_vec())
+    }
+}
+
+pub fn read_feeders(storage: &dyn Storage, asset: &str) -> Vec<CanonicalAddr> {
+    let feeders_bucket: ReadonlyBucket<Vec<CanonicalAddr>> =
+        ReadonlyBucket::new(storage, PREFIX_FEEDERS);
+    feeders_bucket.load(asset.as_bytes()).unwrap_or_default()
+}
+
+/// Move the single feeder each asset had before
+/// the multi-feeder support into the set of feeders
+pub fn migrate_feeders(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_feeders: Vec<(Vec<u8>, CanonicalAddr)> =
+        ReadonlyBucket::<CanonicalAddr>::new(storage, PREFIX_FEEDER)
+            .range(None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Vec<u8>, CanonicalAddr)>>>()?;
+
+    for (k, feeder) in legacy_feeders.into_iter() {
+        let asset = std::str::from_utf8(&k)
+            .map_err(|_| StdError::generic_err("Corrupted feeder key"))?
+            .to_string();
+
+        let mut feeders = read_feeders(storage, &asset);
+        if !feeders.contains(&feeder) {
+            feeders.insert(0, feeder);
+            store_feeders(storage, &asset, &feeders)?;
+        }
+
+        Bucket::<CanonicalAddr>::new(storage, PREFIX_FEEDER).remove(&k);
+    }
+
+    Ok(())
+}
+
+pub fn store_feeder_price(
     storage: &mut dyn Storage,
     asset: &str,
     feeder: &CanonicalAddr,
+    price: &PriceInfo,
 ) -> StdResult<()> {
-    let mut price_bucket: Bucket<CanonicalAddr> = Bucket::new(storage, PREFIX_FEEDER);
-    price_bucket.save(asset.as_bytes(), feeder)
+    let mut feeder_price_bucket: Bucket<PriceInfo> =
+        Bucket::multilevel(storage, &[PREFIX_FEEDER_PRICE, asset.as_bytes()]);
+    feeder_price_bucket.save(feeder.as_slice(), price)
 }
 
-pub fn read_feeder(storage: &dyn Storage, asset: &str) -> StdResult<CanonicalAddr> {
-    let price_bucket: ReadonlyBucket<CanonicalAddr> = ReadonlyBucket::new(storage, PREFIX_FEEDER);
-    let res = price_bucket.load(asset.as_bytes());
-    match res {
-        Ok(data) => Ok(data),
-        Err(_err) => Err(StdError::generic_err(
-            "No feeder data for the specified asset exist",
-        )),
+pub fn remove_feeder_price(storage: &mut dyn Storage, asset: &str, feeder: &CanonicalAddr) {
+    let mut feeder_price_bucket: Bucket<PriceInfo> =
+        Bucket::multilevel(storage, &[PREFIX_FEEDER_PRICE, asset.as_bytes()]);
+    feeder_price_bucket.remove(feeder.as_slice())
+}
+
+pub fn read_feeder_price(
+    storage: &dyn Storage,
+    asset: &str,
+    feeder: &CanonicalAddr,
+) -> Option<PriceInfo> {
+    let feeder_price_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_FEEDER_PRICE, asset.as_bytes()]);
+    feeder_price_bucket
+        .may_load(feeder.as_slice())
+        .ok()
+        .flatten()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetConfig {
+    /// Minimum # of fresh feeder submissions required to aggregate a price
+    pub quorum: u32,
+    /// Submissions older than this (seconds) are excluded from the aggregation
+    pub max_staleness: Option<u64>,
+    /// Aggregated prices changing more than this ratio from the last price get frozen
+    pub max_price_change: Option<Decimal256>,
+    /// Aggregated prices out of [min_price, max_price] get frozen
+    pub min_price: Option<Decimal256>,
+    pub max_price: Option<Decimal256>,
+}
+
+pub fn store_asset_config(
+    storage: &mut dyn Storage,
+    asset: &str,
+    asset_config: &AssetConfig,
+) -> StdResult<()> {
+    let mut asset_config_bucket: Bucket<AssetConfig> = Bucket::new(storage, PREFIX_ASSET_CONFIG);
+    asset_config_bucket.save(asset.as_bytes(), asset_config)
+}
+
+pub fn read_asset_config(storage: &dyn Storage, asset: &str) -> StdResult<AssetConfig> {
+    let asset_config_bucket: ReadonlyBucket<AssetConfig> =
+        ReadonlyBucket::new(storage, PREFIX_ASSET_CONFIG);
+    Ok(asset_config_bucket
+        .may_load(asset.as_bytes())?
+        .unwrap_or(AssetConfig {
+            quorum: 1,
+            max_staleness: None,
+            max_price_change: None,
+            min_price: None,
+            max_price: None,
+        }))
+}
+
+pub fn store_frozen_price(
+    storage: &mut dyn Storage,
+    asset: &str,
+    price: &PriceInfo,
+) -> StdResult<()> {
+    let mut frozen_price_bucket: Bucket<PriceInfo> = Bucket::new(storage, PREFIX_FROZEN_PRICE);
+    frozen_price_bucket.save(asset.as_bytes(), price)
+}
+
+pub fn remove_frozen_price(storage: &mut dyn Storage, asset: &str) {
+    let mut frozen_price_bucket: Bucket<PriceInfo> = Bucket::new(storage, PREFIX_FROZEN_PRICE);
+    frozen_price_bucket.remove(asset.as_bytes())
+}
+
+pub fn read_frozen_price(storage: &dyn Storage, asset: &str) -> Option<PriceInfo> {
+    let frozen_price_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::new(storage, PREFIX_FROZEN_PRICE);
+    frozen_price_bucket
+        .may_load(asset.as_bytes())
+        .ok()
+        .flatten()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceSnapshot {
+    /// Sum of price * elapsed seconds since the first accepted price of the asset
+    pub cumulative_price: Decimal256,
+    /// Price accepted at the snapshot time
+    pub price: Decimal256,
+}
+
+pub fn store_price_snapshot(
+    storage: &mut dyn Storage,
+    asset: &str,
+    time: u64,
+    snapshot: &PriceSnapshot,
+) -> StdResult<()> {
+    let mut snapshot_bucket: Bucket<PriceSnapshot> =
+        Bucket::multilevel(storage, &[PREFIX_PRICE_SNAPSHOT, asset.as_bytes()]);
+    snapshot_bucket.save(&time.to_be_bytes(), snapshot)
+}
+
+/// Returns the latest snapshot taken at or before the given time,
+/// or the latest snapshot of the asset when no time is given
+pub fn read_last_price_snapshot(
+    storage: &dyn Storage,
+    asset: &str,
+    at_or_before: Option<u64>,
+) -> StdResult<Option<(u64, PriceSnapshot)>> {
+    let snapshot_bucket: ReadonlyBucket<PriceSnapshot> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_PRICE_SNAPSHOT, asset.as_bytes()]);
+
+    let end = at_or_before.map(|time| (time + 1).to_be_bytes().to_vec());
+    let last_snapshot = snapshot_bucket
+        .range(None, end.as_deref(), Order::Descending)
+        .next();
+
+    match last_snapshot {
+        Some(item) => {
+            let (k, v) = item?;
+            Ok(Some((to_u64(&k)?, v)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Remove the snapshots which are not needed to look back until the given time
+pub fn remove_price_snapshots_before(
+    storage: &mut dyn Storage,
+    asset: &str,
+    time: u64,
+) -> StdResult<()> {
+    let first_needed = match read_last_price_snapshot(storage, asset, Some(time))? {
+        Some((first_needed, _)) => first_needed,
+        None => return Ok(()),
+    };
+
+    let mut snapshot_bucket: Bucket<PriceSnapshot> =
+        Bucket::multilevel(storage, &[PREFIX_PRICE_SNAPSHOT, asset.as_bytes()]);
+    let keys: Vec<Vec<u8>> = snapshot_bucket
+        .range(None, Some(&first_needed.to_be_bytes()), Order::Ascending)
+        .map(|item| Ok(item?.0))
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+
+    for key in keys.iter() {
+        snapshot_bucket.remove(key);
     }
+
+    Ok(())
+}
+
+fn to_u64(bytes: &[u8]) -> StdResult<u64> {
+    let mut arr = [0u8; 8];
+    if bytes.len() != 8 {
+        return Err(StdError::generic_err("Corrupted snapshot key"));
+    }
+
+    arr.copy_from_slice(bytes);
+    Ok(u64::from_be_bytes(arr))
 }
 
 // this will set the first key after the provided key, by appending a 1 byte
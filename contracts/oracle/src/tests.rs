@@ -1,11 +1,14 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, MAX_TWAP_WINDOW};
 use crate::error::ContractError;
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::from_binary;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{from_binary, from_slice, Api, StdError};
+use cosmwasm_storage::Bucket;
+use moneymarket::access::{Role, RoleMembersResponse};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    AssetConfigResponse, ConfigResponse, ExecuteMsg, FeederPricesResponse,
+    FeederPricesResponseElem, FeederResponse, FeedersResponse, FrozenPriceResponse, InstantiateMsg,
+    MigrateMsg, PriceResponse, PricesResponse, PricesResponseElem, QueryMsg,
 };
 use std::str::FromStr;
 
@@ -32,7 +35,7 @@ fn proper_initialization() {
 }
 
 #[test]
-fn update_config() {
+fn update_owner() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
@@ -43,12 +46,17 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // owner changes once the proposed owner accepts
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expires_in: None,
     };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
 
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
@@ -60,7 +68,10 @@ fn update_config() {
 
     // Unauthorized err
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig { owner: None };
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0000".to_string(),
+        expires_in: None,
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -94,7 +105,346 @@ fn register_feeder() {
     }
 
     let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    // Cannot register the same feeder twice
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::FeederAlreadyRegistered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0001".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let feeders_res: FeedersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Feeders {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        feeders_res,
+        FeedersResponse {
+            asset: "mAAPL".to_string(),
+            feeders: vec!["feeder0000".to_string(), "feeder0001".to_string()],
+        }
+    );
+
+    // Deregister feeder
+    let msg = ExecuteMsg::DeregisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::FeederNotRegistered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let feeders_res: FeedersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Feeders {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        feeders_res,
+        FeedersResponse {
+            asset: "mAAPL".to_string(),
+            feeders: vec!["feeder0001".to_string()],
+        }
+    );
+}
+
+#[test]
+fn feeder_admin() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Only the owner can grant roles
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::FeederAdmin,
+        address: "addr0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let members_res: RoleMembersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RoleMembers {
+                role: Role::FeederAdmin,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        members_res,
+        RoleMembersResponse {
+            role: Role::FeederAdmin,
+            members: vec!["addr0000".to_string()],
+        }
+    );
+
+    // The feeder admin can register feeders
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // but cannot update the asset config
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: Some(1u32),
+        max_staleness: None,
+        max_price_change: None,
+        min_price: None,
+        max_price: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Revoked feeder admin cannot deregister feeders
+    let msg = ExecuteMsg::RevokeRole {
+        role: Role::FeederAdmin,
+        address: "addr0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::DeregisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn update_asset_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Default asset config
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetConfig {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: AssetConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        AssetConfigResponse {
+            asset: "mAAPL".to_string(),
+            quorum: 1,
+            max_staleness: None,
+            max_price_change: None,
+            min_price: None,
+            max_price: None,
+        }
+    );
+
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: Some(2),
+        max_staleness: Some(Some(60)),
+        max_price_change: None,
+        min_price: None,
+        max_price: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetConfig {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: AssetConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        AssetConfigResponse {
+            asset: "mAAPL".to_string(),
+            quorum: 2,
+            max_staleness: Some(60),
+            max_price_change: None,
+            min_price: None,
+            max_price: None,
+        }
+    );
+
+    // Zero quorum is not allowed
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: Some(0),
+        max_staleness: None,
+        max_price_change: None,
+        min_price: None,
+        max_price: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidQuorum {}) => (),
+        _ => panic!("Must return invalid quorum error"),
+    }
+
+    // null clears max_staleness, a missing field keeps it
+    let msg: ExecuteMsg = from_slice(
+        br#"{"update_asset_config":{"asset":"mAAPL","quorum":null,"max_price_change":null,"min_price":null,"max_price":null}}"#,
+    )
+    .unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetConfig {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: AssetConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(60), value.max_staleness);
+
+    let msg: ExecuteMsg = from_slice(
+        br#"{"update_asset_config":{"asset":"mAAPL","quorum":null,"max_staleness":null,"max_price_change":null,"min_price":null,"max_price":null}}"#,
+    )
+    .unwrap();
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetConfig {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: AssetConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(None, value.max_staleness);
+}
+
+#[test]
+fn migrate_feeders() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // feeder stored by the single feeder oracle
+    let feeder_raw = deps.api.addr_canonicalize("feeder0000").unwrap();
+    Bucket::new(&mut deps.storage, b"feeder")
+        .save(b"mAAPL", &feeder_raw)
+        .unwrap();
+
+    let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let feeders_res: FeedersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Feeders {
+                asset: "mAAPL".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        feeders_res,
+        FeedersResponse {
+            asset: "mAAPL".to_string(),
+            feeders: vec!["feeder0000".to_string()],
+        }
+    );
+
+    // the migrated feeder keeps feeding
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_ratio(10, 1))],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg).unwrap();
+
     let feeder_res: FeederResponse = from_binary(
         &query(
             deps.as_ref(),
@@ -113,6 +463,20 @@ fn register_feeder() {
             feeder: "feeder0000".to_string(),
         }
     );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Feeder {
+            asset: "mGOGL".to_string(),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!("No feeder data for the specified asset exist", msg)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
@@ -234,3 +598,510 @@ fn feed_price() {
         _ => panic!("Must return unauthorized error"),
     }
 }
+
+#[test]
+fn feed_price_median() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register three feeders for mAAPL and require two fresh submissions
+    let info = mock_info("owner0000", &[]);
+    for feeder in ["feeder0000", "feeder0001", "feeder0002"].iter() {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: "mAAPL".to_string(),
+            feeder: feeder.to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: Some(2),
+        max_staleness: Some(Some(60)),
+        max_price_change: None,
+        min_price: None,
+        max_price: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_price = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Price {
+                base: "mAAPL".to_string(),
+                quote: "base0000".to_string(),
+            },
+        )
+    };
+
+    // First submission does not reach the quorum
+    let mut env = mock_env();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("1.0").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert!(query_price(&deps).is_err());
+
+    // Two submissions; median is the average of both
+    let feeder0000_time = env.block.time.seconds();
+    env.block.time = env.block.time.plus_seconds(10);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("1.4").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0001", &[]),
+        msg,
+    )
+    .unwrap();
+
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1.2").unwrap(),
+            last_updated_base: feeder0000_time,
+            last_updated_quote: 9999999999,
+        }
+    );
+
+    // Three submissions; outlier does not move the median
+    env.block.time = env.block.time.plus_seconds(10);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("100").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0002", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // The price is timestamped with the median submission, not the oldest one
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("1.4").unwrap(),
+            last_updated_base: feeder0000_time + 10,
+            last_updated_quote: 9999999999,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::FeederPrices {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: FeederPricesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        FeederPricesResponse {
+            asset: "mAAPL".to_string(),
+            prices: vec![
+                FeederPricesResponseElem {
+                    feeder: "feeder0000".to_string(),
+                    price: Decimal256::from_str("1.0").unwrap(),
+                    last_updated_time: feeder0000_time,
+                },
+                FeederPricesResponseElem {
+                    feeder: "feeder0001".to_string(),
+                    price: Decimal256::from_str("1.4").unwrap(),
+                    last_updated_time: feeder0000_time + 10,
+                },
+                FeederPricesResponseElem {
+                    feeder: "feeder0002".to_string(),
+                    price: Decimal256::from_str("100").unwrap(),
+                    last_updated_time: feeder0000_time + 20,
+                },
+            ],
+        }
+    );
+
+    // Stale submissions are excluded; only feeder0002 and feeder0000 are fresh
+    env.block.time = env.block.time.plus_seconds(60);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("2.0").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("51").unwrap(),
+            last_updated_base: feeder0000_time + 20,
+            last_updated_quote: 9999999999,
+        }
+    );
+
+    // Not enough fresh submissions; the aggregated price is left untouched
+    env.block.time = env.block.time.plus_seconds(70);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("3.0").unwrap())],
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("feeder0001", &[]), msg).unwrap();
+
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("51").unwrap());
+}
+
+#[test]
+fn price_circuit_breaker() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::Guardian,
+        address: "guardian0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "feeder0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // Allow 10% change per update within [0.5, 2]
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: None,
+        max_staleness: None,
+        max_price_change: Some(Some(Decimal256::percent(10))),
+        min_price: Some(Some(Decimal256::percent(50))),
+        max_price: Some(Some(Decimal256::percent(200))),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let feed = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, price: &str| {
+        let msg = ExecuteMsg::FeedPrice {
+            prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("feeder0000", &[]), msg)
+    };
+    let query_price = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Price {
+                base: "mAAPL".to_string(),
+                quote: "base0000".to_string(),
+            },
+        )
+    };
+
+    // Zero price is rejected
+    match feed(&mut deps, "0") {
+        Err(ContractError::InvalidZeroPrice {}) => (),
+        _ => panic!("Must return invalid zero price error"),
+    }
+
+    // First price within the absolute bounds is accepted
+    let _res = feed(&mut deps, "1.0").unwrap();
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.0").unwrap());
+
+    // Change within 10% is accepted
+    let _res = feed(&mut deps, "1.1").unwrap();
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.1").unwrap());
+
+    // Big jump freezes the price
+    let _res = feed(&mut deps, "1.5").unwrap();
+    match query_price(&deps) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Price of mAAPL is frozen until confirmed")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Frozen price follows the latest feed
+    let _res = feed(&mut deps, "1.6").unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::FrozenPrice {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: FrozenPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        FrozenPriceResponse {
+            asset: "mAAPL".to_string(),
+            price: Decimal256::from_str("1.6").unwrap(),
+            last_updated_time: mock_env().block.time.seconds(),
+        }
+    );
+
+    // Only the owner or a guardian can confirm
+    let msg = ExecuteMsg::ConfirmPrice {
+        asset: "mAAPL".to_string(),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("feeder0000", &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&query_price(&deps).unwrap()).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.6").unwrap());
+
+    match execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg) {
+        Err(ContractError::PriceNotFrozen {}) => (),
+        _ => panic!("Must return price not frozen error"),
+    }
+
+    // Price out of the absolute bounds freezes the price
+    let _res = feed(&mut deps, "1.7").unwrap();
+    let _res = feed(&mut deps, "1.8").unwrap();
+    let _res = feed(&mut deps, "1.9").unwrap();
+    let _res = feed(&mut deps, "2.05").unwrap();
+    assert!(query_price(&deps).is_err());
+
+    // Frozen prices are left out of the price list
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Prices {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: PricesResponse = from_binary(&res).unwrap();
+    assert_eq!(value, PricesResponse { prices: vec![] });
+
+    // Min price cannot exceed max price
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: None,
+        max_staleness: None,
+        max_price_change: None,
+        min_price: Some(Some(Decimal256::percent(300))),
+        max_price: None,
+    };
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+        Err(ContractError::InvalidPriceBounds {}) => (),
+        _ => panic!("Must return invalid price bounds error"),
+    }
+
+    // Bounds can be cleared
+    let msg = ExecuteMsg::UpdateAssetConfig {
+        asset: "mAAPL".to_string(),
+        quorum: None,
+        max_staleness: None,
+        max_price_change: Some(None),
+        min_price: Some(None),
+        max_price: Some(None),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AssetConfig {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: AssetConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        AssetConfigResponse {
+            asset: "mAAPL".to_string(),
+            quorum: 1,
+            max_staleness: None,
+            max_price_change: None,
+            min_price: None,
+            max_price: None,
+        }
+    );
+}
+
+#[test]
+fn twap() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    for asset in ["mAAPL", "mGOGL"].iter() {
+        let msg = ExecuteMsg::RegisterFeeder {
+            asset: asset.to_string(),
+            feeder: "feeder0000".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let twap_msg = |base: &str, quote: &str, window_seconds: u64| QueryMsg::Twap {
+        base: base.to_string(),
+        quote: quote.to_string(),
+        window_seconds,
+    };
+
+    // mAAPL: 1.0 for 100s, 2.0 for 100s, 4.0 afterwards
+    // mGOGL: 2.0 from the start
+    let mut env = mock_env();
+    let start_time = env.block.time.seconds();
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![
+            ("mAAPL".to_string(), Decimal256::from_str("1.0").unwrap()),
+            ("mGOGL".to_string(), Decimal256::from_str("2.0").unwrap()),
+        ],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // Not enough history for the window
+    env.block.time = env.block.time.plus_seconds(50);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        twap_msg("mAAPL", "base0000", 100),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Not enough price history for the TWAP window")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(50);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("2.0").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str("4.0").unwrap())],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(50);
+
+    // (1.0 * 50 + 2.0 * 100 + 4.0 * 50) / 200
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        twap_msg("mAAPL", "base0000", 200),
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        PriceResponse {
+            rate: Decimal256::from_str("2.25").unwrap(),
+            last_updated_base: start_time + 200,
+            last_updated_quote: 9999999999,
+        }
+    );
+
+    // (2.0 * 50 + 4.0 * 50) / 100
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        twap_msg("mAAPL", "base0000", 100),
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("3").unwrap());
+
+    // Quote in non-base asset
+    let res = query(deps.as_ref(), env.clone(), twap_msg("mAAPL", "mGOGL", 200)).unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.125").unwrap());
+
+    // Spot price is left unchanged
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Price {
+            base: "mAAPL".to_string(),
+            quote: "base0000".to_string(),
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("4").unwrap());
+
+    // Invalid window
+    assert!(query(deps.as_ref(), env.clone(), twap_msg("mAAPL", "base0000", 0)).is_err());
+    assert!(query(
+        deps.as_ref(),
+        env,
+        twap_msg("mAAPL", "base0000", MAX_TWAP_WINDOW + 1)
+    )
+    .is_err());
+}
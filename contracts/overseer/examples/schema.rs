@@ -3,10 +3,14 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
+use moneymarket::access::RoleMembersResponse;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse,
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerEModeResponse, BorrowerHealthResponse,
+    CollateralStatusResponse, CollateralsResponse, ConfigResponse, EModeCategoriesResponse,
+    ExecuteMsg, InstantiateMsg, LiquidatableBorrowersResponse, PauseStatusResponse,
+    PendingChangesResponse, QueryMsg, ShutdownStateResponse, WhitelistResponse,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 use moneymarket_overseer::state::EpochState;
 
 fn main() {
@@ -20,8 +24,18 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(AllCollateralsResponse), &out_dir);
     export_schema(&schema_for!(BorrowLimitResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerEModeResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerHealthResponse), &out_dir);
+    export_schema(&schema_for!(CollateralStatusResponse), &out_dir);
     export_schema(&schema_for!(CollateralsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(RoleMembersResponse), &out_dir);
+    export_schema(&schema_for!(EModeCategoriesResponse), &out_dir);
     export_schema(&schema_for!(EpochState), &out_dir);
+    export_schema(&schema_for!(LiquidatableBorrowersResponse), &out_dir);
+    export_schema(&schema_for!(PauseStatusResponse), &out_dir);
+    export_schema(&schema_for!(ShutdownStateResponse), &out_dir);
+    export_schema(&schema_for!(PendingChangesResponse), &out_dir);
     export_schema(&schema_for!(WhitelistResponse), &out_dir);
 }
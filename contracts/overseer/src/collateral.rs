@@ -1,40 +1,88 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
-    WasmMsg,
+    attr, to_binary, Addr, CanonicalAddr, Coin, ContractResult, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
 
 use crate::error::ContractError;
-use crate::querier::{query_borrower_info, query_liquidation_amount};
+use crate::querier::{
+    query_borrower_info, query_epoch_state, query_liquidation_amount, query_market_state,
+};
 use crate::state::{
-    read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
-    Config, WhitelistElem,
+    is_paused, read_all_collaterals, read_borrower_emode, read_collaterals, read_config,
+    read_dynrate_state, read_emode_category, read_epoch_state, read_isolated_debt,
+    read_isolated_loan, read_liquidation_batch, read_shutdown, read_total_collateral,
+    read_whitelist_elem, remove_isolated_loan, store_borrower_emode, store_collaterals,
+    store_dynrate_state, store_epoch_state, store_isolated_debt, store_isolated_loan,
+    store_liquidation_batch, store_total_collateral, Config, DynrateState, EModeCategory,
+    EpochState, IsolatedLoan, WhitelistElem,
 };
 
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::liquidation::LiquidationAmountResponse;
 use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
-use moneymarket::oracle::PriceResponse;
-use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
+use moneymarket::overseer::{
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerEModeResponse, BorrowerHealthResponse,
+    CollateralHealthElem, CollateralsResponse, ExecuteMsg, LiquidatableBorrowersResponse,
+    LiquidatableBorrowersResponseElem, PausableOperation,
+};
+use moneymarket::querier::{deduct_tax, query_balance, query_price, query_twap, TimeConstraints};
 use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
+use std::cmp::min;
+use std::collections::HashMap;
 
 pub fn lock_collateral(
     deps: DepsMut,
-    info: MessageInfo,
+    borrower: Addr,
     collaterals_human: TokensHuman,
 ) -> Result<Response, ContractError> {
-    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_shutdown(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
+    if is_paused(deps.storage, &PausableOperation::Lock)? {
+        return Err(ContractError::OperationPaused(PausableOperation::Lock));
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
 
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
 
     cur_collaterals.add(collaterals.clone());
+    if cur_collaterals.len() > 1
+        && find_isolated_collateral(deps.storage, &cur_collaterals)?.is_some()
+    {
+        return Err(ContractError::IsolatedCollateral {});
+    }
+
+    // keep the e-mode active while opted in
+    if let Some(category_id) = read_borrower_emode(deps.storage, &borrower_raw)? {
+        let category: EModeCategory = read_emode_category(deps.storage, category_id)?;
+        if !collaterals
+            .iter()
+            .all(|collateral| category.collaterals.contains(&collateral.0))
+        {
+            return Err(ContractError::EModeCollateralMismatch {});
+        }
+    }
+
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
     for collateral in collaterals {
         let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if whitelist_elem.delisting.is_some() {
+            return Err(ContractError::CollateralDelisted {});
+        }
+
+        let total_collateral = read_total_collateral(deps.storage, &collateral.0);
+        store_total_collateral(
+            deps.storage,
+            &collateral.0,
+            &(total_collateral + collateral.1),
+        )?;
+
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -42,7 +90,7 @@ pub fn lock_collateral(
                 .to_string(),
             funds: vec![],
             msg: to_binary(&CustodyExecuteMsg::LockCollateral {
-                borrower: info.sender.to_string(),
+                borrower: borrower.to_string(),
                 amount: collateral.1,
             })?,
         }));
@@ -56,21 +104,67 @@ pub fn lock_collateral(
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "lock_collateral"),
-        attr("borrower", info.sender),
+        attr("borrower", borrower),
         attr("collaterals", collateral_logs.join(",")),
     ]))
 }
 
+/// Lock collaterals just deposited to their custody contracts
+/// on behalf of the borrower, then borrow against them if requested
+/// Executor: custody
+pub fn lock_collateral_for(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    collaterals_human: TokensHuman,
+    borrow_amount: Option<Uint256>,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    // only the custody contract of each collateral can lock it
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    for collateral in collaterals_human.to_raw(deps.as_ref())? {
+        let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if whitelist_elem.custody_contract != sender_raw {
+            return Err(ContractError::Unauthorized {});
+        }
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let mut res = lock_collateral(deps, borrower.clone(), collaterals_human)?;
+    if let Some(borrow_amount) = borrow_amount {
+        // executed after the custody locked the collaterals
+        res = res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market.to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::BorrowStableFor {
+                borrower: borrower.to_string(),
+                borrow_amount,
+                to: to.map(|to| to.to_string()),
+            })?,
+        }));
+    }
+
+    Ok(res)
+}
+
+/// Unlock collaterals of the borrower; with `withdraw`, custodies
+/// also transfer the unlocked collaterals back to the borrower
 pub fn unlock_collateral(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    borrower: Addr,
     collaterals_human: TokensHuman,
+    withdraw: bool,
 ) -> Result<Response, ContractError> {
+    if is_paused(deps.storage, &PausableOperation::Unlock)? {
+        return Err(ContractError::OperationPaused(PausableOperation::Unlock));
+    }
+
     let config: Config = read_config(deps.storage)?;
     let market = deps.api.addr_humanize(&config.market_contract)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
@@ -83,7 +177,9 @@ pub fn unlock_collateral(
     // Compute borrow limit with collaterals except unlock target collaterals
     let (borrow_limit, _) = compute_borrow_limit(
         deps.as_ref(),
+        &borrower_raw,
         &cur_collaterals,
+        env.block.height,
         Some(env.block.time.seconds()),
     )?;
     let borrow_amount_res: BorrowerInfoResponse =
@@ -92,7 +188,15 @@ pub fn unlock_collateral(
         return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
     }
 
+    // past the shutdown deadline, collaterals of unpaid loans are kept for settlement
+    if let Some(shutdown) = read_shutdown(deps.storage)? {
+        if env.block.height >= shutdown.repay_deadline && !borrow_amount_res.loan_amount.is_zero() {
+            return Err(ContractError::RepayDeadlinePassed(shutdown.repay_deadline));
+        }
+    }
+
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    decrease_total_collaterals(deps.storage, &collaterals)?;
 
     let mut messages: Vec<SubMsg> = vec![];
     for collateral in collaterals {
@@ -103,10 +207,17 @@ pub fn unlock_collateral(
                 .addr_humanize(&whitelist_elem.custody_contract)?
                 .to_string(),
             funds: vec![],
-            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                borrower: borrower.to_string(),
-                amount: collateral.1,
-            })?,
+            msg: if withdraw {
+                to_binary(&CustodyExecuteMsg::UnlockAndWithdrawCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral.1,
+                })?
+            } else {
+                to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: borrower.to_string(),
+                    amount: collateral.1,
+                })?
+            },
         })));
     }
 
@@ -125,12 +236,65 @@ pub fn unlock_collateral(
         ]))
 }
 
-pub fn liquidate_collateral(
+pub fn set_emode(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    category_id: Option<u8>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    if let Some(category_id) = category_id {
+        let category: EModeCategory = read_emode_category(deps.storage, category_id)?;
+        if !collaterals
+            .iter()
+            .all(|collateral| category.collaterals.contains(&collateral.0))
+        {
+            return Err(ContractError::EModeCollateralMismatch {});
+        }
+    }
+
+    store_borrower_emode(deps.storage, &borrower_raw, category_id)?;
+
+    // Compute borrow limit with the new category
+    let (borrow_limit, _) = compute_borrow_limit(
+        deps.as_ref(),
+        &borrower_raw,
+        &collaterals,
+        env.block.height,
+        Some(env.block.time.seconds()),
+    )?;
+    let borrow_amount_res: BorrowerInfoResponse =
+        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
+    if borrow_limit < borrow_amount_res.loan_amount {
+        return Err(ContractError::EModeLoanExceedsLimit(borrow_limit.into()));
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_emode"),
+        attr("borrower", borrower),
+        attr(
+            "category_id",
+            category_id.map_or_else(|| "none".to_string(), |id| id.to_string()),
+        ),
+    ]))
+}
+
+pub fn liquidate_collateral(
+    deps: DepsMut,
+    env: Env,
+    liquidator: Addr,
     borrower: Addr,
 ) -> Result<Response, ContractError> {
+    if is_paused(deps.storage, &PausableOperation::Liquidate)? {
+        return Err(ContractError::OperationPaused(PausableOperation::Liquidate));
+    }
+
     let config: Config = read_config(deps.storage)?;
     let market = deps.api.addr_humanize(&config.market_contract)?;
 
@@ -138,71 +302,488 @@ pub fn liquidate_collateral(
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
 
     // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+    let (borrow_limit, liquidation_limit, collateral_prices) = compute_collateral_limits(
         deps.as_ref(),
+        &borrower_raw,
         &cur_collaterals,
+        env.block.height,
         Some(env.block.time.seconds()),
     )?;
     let borrow_amount_res: BorrowerInfoResponse =
         query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
     let borrow_amount = borrow_amount_res.loan_amount;
 
-    // borrow limit is equal or bigger than loan amount
+    // liquidation limit is equal or bigger than loan amount
     // cannot liquidation collaterals
-    if borrow_limit >= borrow_amount {
+    if liquidation_limit >= borrow_amount {
         return Err(ContractError::CannotLiquidateSafeLoan {});
     }
 
-    let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
+    let emode_category = read_active_emode_category(deps.storage, &borrower_raw, &cur_collaterals)?;
+    let liquidation_amount = compute_liquidation_amount(
         deps.as_ref(),
-        deps.api.addr_humanize(&config.liquidation_contract)?,
+        &config,
+        &cur_collaterals,
+        collateral_prices,
         borrow_amount,
         borrow_limit,
-        &cur_collaterals.to_human(deps.as_ref())?,
-        collateral_prices,
+        emode_category.map(|category| category.max_ltv),
+        env.block.height,
     )?;
 
-    let liquidation_amount = liquidation_amount_res.collaterals.to_raw(deps.as_ref())?;
-
     // Store left collaterals
     cur_collaterals.sub(liquidation_amount.clone())?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    decrease_total_collaterals(deps.storage, &liquidation_amount)?;
+
+    Ok(Response::new().add_messages(liquidation_messages(
+        deps.as_ref(),
+        &config,
+        &liquidator,
+        &borrower,
+        &liquidation_amount,
+    )?))
+}
+
+/// Liquidation amounts of the collaterals of a borrower. aterra is burned
+/// against the loan first, without any premium; the liquidation contract
+/// then gets the other collaterals for the part of the loan still unsafe.
+/// `emode_max_ltv` is the max_ltv of the active e-mode category, if any
+#[allow(clippy::too_many_arguments)]
+fn compute_liquidation_amount(
+    deps: Deps,
+    config: &Config,
+    collaterals: &Tokens,
+    collateral_prices: Vec<Decimal256>,
+    borrow_amount: Uint256,
+    borrow_limit: Uint256,
+    emode_max_ltv: Option<Decimal256>,
+    block_height: u64,
+) -> StdResult<Tokens> {
+    let mut borrow_amount = borrow_amount;
+    let mut borrow_limit = borrow_limit;
+    let mut liquidation_amount: Tokens = vec![];
+    let mut bid_collaterals: Tokens = vec![];
+    let mut bid_prices: Vec<Decimal256> = vec![];
+    for (collateral, price) in collaterals.iter().zip(collateral_prices) {
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        if !elem.is_aterra {
+            bid_collaterals.push(collateral.clone());
+            bid_prices.push(price);
+            continue;
+        }
+
+        if borrow_amount <= borrow_limit || price.is_zero() {
+            continue;
+        }
+
+        // repaying x lowers the loan by x and the borrow limit by x * max_ltv
+        let max_ltv = ramp_down(emode_max_ltv.unwrap_or(elem.max_ltv), &elem, block_height);
+        let mut repay_amount = min(collateral.1 * price, borrow_amount);
+        if max_ltv < Decimal256::one() {
+            let safe_amount = (borrow_amount - borrow_limit)
+                * (Decimal256::one() / (Decimal256::one() - max_ltv));
+            repay_amount = min(repay_amount, safe_amount);
+        }
+
+        let amount = Uint256::one() * (Decimal256::from_uint256(repay_amount) / price);
+        if amount.is_zero() {
+            continue;
+        }
 
+        liquidation_amount.push((collateral.0.clone(), amount));
+        borrow_amount = borrow_amount - repay_amount;
+        borrow_limit = borrow_limit - min(borrow_limit, repay_amount * max_ltv);
+    }
+
+    if !bid_collaterals.is_empty()
+        && (liquidation_amount.is_empty() || borrow_amount > borrow_limit)
+    {
+        let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
+            deps,
+            deps.api.addr_humanize(&config.liquidation_contract)?,
+            borrow_amount,
+            borrow_limit,
+            &bid_collaterals.to_human(deps)?,
+            bid_prices,
+        )?;
+
+        liquidation_amount.extend(liquidation_amount_res.collaterals.to_raw(deps)?);
+    }
+
+    Ok(liquidation_amount)
+}
+
+/// Custody liquidations of the given collaterals, followed by
+/// the market repay with the stable they bring in
+fn liquidation_messages(
+    deps: Deps,
+    config: &Config,
+    liquidator: &Addr,
+    borrower: &Addr,
+    liquidation_amount: &Tokens,
+) -> StdResult<Vec<CosmosMsg>> {
     let market_contract = deps.api.addr_humanize(&config.market_contract)?;
-    let prev_balance: Uint256 =
-        query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?;
+    let prev_balance: Uint256 = query_balance(
+        deps,
+        market_contract.clone(),
+        config.stable_denom.to_string(),
+    )?;
 
-    let liquidation_messages: Vec<CosmosMsg> = liquidation_amount
+    let mut messages: Vec<(bool, CosmosMsg)> = liquidation_amount
         .iter()
         .map(|collateral| {
             let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
 
+            Ok((
+                whitelist_elem.is_aterra,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps
+                        .api
+                        .addr_humanize(&whitelist_elem.custody_contract)?
+                        .to_string(),
+                    funds: vec![],
+                    msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                        liquidator: liquidator.to_string(),
+                        borrower: borrower.to_string(),
+                        amount: collateral.1,
+                    })?,
+                }),
+            ))
+        })
+        .collect::<StdResult<Vec<(bool, CosmosMsg)>>>()?;
+
+    // aterra is burned before the stable brought in by the bidders
+    // lifts the market exchange rate; it repays the loan by itself
+    messages.sort_by_key(|(is_aterra, _)| !is_aterra);
+    let repay_stable = messages.is_empty() || messages.iter().any(|(is_aterra, _)| !is_aterra);
+    let mut messages: Vec<CosmosMsg> = messages.into_iter().map(|(_, msg)| msg).collect();
+
+    if repay_stable {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                borrower: borrower.to_string(),
+                prev_balance,
+            })?,
+        }));
+    }
+
+    Ok(messages)
+}
+
+pub fn liquidate_collaterals(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrowers: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    if is_paused(deps.storage, &PausableOperation::Liquidate)? {
+        return Err(ContractError::OperationPaused(PausableOperation::Liquidate));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    // collaterals are priced once for the whole batch
+    let mut price_cache: HashMap<CanonicalAddr, Decimal256> = HashMap::new();
+    let mut liquidated: Vec<Addr> = vec![];
+    let mut liquidated_raw: Vec<CanonicalAddr> = vec![];
+    let mut messages: Vec<SubMsg> = vec![];
+    let mut attributes = vec![attr("action", "liquidate_collaterals")];
+    for borrower in borrowers.into_iter() {
+        if liquidated.contains(&borrower) {
+            attributes.push(attr("skipped", borrower.as_str()));
+            continue;
+        }
+
+        // a borrower who cannot be checked is recorded as failed
+        // without aborting the rest of the batch
+        let borrower_raw =
+            match check_liquidatable(deps.as_ref(), &env, &market, &borrower, &mut price_cache) {
+                Ok(Some(borrower_raw)) => borrower_raw,
+                Ok(None) => {
+                    attributes.push(attr("skipped", borrower.as_str()));
+                    continue;
+                }
+                Err(err) => {
+                    attributes.push(attr("liquidation_failed", borrower.as_str()));
+                    attributes.push(attr("error", err.to_string()));
+                    continue;
+                }
+            };
+
+        // each liquidation runs as its own message, so the market balance
+        // snapshot of RepayStableFromLiquidation is taken right before it;
+        // a failing liquidation is reverted alone and replied with its index
+        messages.push(SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::LiquidateCollateralHook {
+                    borrower: borrower.to_string(),
+                    liquidator: info.sender.to_string(),
+                })?,
+            }),
+            liquidated.len() as u64,
+        ));
+        attributes.push(attr("liquidated", borrower.as_str()));
+        liquidated.push(borrower);
+        liquidated_raw.push(borrower_raw);
+    }
+
+    store_liquidation_batch(deps.storage, &liquidated_raw)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(attributes))
+}
+
+/// Returns the canonical address of the borrower
+/// when its loan exceeds the liquidation limit
+fn check_liquidatable(
+    deps: Deps,
+    env: &Env,
+    market: &Addr,
+    borrower: &Addr,
+    price_cache: &mut HashMap<CanonicalAddr, Decimal256>,
+) -> Result<Option<CanonicalAddr>, ContractError> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    if collaterals.is_empty() {
+        return Ok(None);
+    }
+
+    let (_, liquidation_limit, _) = compute_collateral_limits_with_cache(
+        deps,
+        &borrower_raw,
+        &collaterals,
+        env.block.height,
+        Some(env.block.time.seconds()),
+        price_cache,
+    )?;
+    let borrow_amount_res: BorrowerInfoResponse =
+        query_borrower_info(deps, market.clone(), borrower.clone(), env.block.height)?;
+
+    if liquidation_limit >= borrow_amount_res.loan_amount {
+        return Ok(None);
+    }
+
+    Ok(Some(borrower_raw))
+}
+
+/// Record a failed liquidation of the LiquidateCollaterals batch;
+/// the other liquidations of the batch are kept
+pub fn liquidation_failed(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let borrowers: Vec<CanonicalAddr> = read_liquidation_batch(deps.storage)?;
+    let borrower = match borrowers.get(msg.id as usize) {
+        Some(borrower) => deps.api.addr_humanize(borrower)?,
+        None => return Err(ContractError::InvalidReplyId {}),
+    };
+
+    let error = match msg.result {
+        ContractResult::Err(error) => error,
+        ContractResult::Ok(_) => return Err(ContractError::InvalidReplyId {}),
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "liquidation_failed"),
+        attr("borrower", borrower),
+        attr("error", error),
+    ]))
+}
+
+/// Settle the loan of a borrower who did not repay before the shutdown
+/// deadline. The collaterals are valued at the frozen prices, and the part
+/// covering the loan is handed to the market for the depositors; the
+/// surplus stays with the borrower, who can unlock it once the loan is cleared
+pub fn settle_collateral(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    if is_paused(deps.storage, &PausableOperation::Liquidate)? {
+        return Err(ContractError::OperationPaused(PausableOperation::Liquidate));
+    }
+
+    let shutdown = match read_shutdown(deps.storage)? {
+        Some(shutdown) => shutdown,
+        None => return Err(ContractError::NotShutdown {}),
+    };
+    if env.block.height < shutdown.repay_deadline {
+        return Err(ContractError::RepayDeadlineNotPassed(
+            shutdown.repay_deadline,
+        ));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let borrower_info: BorrowerInfoResponse = query_borrower_info(
+        deps.as_ref(),
+        market.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?;
+    if collaterals.is_empty() || borrower_info.loan_amount.is_zero() {
+        return Err(ContractError::NothingToSettle {});
+    }
+
+    let mut collateral_value = Uint256::zero();
+    for collateral in collaterals.iter() {
+        let price = shutdown
+            .prices
+            .iter()
+            .find(|(collateral_token, _)| *collateral_token == collateral.0)
+            .map(|(_, price)| *price)
+            .ok_or_else(|| StdError::not_found("frozen collateral price"))?;
+        collateral_value += collateral.1 * price;
+    }
+
+    // every collateral is seized in the same ratio, all of them
+    // when they are not worth the loan anymore
+    let settle_ratio = if collateral_value <= borrower_info.loan_amount {
+        Decimal256::one()
+    } else {
+        Decimal256::from_uint256(borrower_info.loan_amount)
+            / Decimal256::from_uint256(collateral_value)
+    };
+
+    let mut settle_amount: Tokens = vec![];
+    for collateral in collaterals.iter() {
+        // rounded up, so the depositors are never paid less than the loan
+        let mut amount = collateral.1 * settle_ratio;
+        if Decimal256::from_uint256(amount) < Decimal256::from_uint256(collateral.1) * settle_ratio
+        {
+            amount += Uint256::one();
+        }
+
+        if !amount.is_zero() {
+            settle_amount.push((collateral.0.clone(), amount));
+        }
+    }
+
+    collaterals.sub(settle_amount.clone())?;
+    store_collaterals(deps.storage, &borrower_raw, &collaterals)?;
+    decrease_total_collaterals(deps.storage, &settle_amount)?;
+
+    let mut messages: Vec<CosmosMsg> = settle_amount
+        .iter()
+        .map(|collateral| {
+            let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
             Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: deps
                     .api
                     .addr_humanize(&whitelist_elem.custody_contract)?
                     .to_string(),
                 funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: info.sender.to_string(),
+                msg: to_binary(&CustodyExecuteMsg::SettleCollateral {
                     borrower: borrower.to_string(),
                     amount: collateral.1,
                 })?,
             }))
         })
-        .filter(|msg| msg.is_ok())
         .collect::<StdResult<Vec<CosmosMsg>>>()?;
 
+    // the loan is cleared once the collaterals reached the market
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: market.to_string(),
+        funds: vec![],
+        msg: to_binary(&MarketExecuteMsg::SettleLoan {
+            borrower: borrower.to_string(),
+            collaterals: settle_amount.to_human(deps.as_ref())?,
+        })?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "settle_collateral"),
+        attr("borrower", borrower),
+        attr("loan_amount", borrower_info.loan_amount),
+        attr("collateral_value", collateral_value),
+    ]))
+}
+
+/// Write off the loan of a borrower left without any collateral.
+/// The interest buffer covers it first; the market then takes
+/// the rest from its reserves and socializes what remains
+pub fn write_off_bad_debt(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    if !read_collaterals(deps.storage, &borrower_raw).is_empty() {
+        return Err(ContractError::BorrowerHasCollaterals {});
+    }
+
+    let borrower_info: BorrowerInfoResponse = query_borrower_info(
+        deps.as_ref(),
+        market.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?;
+    let loan_amount = borrower_info.loan_amount;
+    if loan_amount.is_zero() {
+        return Err(ContractError::NoBadDebt {});
+    }
+
+    let interest_buffer = query_balance(
+        deps.as_ref(),
+        env.contract.address,
+        config.stable_denom.to_string(),
+    )?;
+    let buffer_amount = min(loan_amount, interest_buffer);
+
+    // Spent buffer must not be counted as accrued on the next epoch
+    let mut epoch_state: EpochState = read_epoch_state(deps.storage)?;
+    epoch_state.prev_interest_buffer = if epoch_state.prev_interest_buffer > buffer_amount {
+        epoch_state.prev_interest_buffer - buffer_amount
+    } else {
+        Uint256::zero()
+    };
+    store_epoch_state(deps.storage, &epoch_state)?;
+
+    let mut dynrate_state: DynrateState = read_dynrate_state(deps.storage)?;
+    dynrate_state.prev_yield_reserve =
+        if dynrate_state.prev_yield_reserve > Decimal256::from_uint256(buffer_amount) {
+            dynrate_state.prev_yield_reserve - Decimal256::from_uint256(buffer_amount)
+        } else {
+            Decimal256::zero()
+        };
+    store_dynrate_state(deps.storage, &dynrate_state)?;
+
+    let funds = if buffer_amount.is_zero() {
+        vec![]
+    } else {
+        vec![deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: config.stable_denom,
+                amount: buffer_amount.into(),
+            },
+        )?]
+    };
+
     Ok(Response::new()
-        .add_messages(liquidation_messages)
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: market_contract.to_string(),
-            funds: vec![],
-            msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+            contract_addr: market.to_string(),
+            funds,
+            msg: to_binary(&MarketExecuteMsg::WriteOffBadDebt {
                 borrower: borrower.to_string(),
-                prev_balance,
             })?,
-        })))
+        }))
+        .add_attributes(vec![
+            attr("action", "write_off_bad_debt"),
+            attr("borrower", borrower),
+            attr("loan_amount", loan_amount),
+            attr("buffer_amount", buffer_amount),
+        ]))
 }
 
 pub fn query_collaterals(deps: Deps, borrower: Addr) -> StdResult<CollateralsResponse> {
@@ -239,54 +820,471 @@ pub fn query_all_collaterals(
 #[allow(clippy::ptr_arg)]
 pub(crate) fn compute_borrow_limit(
     deps: Deps,
+    borrower: &CanonicalAddr,
     collaterals: &Tokens,
+    block_height: u64,
     block_time: Option<u64>,
 ) -> StdResult<(Uint256, Vec<Decimal256>)> {
+    let (borrow_limit, _, collateral_prices) =
+        compute_collateral_limits(deps, borrower, collaterals, block_height, block_time)?;
+
+    Ok((borrow_limit, collateral_prices))
+}
+
+/// Compute both the borrow limit (`max_ltv`) and the liquidation limit
+/// (`liquidation_threshold`) of the collaterals in stable denom.
+/// Loans are only liquidatable once they exceed the liquidation limit
+#[allow(clippy::ptr_arg)]
+pub(crate) fn compute_collateral_limits(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    block_height: u64,
+    block_time: Option<u64>,
+) -> StdResult<(Uint256, Uint256, Vec<Decimal256>)> {
+    compute_collateral_limits_with_cache(
+        deps,
+        borrower,
+        collaterals,
+        block_height,
+        block_time,
+        &mut HashMap::new(),
+    )
+}
+
+/// Same as `compute_collateral_limits`, but reuses the collateral prices
+/// already queried for other borrowers
+#[allow(clippy::ptr_arg)]
+fn compute_collateral_limits_with_cache(
+    deps: Deps,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+    block_height: u64,
+    block_time: Option<u64>,
+    price_cache: &mut HashMap<CanonicalAddr, Decimal256>,
+) -> StdResult<(Uint256, Uint256, Vec<Decimal256>)> {
     let config: Config = read_config(deps.storage)?;
     let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let emode_category = read_active_emode_category(deps.storage, borrower, collaterals)?;
+
+    // prices are frozen once the protocol is shut down
+    if let Some(shutdown) = read_shutdown(deps.storage)? {
+        price_cache.extend(shutdown.prices);
+    }
 
     let mut borrow_limit: Uint256 = Uint256::zero();
+    let mut liquidation_limit: Uint256 = Uint256::zero();
     let mut collateral_prices: Vec<Decimal256> = vec![];
     for collateral in collaterals.iter() {
         let collateral_token = collateral.0.clone();
         let collateral_amount = collateral.1;
 
-        let price: PriceResponse = query_price(
-            deps,
-            oracle_contract.clone(),
-            (deps.api.addr_humanize(&collateral_token)?).to_string(),
-            config.stable_denom.to_string(),
-            block_time.map(|block_time| TimeConstraints {
-                block_time,
-                valid_timeframe: config.price_timeframe,
-            }),
+        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let price = match price_cache.get(&collateral_token) {
+            Some(price) => *price,
+            None => {
+                let time_constraints = block_time.map(|block_time| TimeConstraints {
+                    block_time,
+                    valid_timeframe: config.price_timeframe,
+                });
+
+                let price: Decimal256 = if elem.is_aterra {
+                    // aterra is worth its share of the market deposits
+                    query_epoch_state(
+                        deps,
+                        deps.api.addr_humanize(&config.market_contract)?,
+                        block_height,
+                        None,
+                    )?
+                    .exchange_rate
+                } else {
+                    // TWAP valuation smooths out single price update wicks
+                    match config.price_twap_window {
+                        Some(window_seconds) => query_twap(
+                            deps,
+                            oracle_contract.clone(),
+                            (deps.api.addr_humanize(&collateral_token)?).to_string(),
+                            config.stable_denom.to_string(),
+                            window_seconds,
+                            time_constraints,
+                        )?,
+                        None => query_price(
+                            deps,
+                            oracle_contract.clone(),
+                            (deps.api.addr_humanize(&collateral_token)?).to_string(),
+                            config.stable_denom.to_string(),
+                            time_constraints,
+                        )?,
+                    }
+                    .rate
+                };
+
+                price_cache.insert(collateral_token, price);
+                price
+            }
+        };
+
+        let (max_ltv, liquidation_threshold) = match &emode_category {
+            Some(category) => (category.max_ltv, category.liquidation_threshold),
+            None => (elem.max_ltv, elem.liquidation_threshold),
+        };
+
+        let collateral_value = collateral_amount * price;
+        borrow_limit += collateral_value * ramp_down(max_ltv, &elem, block_height);
+        liquidation_limit +=
+            collateral_value * ramp_down(liquidation_threshold, &elem, block_height);
+        collateral_prices.push(price);
+    }
+
+    // returns borrow_limit and liquidation_limit with collaterals value in stable denom
+    Ok((borrow_limit, liquidation_limit, collateral_prices))
+}
+
+/// max_ltv of the collateral at the given height; delisted collaterals
+/// are linearly ramped down to zero
+pub(crate) fn effective_max_ltv(elem: &WhitelistElem, block_height: u64) -> Decimal256 {
+    ramp_down(elem.max_ltv, elem, block_height)
+}
+
+/// Ramps max_ltv or liquidation_threshold down to zero over the delisting
+/// period, so positions still backed by a delisted collateral become liquidatable
+fn ramp_down(ratio: Decimal256, elem: &WhitelistElem, block_height: u64) -> Decimal256 {
+    match &elem.delisting {
+        None => ratio,
+        Some(delisting) => {
+            let end_height = delisting.start_height + delisting.ramp_blocks;
+            if block_height >= end_height {
+                Decimal256::zero()
+            } else {
+                ratio * Decimal256::from_ratio(end_height - block_height, delisting.ramp_blocks)
+            }
+        }
+    }
+}
+
+/// Returns the e-mode category of the borrower, as long as
+/// all the collaterals belong to it
+#[allow(clippy::ptr_arg)]
+pub(crate) fn read_active_emode_category(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    collaterals: &Tokens,
+) -> StdResult<Option<EModeCategory>> {
+    let category_id = match read_borrower_emode(storage, borrower)? {
+        Some(category_id) => category_id,
+        None => return Ok(None),
+    };
+
+    let category: EModeCategory = read_emode_category(storage, category_id)?;
+    if collaterals
+        .iter()
+        .all(|collateral| category.collaterals.contains(&collateral.0))
+    {
+        Ok(Some(category))
+    } else {
+        Ok(None)
+    }
+}
+
+#[allow(clippy::ptr_arg)]
+fn decrease_total_collaterals(storage: &mut dyn Storage, collaterals: &Tokens) -> StdResult<()> {
+    for collateral in collaterals.iter() {
+        let total_collateral = read_total_collateral(storage, &collateral.0);
+
+        let total_collateral = if total_collateral > collateral.1 {
+            total_collateral - collateral.1
+        } else {
+            Uint256::zero()
+        };
+
+        store_total_collateral(storage, &collateral.0, &total_collateral)?;
+    }
+
+    Ok(())
+}
+
+/// Unlock and withdraw collaterals of a borrower who just repaid
+/// Executor: market
+pub fn unlock_collateral_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    collaterals_human: TokensHuman,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.market_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    unlock_collateral(deps, env, borrower, collaterals_human, true)
+}
+
+pub fn update_isolated_debt(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    loan_amount: Uint256,
+    interest_index: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.market_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+
+    // release the previously recorded loan
+    if let Some(isolated_loan) = read_isolated_loan(deps.storage, &borrower_raw)? {
+        let isolated_debt = read_isolated_debt(deps.storage, &isolated_loan.collateral_token);
+        let principal = isolated_loan.principal();
+        let isolated_debt = if isolated_debt > principal {
+            isolated_debt - principal
+        } else {
+            Decimal256::zero()
+        };
+
+        store_isolated_debt(
+            deps.storage,
+            &isolated_loan.collateral_token,
+            &isolated_debt,
         )?;
+        remove_isolated_loan(deps.storage, &borrower_raw);
+    }
 
-        let elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
-        let collateral_value = collateral_amount * price.rate;
-        borrow_limit += collateral_value * elem.max_ltv;
-        collateral_prices.push(price.rate);
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    if let Some((collateral_token, _)) = find_isolated_collateral(deps.storage, &collaterals)? {
+        if !loan_amount.is_zero() {
+            let isolated_loan = IsolatedLoan {
+                collateral_token,
+                loan_amount,
+                interest_index,
+            };
+            let isolated_debt = read_isolated_debt(deps.storage, &isolated_loan.collateral_token);
+            store_isolated_debt(
+                deps.storage,
+                &isolated_loan.collateral_token,
+                &(isolated_debt + isolated_loan.principal()),
+            )?;
+            store_isolated_loan(deps.storage, &borrower_raw, &isolated_loan)?;
+        }
     }
 
-    // returns borrow_limit with collaterals value in stable denom
-    Ok((borrow_limit, collateral_prices))
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_isolated_debt"),
+        attr("borrower", borrower),
+        attr("loan_amount", loan_amount),
+    ]))
+}
+
+/// Returns the first collateral with a debt ceiling and its ceiling
+#[allow(clippy::ptr_arg)]
+fn find_isolated_collateral(
+    storage: &dyn Storage,
+    collaterals: &Tokens,
+) -> StdResult<Option<(CanonicalAddr, Uint256)>> {
+    for collateral in collaterals.iter() {
+        let elem: WhitelistElem = read_whitelist_elem(storage, &collateral.0)?;
+        if let Some(debt_ceiling) = elem.debt_ceiling {
+            return Ok(Some((collateral.0.clone(), debt_ceiling)));
+        }
+    }
+
+    Ok(None)
 }
 
 pub fn query_borrow_limit(
     deps: Deps,
+    env: Env,
     borrower: Addr,
     block_time: Option<u64>,
 ) -> StdResult<BorrowLimitResponse> {
-    let collaterals = read_collaterals(
-        deps.storage,
-        &deps.api.addr_canonicalize(borrower.as_str())?,
-    );
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals = read_collaterals(deps.storage, &borrower_raw);
 
     // Compute borrow limit with collaterals
-    let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    let (mut borrow_limit, _) = compute_borrow_limit(
+        deps,
+        &borrower_raw,
+        &collaterals,
+        env.block.height,
+        block_time,
+    )?;
+
+    // Isolated borrowers share the ceiling left by the other borrowers,
+    // whose loans are accrued to the current market interest index
+    let isolated_collateral = find_isolated_collateral(deps.storage, &collaterals)?;
+    if let Some((collateral_token, debt_ceiling)) = isolated_collateral.clone() {
+        let own_principal = match read_isolated_loan(deps.storage, &borrower_raw)? {
+            Some(isolated_loan) if isolated_loan.collateral_token == collateral_token => {
+                isolated_loan.principal()
+            }
+            _ => Decimal256::zero(),
+        };
+
+        let isolated_debt = read_isolated_debt(deps.storage, &collateral_token);
+        let others_principal = if isolated_debt > own_principal {
+            isolated_debt - own_principal
+        } else {
+            Decimal256::zero()
+        };
+
+        let config: Config = read_config(deps.storage)?;
+        let market_state = query_market_state(
+            deps,
+            deps.api.addr_humanize(&config.market_contract)?,
+            env.block.height,
+        )?;
+        let others_debt = Uint256::one() * (others_principal * market_state.global_interest_index);
+
+        let ceiling_left = if debt_ceiling > others_debt {
+            debt_ceiling - others_debt
+        } else {
+            Uint256::zero()
+        };
+
+        borrow_limit = min(borrow_limit, ceiling_left);
+    }
 
     Ok(BorrowLimitResponse {
         borrower: borrower.to_string(),
         borrow_limit,
+        isolated: isolated_collateral.is_some(),
+    })
+}
+
+pub fn query_borrower_health(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<BorrowerHealthResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals = read_collaterals(deps.storage, &borrower_raw);
+
+    let (borrow_limit, liquidation_limit, collateral_prices) =
+        compute_collateral_limits(deps, &borrower_raw, &collaterals, env.block.height, None)?;
+    let emode_category = read_active_emode_category(deps.storage, &borrower_raw, &collaterals)?;
+    let loan_amount = query_borrower_info(
+        deps,
+        deps.api.addr_humanize(&config.market_contract)?,
+        borrower.clone(),
+        env.block.height,
+    )?
+    .loan_amount;
+
+    let mut total_value = Uint256::zero();
+    let mut collateral_elems: Vec<CollateralHealthElem> = vec![];
+    for (collateral, price) in collaterals.iter().zip(collateral_prices) {
+        let elem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let liquidation_threshold = ramp_down(
+            match &emode_category {
+                Some(category) => category.liquidation_threshold,
+                None => elem.liquidation_threshold,
+            },
+            &elem,
+            env.block.height,
+        );
+        let value = collateral.1 * price;
+        total_value += value;
+
+        // liquidation_limit of the other collaterals + amount * price * threshold = loan_amount
+        let other_liquidation_limit = liquidation_limit - value * liquidation_threshold;
+        let liquidation_denominator =
+            Decimal256::from_uint256(collateral.1) * liquidation_threshold;
+        let liquidation_price =
+            if loan_amount <= other_liquidation_limit || liquidation_denominator.is_zero() {
+                None
+            } else {
+                Some(
+                    Decimal256::from_uint256(loan_amount - other_liquidation_limit)
+                        / liquidation_denominator,
+                )
+            };
+
+        collateral_elems.push(CollateralHealthElem {
+            collateral_token: deps.api.addr_humanize(&collateral.0)?.to_string(),
+            amount: collateral.1,
+            price,
+            value,
+            liquidation_price,
+        });
+    }
+
+    let ltv = if total_value.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_uint256(loan_amount) / Decimal256::from_uint256(total_value)
+    };
+
+    Ok(BorrowerHealthResponse {
+        borrower: borrower.to_string(),
+        loan_amount,
+        borrow_limit,
+        liquidation_limit,
+        ltv,
+        collaterals: collateral_elems,
+    })
+}
+
+pub fn query_liquidatable_borrowers(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<LiquidatableBorrowersResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let all_collaterals: Vec<CollateralsResponse> = read_all_collaterals(deps, start_after, limit)?;
+
+    // collaterals are priced once per page
+    let mut price_cache: HashMap<CanonicalAddr, Decimal256> = HashMap::new();
+    let mut borrowers: Vec<LiquidatableBorrowersResponseElem> = vec![];
+    for elem in all_collaterals.iter() {
+        let collaterals: Tokens = elem.collaterals.to_raw(deps)?;
+        let (borrow_limit, liquidation_limit, _) = compute_collateral_limits_with_cache(
+            deps,
+            &deps.api.addr_canonicalize(&elem.borrower)?,
+            &collaterals,
+            env.block.height,
+            None,
+            &mut price_cache,
+        )?;
+
+        let borrower = deps.api.addr_validate(&elem.borrower)?;
+        let loan_amount =
+            query_borrower_info(deps, market.clone(), borrower, env.block.height)?.loan_amount;
+
+        if loan_amount > liquidation_limit {
+            borrowers.push(LiquidatableBorrowersResponseElem {
+                borrower: elem.borrower.to_string(),
+                loan_amount,
+                borrow_limit,
+                liquidation_limit,
+            });
+        }
+    }
+
+    Ok(LiquidatableBorrowersResponse {
+        borrowers,
+        last_scanned: all_collaterals.last().map(|elem| elem.borrower.to_string()),
+    })
+}
+
+pub fn query_borrower_emode(deps: Deps, borrower: Addr) -> StdResult<BorrowerEModeResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    Ok(BorrowerEModeResponse {
+        borrower: borrower.to_string(),
+        category_id: read_borrower_emode(deps.storage, &borrower_raw)?,
+        active: read_active_emode_category(deps.storage, &borrower_raw, &collaterals)?.is_some(),
     })
 }
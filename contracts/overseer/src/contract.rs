@@ -1,35 +1,47 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Attribute, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps,
+    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, Uint128, WasmMsg,
 };
 use std::cmp::{max, min};
 
 use crate::collateral::{
-    liquidate_collateral, lock_collateral, query_all_collaterals, query_borrow_limit,
-    query_collaterals, unlock_collateral,
+    effective_max_ltv, liquidate_collateral, liquidate_collaterals, liquidation_failed,
+    lock_collateral, lock_collateral_for, query_all_collaterals, query_borrow_limit,
+    query_borrower_emode, query_borrower_health, query_collaterals, query_liquidatable_borrowers,
+    set_emode, settle_collateral, unlock_collateral, unlock_collateral_for, update_isolated_debt,
+    write_off_bad_debt,
 };
 use crate::error::ContractError;
-use crate::querier::query_epoch_state;
+use crate::querier::{query_epoch_state, query_market_config};
 
 use crate::state::{
-    read_config, read_dynrate_config, read_dynrate_state, read_epoch_state, read_whitelist,
-    read_whitelist_elem, store_config, store_dynrate_config, store_dynrate_state,
-    store_epoch_state, store_whitelist_elem, Config, DynrateConfig, DynrateState, EpochState,
-    WhitelistElem,
+    migrate_total_collaterals, migrate_whitelist, read_config, read_dynrate_config,
+    read_dynrate_state, read_emode_categories, read_epoch_state, read_paused_operations,
+    read_pending_change, read_pending_changes, read_shutdown, read_total_collateral,
+    read_whitelist, read_whitelist_elem, read_whitelist_elems, remove_pending_change,
+    remove_whitelist_elem, store_config, store_dynrate_config, store_dynrate_state,
+    store_emode_category, store_epoch_state, store_paused_operations, store_pending_change,
+    store_shutdown, store_whitelist_elem, Config, DelistingInfo, DynrateConfig, DynrateState,
+    EModeCategory, EpochState, PendingChange, Shutdown, WhitelistElem,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use moneymarket::access::{self, Role};
 use moneymarket::common::optional_addr_validate;
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, WhitelistResponse,
+    CollateralStatus, CollateralStatusElem, CollateralStatusResponse, ConfigResponse,
+    EModeCategoriesResponse, EModeCategoryResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    ParamChange, PausableOperation, PauseStatusResponse, PendingChangesResponse,
+    PendingChangesResponseElem, QueryMsg, ShutdownStateResponse, WhitelistResponse,
     WhitelistResponseElem,
 };
-use moneymarket::querier::{deduct_tax, query_balance};
+use moneymarket::ownership;
+use moneymarket::querier::{deduct_tax, query_balance, query_price, TimeConstraints};
 
 pub const BLOCKS_PER_YEAR: u128 = 4656810;
 
@@ -55,6 +67,8 @@ pub fn instantiate(
             buffer_distribution_factor: msg.buffer_distribution_factor,
             anc_purchase_factor: msg.anc_purchase_factor,
             price_timeframe: msg.price_timeframe,
+            price_twap_window: None,
+            timelock_period: None,
         },
     )?;
 
@@ -123,6 +137,8 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response>
     config.threshold_deposit_rate = new_rate;
     config.target_deposit_rate = new_rate;
     store_config(deps.storage, &config)?;
+    migrate_whitelist(deps.storage)?;
+    migrate_total_collaterals(deps.storage)?;
     Ok(Response::default())
 }
 
@@ -135,7 +151,6 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::UpdateConfig {
-            owner_addr,
             oracle_contract,
             liquidation_contract,
             threshold_deposit_rate,
@@ -144,17 +159,19 @@ pub fn execute(
             anc_purchase_factor,
             epoch_period,
             price_timeframe,
+            price_twap_window,
             dyn_rate_epoch,
             dyn_rate_maxchange,
             dyn_rate_yr_increase_expectation,
             dyn_rate_min,
             dyn_rate_max,
+            timelock_period,
         } => {
             let api = deps.api;
             update_config(
                 deps,
+                env,
                 info,
-                optional_addr_validate(api, owner_addr)?,
                 optional_addr_validate(api, oracle_contract)?,
                 optional_addr_validate(api, liquidation_contract)?,
                 threshold_deposit_rate,
@@ -163,19 +180,46 @@ pub fn execute(
                 anc_purchase_factor,
                 epoch_period,
                 price_timeframe,
+                price_twap_window,
                 dyn_rate_epoch,
                 dyn_rate_maxchange,
                 dyn_rate_yr_increase_expectation,
                 dyn_rate_min,
                 dyn_rate_max,
+                timelock_period,
             )
         }
+        ExecuteMsg::CancelPendingChange { change_id } => {
+            cancel_pending_change(deps, info, change_id)
+        }
+        ExecuteMsg::Shutdown {
+            repay_period,
+            prices,
+        } => shutdown(deps, env, info, repay_period, prices),
+        ExecuteMsg::Unpause { operations } => unpause(deps, info, operations),
+        ExecuteMsg::Pause { operations } => pause(deps, info, operations),
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_in)
+        }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::GrantRole { role, address } => {
+            let api = deps.api;
+            grant_role(deps, info, role, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RevokeRole { role, address } => {
+            let api = deps.api;
+            revoke_role(deps, info, role, api.addr_validate(&address)?)
+        }
         ExecuteMsg::Whitelist {
             name,
             symbol,
             collateral_token,
             custody_contract,
             max_ltv,
+            liquidation_threshold,
+            debt_ceiling,
         } => {
             let api = deps.api;
             register_whitelist(
@@ -186,20 +230,66 @@ pub fn execute(
                 api.addr_validate(&collateral_token)?,
                 api.addr_validate(&custody_contract)?,
                 max_ltv,
+                liquidation_threshold,
+                debt_ceiling,
             )
         }
         ExecuteMsg::UpdateWhitelist {
             collateral_token,
             custody_contract,
             max_ltv,
+            liquidation_threshold,
+            debt_ceiling,
         } => {
             let api = deps.api;
             update_whitelist(
                 deps,
+                env,
                 info,
                 api.addr_validate(&collateral_token)?,
                 optional_addr_validate(api, custody_contract)?,
                 max_ltv,
+                liquidation_threshold,
+                debt_ceiling,
+            )
+        }
+        ExecuteMsg::DelistCollateral {
+            collateral_token,
+            ramp_blocks,
+        } => {
+            let api = deps.api;
+            delist_collateral(
+                deps,
+                env,
+                info,
+                api.addr_validate(&collateral_token)?,
+                ramp_blocks,
+            )
+        }
+        ExecuteMsg::RemoveWhitelist { collateral_token } => {
+            let api = deps.api;
+            remove_whitelist(deps, env, info, api.addr_validate(&collateral_token)?)
+        }
+        ExecuteMsg::UpdateEModeCategory {
+            category_id,
+            name,
+            max_ltv,
+            liquidation_threshold,
+            collaterals,
+        } => {
+            let api = deps.api;
+            update_emode_category(
+                deps,
+                env,
+                info,
+                category_id,
+                name,
+                max_ltv,
+                liquidation_threshold,
+                collaterals
+                    .iter()
+                    .map(|collateral| api.addr_validate(collateral))
+                    .collect::<StdResult<Vec<Addr>>>()?,
             )
         }
         ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
@@ -207,23 +297,108 @@ pub fn execute(
             interest_buffer,
             distributed_interest,
         } => update_epoch_state(deps, env, info, interest_buffer, distributed_interest),
-        ExecuteMsg::LockCollateral { collaterals } => lock_collateral(deps, info, collaterals),
+        ExecuteMsg::UpdateIsolatedDebt {
+            borrower,
+            loan_amount,
+            interest_index,
+        } => {
+            let api = deps.api;
+            update_isolated_debt(
+                deps,
+                info,
+                api.addr_validate(&borrower)?,
+                loan_amount,
+                interest_index,
+            )
+        }
+        ExecuteMsg::UnlockCollateralFor {
+            borrower,
+            collaterals,
+        } => {
+            let api = deps.api;
+            unlock_collateral_for(deps, env, info, api.addr_validate(&borrower)?, collaterals)
+        }
+        ExecuteMsg::LockCollateralFor {
+            borrower,
+            collaterals,
+            borrow_amount,
+            to,
+        } => {
+            let api = deps.api;
+            lock_collateral_for(
+                deps,
+                info,
+                api.addr_validate(&borrower)?,
+                collaterals,
+                borrow_amount,
+                optional_addr_validate(api, to)?,
+            )
+        }
+        ExecuteMsg::LockCollateral { collaterals } => {
+            lock_collateral(deps, info.sender, collaterals)
+        }
         ExecuteMsg::UnlockCollateral { collaterals } => {
-            unlock_collateral(deps, env, info, collaterals)
+            unlock_collateral(deps, env, info.sender, collaterals, false)
         }
+        ExecuteMsg::SetEMode { category_id } => set_emode(deps, env, info, category_id),
         ExecuteMsg::LiquidateCollateral { borrower } => {
             let api = deps.api;
-            liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?)
+            liquidate_collateral(deps, env, info.sender, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::LiquidateCollaterals { borrowers } => {
+            let api = deps.api;
+            liquidate_collaterals(
+                deps,
+                env,
+                info,
+                borrowers
+                    .iter()
+                    .map(|borrower| api.addr_validate(borrower))
+                    .collect::<StdResult<Vec<Addr>>>()?,
+            )
+        }
+        ExecuteMsg::LiquidateCollateralHook {
+            borrower,
+            liquidator,
+        } => {
+            if info.sender != env.contract.address {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let api = deps.api;
+            liquidate_collateral(
+                deps,
+                env,
+                api.addr_validate(&liquidator)?,
+                api.addr_validate(&borrower)?,
+            )
+        }
+        ExecuteMsg::WriteOffBadDebt { borrower } => {
+            let api = deps.api;
+            write_off_bad_debt(deps, env, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::SettleCollateral { borrower } => {
+            let api = deps.api;
+            settle_collateral(deps, env, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::ExecutePendingChange { change_id } => {
+            execute_pending_change(deps, env, change_id)
         }
         ExecuteMsg::FundReserve {} => fund_reserve(deps, info),
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    // only failed LiquidateCollaterals liquidations are replied
+    liquidation_failed(deps, msg)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
     oracle_contract: Option<Addr>,
     liquidation_contract: Option<Addr>,
     threshold_deposit_rate: Option<Decimal256>,
@@ -232,35 +407,43 @@ pub fn update_config(
     anc_purchase_factor: Option<Decimal256>,
     epoch_period: Option<u64>,
     price_timeframe: Option<u64>,
+    price_twap_window: Option<u64>,
     dyn_rate_epoch: Option<u64>,
     dyn_rate_maxchange: Option<Decimal256>,
     dyn_rate_yr_increase_expectation: Option<Decimal256>,
     dyn_rate_min: Option<Decimal256>,
     dyn_rate_max: Option<Decimal256>,
+    timelock_period: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     let mut dynrate_config: DynrateConfig = read_dynrate_config(deps.storage)?;
 
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(&owner_addr.to_string())?;
-    }
-
-    if let Some(oracle_contract) = oracle_contract {
-        config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract.to_string())?;
-    }
-
-    if let Some(liquidation_contract) = liquidation_contract {
-        config.liquidation_contract = deps
-            .api
-            .addr_canonicalize(&liquidation_contract.to_string())?;
-    }
-
-    if let Some(threshold_deposit_rate) = threshold_deposit_rate {
-        config.threshold_deposit_rate = threshold_deposit_rate;
+    // the owner can update every field, role members only the fields of their role
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr {
+        let owner_fields = oracle_contract.is_some()
+            || liquidation_contract.is_some()
+            || epoch_period.is_some()
+            || timelock_period.is_some();
+        let risk_fields = threshold_deposit_rate.is_some()
+            || target_deposit_rate.is_some()
+            || price_timeframe.is_some()
+            || price_twap_window.is_some()
+            || dyn_rate_epoch.is_some()
+            || dyn_rate_maxchange.is_some()
+            || dyn_rate_yr_increase_expectation.is_some()
+            || dyn_rate_min.is_some()
+            || dyn_rate_max.is_some();
+        let treasury_fields = buffer_distribution_factor.is_some() || anc_purchase_factor.is_some();
+
+        if owner_fields
+            || !(risk_fields || treasury_fields)
+            || (risk_fields && !access::has_role(deps.storage, &Role::RiskAdmin, &sender_raw)?)
+            || (treasury_fields
+                && !access::has_role(deps.storage, &Role::TreasuryAdmin, &sender_raw)?)
+        {
+            return Err(ContractError::Unauthorized {});
+        }
     }
 
     if let Some(buffer_distribution_factor) = buffer_distribution_factor {
@@ -271,18 +454,10 @@ pub fn update_config(
         config.anc_purchase_factor = anc_purchase_factor;
     }
 
-    if let Some(target_deposit_rate) = target_deposit_rate {
-        config.target_deposit_rate = target_deposit_rate;
-    }
-
     if let Some(epoch_period) = epoch_period {
         config.epoch_period = epoch_period;
     }
 
-    if let Some(price_timeframe) = price_timeframe {
-        config.price_timeframe = price_timeframe;
-    }
-
     if let Some(dyn_rate_epoch) = dyn_rate_epoch {
         dynrate_config.dyn_rate_epoch = dyn_rate_epoch;
     }
@@ -306,9 +481,110 @@ pub fn update_config(
     store_config(deps.storage, &config)?;
     store_dynrate_config(deps.storage, &dynrate_config)?;
 
-    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+    let mut attributes = vec![attr("action", "update_config")];
+
+    // risk-sensitive fields wait for the timelock
+    if oracle_contract.is_some()
+        || liquidation_contract.is_some()
+        || threshold_deposit_rate.is_some()
+        || target_deposit_rate.is_some()
+        || price_timeframe.is_some()
+        || price_twap_window.is_some()
+        || timelock_period.is_some()
+    {
+        let change = ParamChange::Config {
+            oracle_contract: oracle_contract.map(|addr| addr.to_string()),
+            liquidation_contract: liquidation_contract.map(|addr| addr.to_string()),
+            threshold_deposit_rate,
+            target_deposit_rate,
+            price_timeframe,
+            price_twap_window,
+            timelock_period,
+        };
+
+        attributes.extend(schedule_param_change(deps, env, &config, change)?);
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::propose_new_owner(
+        deps.storage,
+        deps.api,
+        &env,
+        owner,
+        expires_in,
+    )?)
 }
 
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner_addr = ownership::accept_ownership(deps.storage, deps.api, &env, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(ownership::cancel_ownership_proposal(deps.storage)?)
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::grant_role(deps.storage, deps.api, role, address)?)
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(access::revoke_role(deps.storage, deps.api, role, address)?)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn register_whitelist(
     deps: DepsMut,
     info: MessageInfo,
@@ -317,17 +593,30 @@ pub fn register_whitelist(
     collateral_token: Addr,
     custody_contract: Addr,
     max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+    debt_ceiling: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
+    if liquidation_threshold < max_ltv || liquidation_threshold > Decimal256::one() {
+        return Err(ContractError::InvalidLiquidationThreshold {});
+    }
+
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
     if read_whitelist_elem(deps.storage, &collateral_token_raw).is_ok() {
         return Err(ContractError::TokenAlreadyRegistered {});
     }
 
+    // the market aterra is priced and liquidated through the market itself
+    let market_config = query_market_config(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.market_contract)?,
+    )?;
+    let is_aterra = market_config.aterra_contract == collateral_token.as_str();
+
     store_whitelist_elem(
         deps.storage,
         &collateral_token_raw,
@@ -336,25 +625,551 @@ pub fn register_whitelist(
             symbol: symbol.to_string(),
             custody_contract: deps.api.addr_canonicalize(custody_contract.as_str())?,
             max_ltv,
+            liquidation_threshold,
+            delisting: None,
+            debt_ceiling,
+            is_aterra,
         },
     )?;
 
-    Ok(Response::new().add_attributes(vec![
+    let mut attributes = vec![
         attr("action", "register_whitelist"),
         attr("name", name),
         attr("symbol", symbol),
         attr("collateral_token", collateral_token),
         attr("custody_contract", custody_contract),
         attr("LTV", max_ltv.to_string()),
-    ]))
+        attr("liquidation_threshold", liquidation_threshold.to_string()),
+    ];
+    if let Some(debt_ceiling) = debt_ceiling {
+        attributes.push(attr("debt_ceiling", debt_ceiling));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_whitelist(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     collateral_token: Addr,
     custody_contract: Option<Addr>,
     max_ltv: Option<Decimal256>,
+    liquidation_threshold: Option<Decimal256>,
+    debt_ceiling: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner_addr,
+        &Role::RiskAdmin,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let change = ParamChange::Whitelist {
+        collateral_token: collateral_token.to_string(),
+        custody_contract: custody_contract.map(|addr| addr.to_string()),
+        max_ltv,
+        liquidation_threshold,
+        debt_ceiling,
+    };
+
+    let mut attributes = vec![
+        attr("action", "update_whitelist"),
+        attr("collateral_token", collateral_token),
+    ];
+    attributes.extend(schedule_param_change(deps, env, &config, change)?);
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Apply the change right away without a timelock,
+/// otherwise validate it against the current state and queue it
+fn schedule_param_change(
+    deps: DepsMut,
+    env: Env,
+    config: &Config,
+    change: ParamChange,
+) -> Result<Vec<Attribute>, ContractError> {
+    let timelock_period = config.timelock_period.unwrap_or(0);
+    if timelock_period == 0 {
+        return apply_param_change(deps, &env, change);
+    }
+
+    compute_param_change(deps.as_ref(), &env, &change)?;
+
+    let executable_at = env.block.height + timelock_period;
+    let change_id = store_pending_change(
+        deps.storage,
+        &PendingChange {
+            change,
+            executable_at,
+        },
+    )?;
+
+    Ok(vec![
+        attr("change_id", change_id.to_string()),
+        attr("executable_at", executable_at.to_string()),
+    ])
+}
+
+/// Resulting state of a parameter change
+enum ParamChangeResult {
+    Config(Config),
+    Whitelist(CanonicalAddr, WhitelistElem),
+    EModeCategory(u8, EModeCategory),
+    Delisting(CanonicalAddr, WhitelistElem),
+}
+
+fn compute_param_change(
+    deps: Deps,
+    env: &Env,
+    change: &ParamChange,
+) -> Result<ParamChangeResult, ContractError> {
+    match change.clone() {
+        ParamChange::Config {
+            oracle_contract,
+            liquidation_contract,
+            threshold_deposit_rate,
+            target_deposit_rate,
+            price_timeframe,
+            price_twap_window,
+            timelock_period,
+        } => {
+            let mut config: Config = read_config(deps.storage)?;
+
+            if let Some(oracle_contract) = oracle_contract {
+                config.oracle_contract = deps.api.addr_canonicalize(&oracle_contract)?;
+            }
+
+            if let Some(liquidation_contract) = liquidation_contract {
+                config.liquidation_contract = deps.api.addr_canonicalize(&liquidation_contract)?;
+            }
+
+            if let Some(threshold_deposit_rate) = threshold_deposit_rate {
+                config.threshold_deposit_rate = threshold_deposit_rate;
+            }
+
+            if let Some(target_deposit_rate) = target_deposit_rate {
+                config.target_deposit_rate = target_deposit_rate;
+            }
+
+            if let Some(price_timeframe) = price_timeframe {
+                config.price_timeframe = price_timeframe;
+            }
+
+            if let Some(price_twap_window) = price_twap_window {
+                config.price_twap_window = if price_twap_window == 0 {
+                    None
+                } else {
+                    Some(price_twap_window)
+                };
+            }
+
+            if let Some(timelock_period) = timelock_period {
+                config.timelock_period = if timelock_period == 0 {
+                    None
+                } else {
+                    Some(timelock_period)
+                };
+            }
+
+            Ok(ParamChangeResult::Config(config))
+        }
+        ParamChange::Whitelist {
+            collateral_token,
+            custody_contract,
+            max_ltv,
+            liquidation_threshold,
+            debt_ceiling,
+        } => {
+            let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+            let mut whitelist_elem: WhitelistElem =
+                read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+
+            if let Some(custody_contract) = custody_contract {
+                whitelist_elem.custody_contract = deps.api.addr_canonicalize(&custody_contract)?;
+            }
+
+            if let Some(max_ltv) = max_ltv {
+                // max_ltv is driven by the ramp while delisting
+                if whitelist_elem.delisting.is_some() {
+                    return Err(ContractError::CollateralDelisted {});
+                }
+
+                whitelist_elem.max_ltv = max_ltv;
+            }
+
+            if let Some(liquidation_threshold) = liquidation_threshold {
+                whitelist_elem.liquidation_threshold = liquidation_threshold;
+            }
+
+            if let Some(debt_ceiling) = debt_ceiling {
+                if debt_ceiling.is_zero() {
+                    whitelist_elem.debt_ceiling = None;
+                } else {
+                    whitelist_elem.debt_ceiling = Some(debt_ceiling);
+                }
+            }
+
+            if whitelist_elem.liquidation_threshold < whitelist_elem.max_ltv
+                || whitelist_elem.liquidation_threshold > Decimal256::one()
+            {
+                return Err(ContractError::InvalidLiquidationThreshold {});
+            }
+
+            Ok(ParamChangeResult::Whitelist(
+                collateral_token_raw,
+                whitelist_elem,
+            ))
+        }
+        ParamChange::EModeCategory {
+            category_id,
+            name,
+            max_ltv,
+            liquidation_threshold,
+            collaterals,
+        } => {
+            if liquidation_threshold < max_ltv || liquidation_threshold > Decimal256::one() {
+                return Err(ContractError::InvalidLiquidationThreshold {});
+            }
+
+            let collaterals_raw = collaterals
+                .iter()
+                .map(|collateral| {
+                    let collateral_raw = deps.api.addr_canonicalize(collateral)?;
+
+                    // only whitelisted collaterals can join a category
+                    read_whitelist_elem(deps.storage, &collateral_raw)?;
+                    Ok(collateral_raw)
+                })
+                .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+
+            Ok(ParamChangeResult::EModeCategory(
+                category_id,
+                EModeCategory {
+                    name,
+                    max_ltv,
+                    liquidation_threshold,
+                    collaterals: collaterals_raw,
+                },
+            ))
+        }
+        ParamChange::DelistCollateral {
+            collateral_token,
+            ramp_blocks,
+        } => {
+            // the ramp gives borrowers at least the notice of any other LTV cut
+            let min_ramp_blocks = max(1, read_config(deps.storage)?.timelock_period.unwrap_or(0));
+            if ramp_blocks < min_ramp_blocks {
+                return Err(ContractError::DelistingRampTooShort(min_ramp_blocks));
+            }
+
+            let collateral_token_raw = deps.api.addr_canonicalize(&collateral_token)?;
+            let mut whitelist_elem: WhitelistElem =
+                read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+            if whitelist_elem.delisting.is_some() {
+                return Err(ContractError::CollateralDelisted {});
+            }
+
+            whitelist_elem.delisting = Some(DelistingInfo {
+                start_height: env.block.height,
+                ramp_blocks,
+            });
+
+            Ok(ParamChangeResult::Delisting(
+                collateral_token_raw,
+                whitelist_elem,
+            ))
+        }
+    }
+}
+
+fn apply_param_change(
+    deps: DepsMut,
+    env: &Env,
+    change: ParamChange,
+) -> Result<Vec<Attribute>, ContractError> {
+    match compute_param_change(deps.as_ref(), env, &change)? {
+        ParamChangeResult::Config(config) => {
+            store_config(deps.storage, &config)?;
+
+            Ok(vec![])
+        }
+        ParamChangeResult::Whitelist(collateral_token_raw, whitelist_elem) => {
+            store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
+
+            Ok(vec![
+                attr(
+                    "custody_contract",
+                    deps.api.addr_humanize(&whitelist_elem.custody_contract)?,
+                ),
+                attr("LTV", whitelist_elem.max_ltv.to_string()),
+                attr(
+                    "liquidation_threshold",
+                    whitelist_elem.liquidation_threshold.to_string(),
+                ),
+            ])
+        }
+        ParamChangeResult::EModeCategory(category_id, category) => {
+            store_emode_category(deps.storage, category_id, &category)?;
+
+            Ok(vec![
+                attr("LTV", category.max_ltv.to_string()),
+                attr(
+                    "liquidation_threshold",
+                    category.liquidation_threshold.to_string(),
+                ),
+            ])
+        }
+        ParamChangeResult::Delisting(collateral_token_raw, whitelist_elem) => {
+            store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
+
+            let delisting_end_height = whitelist_elem
+                .delisting
+                .map(|delisting| delisting.start_height + delisting.ramp_blocks)
+                .unwrap_or_default();
+            Ok(vec![attr(
+                "delisting_end_height",
+                delisting_end_height.to_string(),
+            )])
+        }
+    }
+}
+
+pub fn execute_pending_change(
+    deps: DepsMut,
+    env: Env,
+    change_id: u64,
+) -> Result<Response, ContractError> {
+    let pending_change: PendingChange = read_pending_change(deps.storage, change_id)?;
+    if env.block.height < pending_change.executable_at {
+        return Err(ContractError::TimelockNotPassed(
+            pending_change.executable_at,
+        ));
+    }
+
+    remove_pending_change(deps.storage, change_id);
+
+    let mut attributes = vec![
+        attr("action", "execute_pending_change"),
+        attr("change_id", change_id.to_string()),
+    ];
+    attributes.extend(apply_param_change(deps, &env, pending_change.change)?);
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn cancel_pending_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    change_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // fails when the change does not exist
+    read_pending_change(deps.storage, change_id)?;
+    remove_pending_change(deps.storage, change_id);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_pending_change"),
+        attr("change_id", change_id.to_string()),
+    ]))
+}
+
+pub fn pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<PausableOperation>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner_addr,
+        &Role::Guardian,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut paused_operations = read_paused_operations(deps.storage)?;
+    for operation in operations.iter() {
+        if !paused_operations.contains(operation) {
+            paused_operations.push(operation.clone());
+        }
+    }
+
+    store_paused_operations(deps.storage, &paused_operations)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "pause"),
+        attr("operations", join_operations(&operations)),
+    ]))
+}
+
+pub fn unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<PausableOperation>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut paused_operations = read_paused_operations(deps.storage)?;
+    paused_operations.retain(|operation| !operations.contains(operation));
+
+    store_paused_operations(deps.storage, &paused_operations)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unpause"),
+        attr("operations", join_operations(&operations)),
+    ]))
+}
+
+/// Shut the protocol down for good. Collateral prices are frozen,
+/// the market stops accruing interest, and borrowers have `repay_period`
+/// blocks to repay before their collaterals get settled
+pub fn shutdown(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    repay_period: u64,
+    prices: Option<Vec<(String, Decimal256)>>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if read_shutdown(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
+    let mut price_overrides: Vec<(CanonicalAddr, Decimal256)> = vec![];
+    for (collateral_token, price) in prices.unwrap_or_default() {
+        price_overrides.push((
+            deps.api
+                .addr_canonicalize(deps.api.addr_validate(&collateral_token)?.as_str())?,
+            price,
+        ));
+    }
+
+    // freeze the price of every whitelisted collateral, falling back
+    // to the oracle for the collaterals without an override
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+    let mut frozen_prices: Vec<(CanonicalAddr, Decimal256)> = vec![];
+    let mut start_after: Option<CanonicalAddr> = None;
+    loop {
+        let elems = read_whitelist_elems(deps.storage, start_after, None)?;
+        if elems.is_empty() {
+            break;
+        }
+
+        start_after = elems
+            .last()
+            .map(|(collateral_token, _)| collateral_token.clone());
+        for (collateral_token, elem) in elems {
+            let price = match price_overrides
+                .iter()
+                .find(|(token, _)| *token == collateral_token)
+            {
+                Some((_, price)) => *price,
+                None if elem.is_aterra => {
+                    query_epoch_state(
+                        deps.as_ref(),
+                        deps.api.addr_humanize(&config.market_contract)?,
+                        env.block.height,
+                        None,
+                    )?
+                    .exchange_rate
+                }
+                None => {
+                    query_price(
+                        deps.as_ref(),
+                        oracle_contract.clone(),
+                        deps.api.addr_humanize(&collateral_token)?.to_string(),
+                        config.stable_denom.to_string(),
+                        Some(TimeConstraints {
+                            block_time: env.block.time.seconds(),
+                            valid_timeframe: config.price_timeframe,
+                        }),
+                    )?
+                    .rate
+                }
+            };
+            frozen_prices.push((collateral_token, price));
+        }
+    }
+
+    let repay_deadline = env.block.height + repay_period;
+    store_shutdown(
+        deps.storage,
+        &Shutdown {
+            height: env.block.height,
+            repay_deadline,
+            prices: frozen_prices,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.market_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::Shutdown {})?,
+        }))
+        .add_attributes(vec![
+            attr("action", "shutdown"),
+            attr("repay_deadline", repay_deadline.to_string()),
+        ]))
+}
+
+fn join_operations(operations: &[PausableOperation]) -> String {
+    operations
+        .iter()
+        .map(|operation| operation.as_str())
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+pub fn delist_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
+    ramp_blocks: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let change = ParamChange::DelistCollateral {
+        collateral_token: collateral_token.to_string(),
+        ramp_blocks,
+    };
+
+    let mut attributes = vec![
+        attr("action", "delist_collateral"),
+        attr("collateral_token", collateral_token),
+    ];
+    attributes.extend(schedule_param_change(deps, env, &config, change)?);
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn remove_whitelist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collateral_token: Addr,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
@@ -362,30 +1177,71 @@ pub fn update_whitelist(
     }
 
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
-    let mut whitelist_elem: WhitelistElem =
-        read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral_token_raw)?;
+    let delisting = match whitelist_elem.delisting {
+        Some(delisting) => delisting,
+        None => return Err(ContractError::CollateralNotDelisted {}),
+    };
 
-    if let Some(custody_contract) = custody_contract {
-        whitelist_elem.custody_contract = deps.api.addr_canonicalize(custody_contract.as_str())?;
+    let end_height = delisting.start_height + delisting.ramp_blocks;
+    if env.block.height < end_height {
+        return Err(ContractError::DelistingNotFinished(end_height));
     }
 
-    if let Some(max_ltv) = max_ltv {
-        whitelist_elem.max_ltv = max_ltv;
+    if !read_total_collateral(deps.storage, &collateral_token_raw).is_zero() {
+        return Err(ContractError::CollateralStillLocked {});
     }
 
-    store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
+    remove_whitelist_elem(deps.storage, &collateral_token_raw);
 
     Ok(Response::new().add_attributes(vec![
-        attr("action", "update_whitelist"),
+        attr("action", "remove_whitelist"),
         attr("collateral_token", collateral_token),
-        attr(
-            "custody_contract",
-            deps.api.addr_humanize(&whitelist_elem.custody_contract)?,
-        ),
-        attr("LTV", whitelist_elem.max_ltv.to_string()),
     ]))
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn update_emode_category(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    category_id: u8,
+    name: String,
+    max_ltv: Decimal256,
+    liquidation_threshold: Decimal256,
+    collaterals: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if !access::is_owner_or_role(
+        deps.storage,
+        &config.owner_addr,
+        &Role::RiskAdmin,
+        &deps.api.addr_canonicalize(info.sender.as_str())?,
+    )? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let change = ParamChange::EModeCategory {
+        category_id,
+        name: name.clone(),
+        max_ltv,
+        liquidation_threshold,
+        collaterals: collaterals
+            .iter()
+            .map(|collateral| collateral.to_string())
+            .collect(),
+    };
+
+    let mut attributes = vec![
+        attr("action", "update_emode_category"),
+        attr("category_id", category_id.to_string()),
+        attr("name", name),
+    ];
+    attributes.extend(schedule_param_change(deps, env, &config, change)?);
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
 fn update_deposit_rate(deps: DepsMut, env: Env) -> StdResult<()> {
     let dynrate_config: DynrateConfig = read_dynrate_config(deps.storage)?;
     let dynrate_state: DynrateState = read_dynrate_state(deps.storage)?;
@@ -471,6 +1327,10 @@ fn update_deposit_rate(deps: DepsMut, env: Env) -> StdResult<()> {
 }
 
 pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    if read_shutdown(deps.storage)?.is_some() {
+        return Err(ContractError::Shutdown {});
+    }
+
     let config: Config = read_config(deps.storage)?;
     let state: EpochState = read_epoch_state(deps.storage)?;
     if env.block.height < state.last_executed_height + config.epoch_period {
@@ -692,9 +1552,20 @@ pub fn fund_reserve(deps: DepsMut, info: MessageInfo) -> Result<Response, Contra
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&ownership::query_pending_owner(deps)?),
+        QueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_binary(&access::query_role_members(
+            deps,
+            role,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
         QueryMsg::EpochState {} => to_binary(&query_state(deps)?),
         QueryMsg::Whitelist {
             collateral_token,
@@ -706,6 +1577,12 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             optional_addr_validate(deps.api, start_after)?,
             limit,
         )?),
+        QueryMsg::CollateralStatus { start_after, limit } => to_binary(&query_collateral_status(
+            deps,
+            env,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
         QueryMsg::Collaterals { borrower } => to_binary(&query_collaterals(
             deps,
             deps.api.addr_validate(&borrower)?,
@@ -720,10 +1597,34 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             block_time,
         } => to_binary(&query_borrow_limit(
             deps,
+            env,
             deps.api.addr_validate(&borrower)?,
             block_time,
         )?),
+        QueryMsg::BorrowerHealth { borrower } => to_binary(&query_borrower_health(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::LiquidatableBorrowers { start_after, limit } => {
+            to_binary(&query_liquidatable_borrowers(
+                deps,
+                env,
+                optional_addr_validate(deps.api, start_after)?,
+                limit,
+            )?)
+        }
         QueryMsg::DynrateState {} => to_binary(&query_dynrate_state(deps)?),
+        QueryMsg::EModeCategories {} => to_binary(&query_emode_categories(deps)?),
+        QueryMsg::BorrowerEMode { borrower } => to_binary(&query_borrower_emode(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::PendingChanges { start_after, limit } => {
+            to_binary(&query_pending_changes(deps, start_after, limit)?)
+        }
+        QueryMsg::PauseStatus {} => to_binary(&query_pause_status(deps)?),
+        QueryMsg::ShutdownState {} => to_binary(&query_shutdown_state(deps)?),
     }
 }
 
@@ -749,6 +1650,8 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         buffer_distribution_factor: config.buffer_distribution_factor,
         anc_purchase_factor: config.anc_purchase_factor,
         price_timeframe: config.price_timeframe,
+        price_twap_window: config.price_twap_window,
+        timelock_period: config.timelock_period.unwrap_or(0),
         dyn_rate_epoch: dynrate_config.dyn_rate_epoch,
         dyn_rate_maxchange: dynrate_config.dyn_rate_maxchange,
         dyn_rate_yr_increase_expectation: dynrate_config.dyn_rate_yr_increase_expectation,
@@ -781,11 +1684,13 @@ pub fn query_whitelist(
                 name: whitelist_elem.name,
                 symbol: whitelist_elem.symbol,
                 max_ltv: whitelist_elem.max_ltv,
+                liquidation_threshold: whitelist_elem.liquidation_threshold,
                 custody_contract: deps
                     .api
                     .addr_humanize(&whitelist_elem.custody_contract)?
                     .to_string(),
                 collateral_token: collateral_token.to_string(),
+                debt_ceiling: whitelist_elem.debt_ceiling,
             }],
         })
     } else {
@@ -799,3 +1704,117 @@ pub fn query_whitelist(
         Ok(WhitelistResponse { elems: whitelist })
     }
 }
+
+pub fn query_collateral_status(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<CollateralStatusResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let elems: Vec<CollateralStatusElem> = read_whitelist_elems(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(collateral_token, elem)| {
+            let delisting_end_height = elem
+                .delisting
+                .as_ref()
+                .map(|delisting| delisting.start_height + delisting.ramp_blocks);
+            let status = match delisting_end_height {
+                None => CollateralStatus::Active,
+                Some(end_height) if env.block.height < end_height => CollateralStatus::Delisting,
+                Some(_) => CollateralStatus::Delisted,
+            };
+
+            Ok(CollateralStatusElem {
+                collateral_token: deps.api.addr_humanize(&collateral_token)?.to_string(),
+                status,
+                max_ltv: effective_max_ltv(&elem, env.block.height),
+                total_locked: read_total_collateral(deps.storage, &collateral_token),
+                delisting_end_height,
+            })
+        })
+        .collect::<StdResult<Vec<CollateralStatusElem>>>()?;
+
+    Ok(CollateralStatusResponse { elems })
+}
+
+pub fn query_emode_categories(deps: Deps) -> StdResult<EModeCategoriesResponse> {
+    let categories = read_emode_categories(deps.storage)?
+        .into_iter()
+        .map(|(category_id, category)| {
+            Ok(EModeCategoryResponse {
+                category_id,
+                name: category.name,
+                max_ltv: category.max_ltv,
+                liquidation_threshold: category.liquidation_threshold,
+                collaterals: category
+                    .collaterals
+                    .iter()
+                    .map(|collateral| Ok(deps.api.addr_humanize(collateral)?.to_string()))
+                    .collect::<StdResult<Vec<String>>>()?,
+            })
+        })
+        .collect::<StdResult<Vec<EModeCategoryResponse>>>()?;
+
+    Ok(EModeCategoriesResponse { categories })
+}
+
+pub fn query_pending_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingChangesResponse> {
+    let changes = read_pending_changes(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(change_id, pending_change)| PendingChangesResponseElem {
+            change_id,
+            change: pending_change.change,
+            executable_at: pending_change.executable_at,
+        })
+        .collect();
+
+    Ok(PendingChangesResponse { changes })
+}
+
+pub fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+    let paused_operations = read_paused_operations(deps.storage)?;
+    let is_paused = |operation: PausableOperation| paused_operations.contains(&operation);
+
+    Ok(PauseStatusResponse {
+        deposit: is_paused(PausableOperation::Deposit),
+        redeem: is_paused(PausableOperation::Redeem),
+        borrow: is_paused(PausableOperation::Borrow),
+        lock: is_paused(PausableOperation::Lock),
+        unlock: is_paused(PausableOperation::Unlock),
+        withdraw: is_paused(PausableOperation::Withdraw),
+        liquidate: is_paused(PausableOperation::Liquidate),
+        submit_bid: is_paused(PausableOperation::SubmitBid),
+    })
+}
+
+pub fn query_shutdown_state(deps: Deps) -> StdResult<ShutdownStateResponse> {
+    let shutdown = match read_shutdown(deps.storage)? {
+        Some(shutdown) => shutdown,
+        None => return Err(StdError::generic_err("Protocol is not shut down")),
+    };
+
+    Ok(ShutdownStateResponse {
+        height: shutdown.height,
+        repay_deadline: shutdown.repay_deadline,
+        prices: shutdown
+            .prices
+            .iter()
+            .map(|(collateral_token, price)| {
+                Ok((
+                    deps.api.addr_humanize(collateral_token)?.to_string(),
+                    *price,
+                ))
+            })
+            .collect::<StdResult<Vec<(String, Decimal256)>>>()?,
+    })
+}
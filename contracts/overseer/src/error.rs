@@ -1,4 +1,5 @@
 use cosmwasm_std::{OverflowError, StdError};
+use moneymarket::overseer::PausableOperation;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -12,15 +13,72 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Invalid reply ID")]
+    InvalidReplyId {},
+
+    #[error("Operation is paused: {0}")]
+    OperationPaused(PausableOperation),
+
+    #[error("Protocol is shut down")]
+    Shutdown {},
+
+    #[error("Protocol is not shut down")]
+    NotShutdown {},
+
+    #[error("Repay deadline has not passed yet; settleable at height: {0}")]
+    RepayDeadlineNotPassed(u64),
+
+    #[error("Repay deadline has passed at height: {0}; collaterals are being settled")]
+    RepayDeadlinePassed(u64),
+
+    #[error("Borrower has no collateralized loan to settle")]
+    NothingToSettle {},
+
     #[error("Cannot liquidate safely collateralized loan")]
     CannotLiquidateSafeLoan {},
 
+    #[error("Cannot write off a loan still backed by collaterals")]
+    BorrowerHasCollaterals {},
+
+    #[error("Borrower has no loan to write off")]
+    NoBadDebt {},
+
     #[error("An epoch has not passed yet; last executed height: {0}")]
     EpochNotPassed(u64),
 
+    #[error("Timelock has not passed yet; executable at height: {0}")]
+    TimelockNotPassed(u64),
+
     #[error("Token is already registered as collateral")]
     TokenAlreadyRegistered {},
 
+    #[error("Liquidation threshold must be between max LTV and 1")]
+    InvalidLiquidationThreshold {},
+
+    #[error("Collaterals do not belong to the e-mode category")]
+    EModeCollateralMismatch {},
+
+    #[error("Loan liability becomes greater than borrow limit: {0}")]
+    EModeLoanExceedsLimit(u128),
+
+    #[error("Isolated collateral cannot be combined with other collaterals")]
+    IsolatedCollateral {},
+
+    #[error("Collateral is being delisted")]
+    CollateralDelisted {},
+
+    #[error("Delisting ramp must be at least {0} blocks")]
+    DelistingRampTooShort(u64),
+
+    #[error("Collateral is not being delisted")]
+    CollateralNotDelisted {},
+
+    #[error("Collateral delisting ramp ends at height: {0}")]
+    DelistingNotFinished(u64),
+
+    #[error("Collateral is still locked by borrowers")]
+    CollateralStillLocked {},
+
     #[error("Unlock amount cannot exceed locked amount")]
     UnlockExceedsLocked {},
 
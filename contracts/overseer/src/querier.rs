@@ -3,10 +3,21 @@ use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
 
 use moneymarket::liquidation::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
 use moneymarket::market::{
-    BorrowerInfoResponse, EpochStateResponse, QueryMsg as MarketQueryMsg, StateResponse,
+    BorrowerInfoResponse, ConfigResponse as MarketConfigResponse, EpochStateResponse,
+    QueryMsg as MarketQueryMsg, StateResponse,
 };
 use moneymarket::tokens::TokensHuman;
 
+pub fn query_market_config(deps: Deps, market_addr: Addr) -> StdResult<MarketConfigResponse> {
+    let market_config: MarketConfigResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: market_addr.to_string(),
+            msg: to_binary(&MarketQueryMsg::Config {})?,
+        }))?;
+
+    Ok(market_config)
+}
+
 pub fn query_market_state(
     deps: Deps,
     market_addr: Addr,
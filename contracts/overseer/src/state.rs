@@ -5,16 +5,36 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, WhitelistResponseElem};
+use moneymarket::overseer::{
+    CollateralsResponse, ParamChange, PausableOperation, WhitelistResponseElem,
+};
 use moneymarket::tokens::Tokens;
 
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_DYNRATE_CONFIG: &[u8] = b"dynrate_config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
 const KEY_DYNRATE_STATE: &[u8] = b"dynrate_state";
+const KEY_PENDING_CHANGE_ID: &[u8] = b"pending_change_id";
+const KEY_PAUSED_OPERATIONS: &[u8] = b"paused_operations";
+const KEY_SHUTDOWN: &[u8] = b"shutdown";
+const KEY_LIQUIDATION_BATCH: &[u8] = b"liquidation_batch";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_TOTAL_COLLATERAL: &[u8] = b"total_collateral";
+const PREFIX_ISOLATED_DEBT: &[u8] = b"isolated_debt";
+const PREFIX_ISOLATED_LOAN: &[u8] = b"isolated_loan";
+const PREFIX_EMODE_CATEGORY: &[u8] = b"emode_category";
+const PREFIX_BORROWER_EMODE: &[u8] = b"borrower_emode";
+const PREFIX_PENDING_CHANGE: &[u8] = b"pending_change";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Shutdown {
+    pub height: u64,
+    pub repay_deadline: u64,
+    /// Collateral prices frozen at shutdown
+    pub prices: Vec<(CanonicalAddr, Decimal256)>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -30,6 +50,12 @@ pub struct Config {
     pub buffer_distribution_factor: Decimal256,
     pub anc_purchase_factor: Decimal256,
     pub price_timeframe: u64,
+    /// Collaterals are valued with the oracle TWAP over this window (seconds)
+    /// instead of the spot price, when set
+    pub price_twap_window: Option<u64>,
+    /// # of blocks risk-sensitive changes wait before they can be executed;
+    /// changes apply immediately when unset
+    pub timelock_period: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -62,7 +88,70 @@ pub struct WhitelistElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+    pub custody_contract: CanonicalAddr,
+    /// Set once the collateral is being retired
+    pub delisting: Option<DelistingInfo>,
+    /// Isolated collaterals cannot be combined with other collaterals
+    /// and their total loan is capped by the ceiling
+    pub debt_ceiling: Option<Uint256>,
+    /// The market aterra, valued at the market exchange rate
+    /// and burned against the loan when liquidated
+    #[serde(default)]
+    pub is_aterra: bool,
+}
+
+/// Whitelist element as stored before the liquidation threshold was introduced
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct LegacyWhitelistElem {
+    pub name: String,
+    pub symbol: String,
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Option<Decimal256>,
     pub custody_contract: CanonicalAddr,
+    pub delisting: Option<DelistingInfo>,
+    pub debt_ceiling: Option<Uint256>,
+    #[serde(default)]
+    pub is_aterra: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelistingInfo {
+    pub start_height: u64,
+    /// max_ltv is ramped linearly to zero over this many blocks
+    pub ramp_blocks: u64,
+}
+
+/// Loan of a borrower attributed to its isolated collateral
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsolatedLoan {
+    pub collateral_token: CanonicalAddr,
+    pub loan_amount: Uint256,
+    /// Market global interest index the loan amount was computed at
+    pub interest_index: Decimal256,
+}
+
+impl IsolatedLoan {
+    /// Loan amount at a global interest index of one,
+    /// so the loan accrues interest with the market index
+    pub fn principal(&self) -> Decimal256 {
+        Decimal256::from_uint256(self.loan_amount) / self.interest_index
+    }
+}
+
+/// Efficiency mode category of correlated collaterals with boosted parameters
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EModeCategory {
+    pub name: String,
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+    pub collaterals: Vec<CanonicalAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChange {
+    pub change: ParamChange,
+    pub executable_at: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -108,6 +197,60 @@ pub fn store_whitelist_elem(
     Ok(())
 }
 
+/// Default the liquidation threshold of the collaterals whitelisted
+/// before it was introduced to their max_ltv
+pub fn migrate_whitelist(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_elems: Vec<(Vec<u8>, LegacyWhitelistElem)> =
+        ReadonlyBucket::<LegacyWhitelistElem>::new(storage, PREFIX_WHITELIST)
+            .range(None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Vec<u8>, LegacyWhitelistElem)>>>()?;
+
+    for (k, elem) in legacy_elems.into_iter() {
+        store_whitelist_elem(
+            storage,
+            &CanonicalAddr::from(k),
+            &WhitelistElem {
+                name: elem.name,
+                symbol: elem.symbol,
+                max_ltv: elem.max_ltv,
+                liquidation_threshold: elem.liquidation_threshold.unwrap_or(elem.max_ltv),
+                custody_contract: elem.custody_contract,
+                delisting: elem.delisting,
+                debt_ceiling: elem.debt_ceiling,
+                is_aterra: elem.is_aterra,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Recompute the total amount of each collateral from the
+/// collaterals locked by all borrowers, including the locks
+/// made before the totals were tracked
+pub fn migrate_total_collaterals(storage: &mut dyn Storage) -> StdResult<()> {
+    let borrower_collaterals: Vec<(Vec<u8>, Tokens)> =
+        ReadonlyBucket::<Tokens>::new(storage, PREFIX_COLLATERALS)
+            .range(None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Vec<u8>, Tokens)>>>()?;
+
+    let mut totals: Vec<(CanonicalAddr, Uint256)> = vec![];
+    for (_, collaterals) in borrower_collaterals.into_iter() {
+        for (collateral_token, amount) in collaterals.into_iter() {
+            match totals.iter_mut().find(|total| total.0 == collateral_token) {
+                Some(total) => total.1 += amount,
+                None => totals.push((collateral_token, amount)),
+            }
+        }
+    }
+
+    for (collateral_token, amount) in totals.iter() {
+        store_total_collateral(storage, collateral_token, amount)?;
+    }
+
+    Ok(())
+}
+
 pub fn read_whitelist_elem(
     storage: &dyn Storage,
     collateral_token: &CanonicalAddr,
@@ -122,6 +265,32 @@ pub fn read_whitelist_elem(
     }
 }
 
+pub fn remove_whitelist_elem(storage: &mut dyn Storage, collateral_token: &CanonicalAddr) {
+    let mut whitelist_bucket: Bucket<WhitelistElem> = Bucket::new(storage, PREFIX_WHITELIST);
+    whitelist_bucket.remove(collateral_token.as_slice());
+}
+
+pub fn read_whitelist_elems(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, WhitelistElem)>> {
+    let whitelist_bucket: ReadonlyBucket<WhitelistElem> =
+        ReadonlyBucket::new(storage, PREFIX_WHITELIST);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    whitelist_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
 pub fn read_whitelist(
     deps: Deps,
     start_after: Option<CanonicalAddr>,
@@ -146,6 +315,8 @@ pub fn read_whitelist(
                 collateral_token,
                 custody_contract,
                 max_ltv: v.max_ltv,
+                liquidation_threshold: v.liquidation_threshold,
+                debt_ceiling: v.debt_ceiling,
             })
         })
         .collect()
@@ -176,6 +347,223 @@ pub fn read_collaterals(storage: &dyn Storage, borrower: &CanonicalAddr) -> Toke
     }
 }
 
+pub fn store_total_collateral(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    amount: &Uint256,
+) -> StdResult<()> {
+    let mut total_collateral_bucket: Bucket<Uint256> =
+        Bucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket.save(collateral_token.as_slice(), amount)
+}
+
+/// Total amount of the collateral locked by all borrowers
+pub fn read_total_collateral(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> Uint256 {
+    let total_collateral_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket
+        .load(collateral_token.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+pub fn store_isolated_debt(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    principal: &Decimal256,
+) -> StdResult<()> {
+    let mut isolated_debt_bucket: Bucket<Decimal256> = Bucket::new(storage, PREFIX_ISOLATED_DEBT);
+    isolated_debt_bucket.save(collateral_token.as_slice(), principal)
+}
+
+/// Total principal of the loans attributed to the isolated collateral;
+/// multiplied by the market global interest index, it gives the accrued debt
+pub fn read_isolated_debt(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> Decimal256 {
+    let isolated_debt_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::new(storage, PREFIX_ISOLATED_DEBT);
+    isolated_debt_bucket
+        .load(collateral_token.as_slice())
+        .unwrap_or_else(|_| Decimal256::zero())
+}
+
+pub fn store_isolated_loan(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    isolated_loan: &IsolatedLoan,
+) -> StdResult<()> {
+    let mut isolated_loan_bucket: Bucket<IsolatedLoan> = Bucket::new(storage, PREFIX_ISOLATED_LOAN);
+    isolated_loan_bucket.save(borrower.as_slice(), isolated_loan)
+}
+
+pub fn remove_isolated_loan(storage: &mut dyn Storage, borrower: &CanonicalAddr) {
+    let mut isolated_loan_bucket: Bucket<IsolatedLoan> = Bucket::new(storage, PREFIX_ISOLATED_LOAN);
+    isolated_loan_bucket.remove(borrower.as_slice());
+}
+
+pub fn read_isolated_loan(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<IsolatedLoan>> {
+    let isolated_loan_bucket: ReadonlyBucket<IsolatedLoan> =
+        ReadonlyBucket::new(storage, PREFIX_ISOLATED_LOAN);
+    isolated_loan_bucket.may_load(borrower.as_slice())
+}
+
+pub fn store_emode_category(
+    storage: &mut dyn Storage,
+    category_id: u8,
+    category: &EModeCategory,
+) -> StdResult<()> {
+    let mut emode_category_bucket: Bucket<EModeCategory> =
+        Bucket::new(storage, PREFIX_EMODE_CATEGORY);
+    emode_category_bucket.save(&[category_id], category)
+}
+
+pub fn read_emode_category(storage: &dyn Storage, category_id: u8) -> StdResult<EModeCategory> {
+    let emode_category_bucket: ReadonlyBucket<EModeCategory> =
+        ReadonlyBucket::new(storage, PREFIX_EMODE_CATEGORY);
+    match emode_category_bucket.load(&[category_id]) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err(format!(
+            "E-mode category {} is not registered",
+            category_id
+        ))),
+    }
+}
+
+pub fn read_emode_categories(storage: &dyn Storage) -> StdResult<Vec<(u8, EModeCategory)>> {
+    let emode_category_bucket: ReadonlyBucket<EModeCategory> =
+        ReadonlyBucket::new(storage, PREFIX_EMODE_CATEGORY);
+
+    emode_category_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((k[0], v))
+        })
+        .collect()
+}
+
+pub fn store_borrower_emode(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    category_id: Option<u8>,
+) -> StdResult<()> {
+    let mut borrower_emode_bucket: Bucket<u8> = Bucket::new(storage, PREFIX_BORROWER_EMODE);
+    match category_id {
+        Some(category_id) => borrower_emode_bucket.save(borrower.as_slice(), &category_id),
+        None => {
+            borrower_emode_bucket.remove(borrower.as_slice());
+            Ok(())
+        }
+    }
+}
+
+pub fn read_borrower_emode(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<Option<u8>> {
+    let borrower_emode_bucket: ReadonlyBucket<u8> =
+        ReadonlyBucket::new(storage, PREFIX_BORROWER_EMODE);
+    borrower_emode_bucket.may_load(borrower.as_slice())
+}
+
+/// Queue the change under a new id and return the id
+pub fn store_pending_change(
+    storage: &mut dyn Storage,
+    pending_change: &PendingChange,
+) -> StdResult<u64> {
+    let mut change_id_singleton: Singleton<u64> = Singleton::new(storage, KEY_PENDING_CHANGE_ID);
+    let change_id = change_id_singleton.may_load()?.unwrap_or(0) + 1;
+    change_id_singleton.save(&change_id)?;
+
+    let mut pending_change_bucket: Bucket<PendingChange> =
+        Bucket::new(storage, PREFIX_PENDING_CHANGE);
+    pending_change_bucket.save(&change_id.to_be_bytes(), pending_change)?;
+
+    Ok(change_id)
+}
+
+pub fn read_pending_change(storage: &dyn Storage, change_id: u64) -> StdResult<PendingChange> {
+    let pending_change_bucket: ReadonlyBucket<PendingChange> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_CHANGE);
+    match pending_change_bucket.load(&change_id.to_be_bytes()) {
+        Ok(v) => Ok(v),
+        _ => Err(StdError::generic_err(format!(
+            "Pending change {} does not exist",
+            change_id
+        ))),
+    }
+}
+
+pub fn remove_pending_change(storage: &mut dyn Storage, change_id: u64) {
+    let mut pending_change_bucket: Bucket<PendingChange> =
+        Bucket::new(storage, PREFIX_PENDING_CHANGE);
+    pending_change_bucket.remove(&change_id.to_be_bytes());
+}
+
+pub fn read_pending_changes(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, PendingChange)>> {
+    let pending_change_bucket: ReadonlyBucket<PendingChange> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_CHANGE);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|change_id| (change_id + 1).to_be_bytes().to_vec());
+
+    pending_change_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut change_id = [0u8; 8];
+            change_id.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(change_id), v))
+        })
+        .collect()
+}
+
+pub fn store_paused_operations(
+    storage: &mut dyn Storage,
+    paused_operations: &[PausableOperation],
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_PAUSED_OPERATIONS).save(&paused_operations.to_vec())
+}
+
+pub fn read_paused_operations(storage: &dyn Storage) -> StdResult<Vec<PausableOperation>> {
+    Ok(ReadonlySingleton::new(storage, KEY_PAUSED_OPERATIONS)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn is_paused(storage: &dyn Storage, operation: &PausableOperation) -> StdResult<bool> {
+    Ok(read_paused_operations(storage)?.contains(operation))
+}
+
+pub fn store_shutdown(storage: &mut dyn Storage, shutdown: &Shutdown) -> StdResult<()> {
+    Singleton::new(storage, KEY_SHUTDOWN).save(shutdown)
+}
+
+pub fn read_shutdown(storage: &dyn Storage) -> StdResult<Option<Shutdown>> {
+    ReadonlySingleton::new(storage, KEY_SHUTDOWN).may_load()
+}
+
+/// Borrowers liquidated by the last LiquidateCollaterals,
+/// indexed by the reply id of their liquidation
+pub fn store_liquidation_batch(
+    storage: &mut dyn Storage,
+    borrowers: &[CanonicalAddr],
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_LIQUIDATION_BATCH).save(&borrowers.to_vec())
+}
+
+pub fn read_liquidation_batch(storage: &dyn Storage) -> StdResult<Vec<CanonicalAddr>> {
+    Ok(ReadonlySingleton::new(storage, KEY_LIQUIDATION_BATCH)
+        .may_load()?
+        .unwrap_or_default())
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
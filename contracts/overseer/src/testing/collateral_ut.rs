@@ -47,6 +47,8 @@ fn proper_compute_borrow_limit() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -57,6 +59,8 @@ fn proper_compute_borrow_limit() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
@@ -92,7 +96,14 @@ fn proper_compute_borrow_limit() {
     );
     collaterals.push(token2);
 
-    let res = compute_borrow_limit(deps.as_ref(), &collaterals, None).unwrap();
+    let res = compute_borrow_limit(
+        deps.as_ref(),
+        &deps.api.addr_canonicalize("addr0000").unwrap(),
+        &collaterals,
+        env.block.height,
+        None,
+    )
+    .unwrap();
     let vec: Vec<Decimal256> = vec![
         Decimal256::from_uint256(1000u128),
         Decimal256::from_uint256(2000u128),
@@ -101,3 +112,148 @@ fn proper_compute_borrow_limit() {
     let res2 = (Uint256::from(1800000u128), vec);
     assert_eq!(res, res2);
 }
+
+#[test]
+fn compute_borrow_limit_with_twap() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(1000u128),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_oracle_twap(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(800u128),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let collaterals: Tokens = vec![(
+        deps.api.addr_canonicalize("bluna").unwrap(),
+        Uint256::from(1000u128),
+    )];
+
+    // spot price by default
+    let res = compute_borrow_limit(
+        deps.as_ref(),
+        &deps.api.addr_canonicalize("addr0000").unwrap(),
+        &collaterals,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        (
+            Uint256::from(600000u128),
+            vec![Decimal256::from_uint256(1000u128)]
+        )
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        price_twap_window: Some(1800u64),
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res = compute_borrow_limit(
+        deps.as_ref(),
+        &deps.api.addr_canonicalize("addr0000").unwrap(),
+        &collaterals,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        (
+            Uint256::from(480000u128),
+            vec![Decimal256::from_uint256(800u128)]
+        )
+    );
+
+    // 0 goes back to the spot price
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        price_twap_window: Some(0u64),
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = compute_borrow_limit(
+        deps.as_ref(),
+        &deps.api.addr_canonicalize("addr0000").unwrap(),
+        &collaterals,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(res.0, Uint256::from(600000u128));
+}
@@ -10,17 +10,19 @@ use cosmwasm_std::{
 use std::collections::HashMap;
 
 use moneymarket::liquidation::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, StateResponse};
+use moneymarket::market::{
+    BorrowerInfoResponse, ConfigResponse as MarketConfigResponse, EpochStateResponse, StateResponse,
+};
 use moneymarket::oracle::PriceResponse;
 use moneymarket::tokens::TokensHuman;
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
-use std::str::FromStr;
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
+    /// Query config to market contract
+    Config {},
     /// Market state to market contract
     State { block_height: Option<u64> },
     /// Query epoch state to market contract
@@ -35,6 +37,12 @@ pub enum QueryMsg {
     },
     /// Query oracle price to oracle contract
     Price { base: String, quote: String },
+    /// Query oracle TWAP to oracle contract
+    Twap {
+        base: String,
+        quote: String,
+        window_seconds: u64,
+    },
     /// Query liquidation amount to liquidation model contract
     LiquidationAmount {
         borrow_amount: Uint256,
@@ -66,6 +74,7 @@ pub struct WasmMockQuerier {
     oracle_price_querier: OraclePriceQuerier,
     loan_amount_querier: LoanAmountQuerier,
     liquidation_percent_querier: LiquidationPercentQuerier,
+    global_interest_index: Decimal256,
 }
 
 #[derive(Clone, Default)]
@@ -96,6 +105,7 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 pub struct OraclePriceQuerier {
     // this lets us iterate over all pairs that match the first string
     oracle_price: HashMap<(String, String), (Decimal256, u64, u64)>,
+    oracle_twap: HashMap<(String, String), (Decimal256, u64, u64)>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -103,6 +113,7 @@ impl OraclePriceQuerier {
     pub fn new(oracle_price: &[(&(String, String), &(Decimal256, u64, u64))]) -> Self {
         OraclePriceQuerier {
             oracle_price: oracle_price_to_map(oracle_price),
+            oracle_twap: HashMap::new(),
         }
     }
 }
@@ -237,28 +248,33 @@ impl WasmMockQuerier {
             }
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 match from_binary(msg).unwrap() {
+                    QueryMsg::Config {} => {
+                        SystemResult::Ok(ContractResult::from(to_binary(&MarketConfigResponse {
+                            owner_addr: "owner".to_string(),
+                            aterra_contract: "aterra".to_string(),
+                            interest_model: "interest".to_string(),
+                            distribution_model: "distribution".to_string(),
+                            overseer_contract: MOCK_CONTRACT_ADDR.to_string(),
+                            collector_contract: "collector".to_string(),
+                            distributor_contract: "distributor".to_string(),
+                            stable_denom: "uusd".to_string(),
+                            max_borrow_factor: Decimal256::one(),
+                            flash_loan_fee: Decimal256::zero(),
+                            depositor_reward_ratio: Decimal256::zero(),
+                        })))
+                    }
                     QueryMsg::State { block_height: _ } => {
-                        match self.epoch_state_querier.epoch_state.get(contract_addr) {
-                            // TODO:
-                            Some(_v) => {
-                                SystemResult::Ok(ContractResult::from(to_binary(&StateResponse {
-                                    total_liabilities: Decimal256::zero(),
-                                    total_reserves: Decimal256::zero(),
-                                    last_interest_updated: 0,
-                                    last_reward_updated: 0,
-                                    global_interest_index: Decimal256::from_str("1000000.0")
-                                        .unwrap(),
-                                    global_reward_index: Decimal256::zero(),
-                                    anc_emission_rate: Decimal256::zero(),
-                                    prev_aterra_supply: Uint256::zero(),
-                                    prev_exchange_rate: Decimal256::zero(),
-                                })))
-                            }
-                            None => SystemResult::Err(SystemError::InvalidRequest {
-                                error: "No epoch state exists".to_string(),
-                                request: msg.as_slice().into(),
-                            }),
-                        }
+                        SystemResult::Ok(ContractResult::from(to_binary(&StateResponse {
+                            total_liabilities: Decimal256::zero(),
+                            total_reserves: Decimal256::zero(),
+                            last_interest_updated: 0,
+                            last_reward_updated: 0,
+                            global_interest_index: self.global_interest_index,
+                            global_reward_index: Decimal256::zero(),
+                            anc_emission_rate: Decimal256::zero(),
+                            prev_aterra_supply: Uint256::zero(),
+                            prev_exchange_rate: Decimal256::zero(),
+                        })))
                     }
                     QueryMsg::EpochState {
                         block_height: _,
@@ -308,6 +324,23 @@ impl WasmMockQuerier {
                             }),
                         }
                     }
+                    QueryMsg::Twap {
+                        base,
+                        quote,
+                        window_seconds: _,
+                    } => match self.oracle_price_querier.oracle_twap.get(&(base, quote)) {
+                        Some(v) => {
+                            SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
+                                rate: v.0,
+                                last_updated_base: v.1,
+                                last_updated_quote: v.2,
+                            })))
+                        }
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No oracle twap exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    },
                     QueryMsg::LiquidationAmount {
                         borrow_amount,
                         borrow_limit,
@@ -360,6 +393,7 @@ impl WasmMockQuerier {
             oracle_price_querier: OraclePriceQuerier::default(),
             loan_amount_querier: LoanAmountQuerier::default(),
             liquidation_percent_querier: LiquidationPercentQuerier::default(),
+            global_interest_index: Decimal256::one(),
         }
     }
 
@@ -380,6 +414,18 @@ impl WasmMockQuerier {
         self.oracle_price_querier = OraclePriceQuerier::new(oracle_price);
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn with_oracle_twap(
+        &mut self,
+        oracle_twap: &[(&(String, String), &(Decimal256, u64, u64))],
+    ) {
+        self.oracle_price_querier.oracle_twap = oracle_price_to_map(oracle_twap);
+    }
+
+    pub fn with_global_interest_index(&mut self, global_interest_index: Decimal256) {
+        self.global_interest_index = global_interest_index;
+    }
+
     pub fn with_loan_amount(&mut self, loan_amount: &[(&String, &Uint256)]) {
         self.loan_amount_querier = LoanAmountQuerier::new(loan_amount);
     }
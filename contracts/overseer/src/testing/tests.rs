@@ -1,25 +1,36 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use crate::error::ContractError;
 use crate::querier::query_epoch_state;
 use crate::state::{
-    read_epoch_state, store_dynrate_state, store_epoch_state, DynrateState, EpochState,
+    read_dynrate_state, read_epoch_state, read_total_collateral, read_whitelist_elem,
+    store_collaterals, store_dynrate_state, store_epoch_state, DynrateState, EpochState,
+    WhitelistElem,
 };
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal,
-    DepsMut, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, to_vec, Addr, Api, BankMsg, CanonicalAddr, Coin, ContractResult,
+    CosmosMsg, Decimal, DepsMut, Reply, StdError, Storage, SubMsg, Uint128, WasmMsg,
 };
+use cosmwasm_storage::to_length_prefixed;
+use moneymarket::access::{Role, RoleMembersResponse};
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    AllCollateralsResponse, BorrowLimitResponse, BorrowerEModeResponse, BorrowerHealthResponse,
+    CollateralHealthElem, CollateralStatus, CollateralStatusElem, CollateralStatusResponse,
+    CollateralsResponse, ConfigResponse, EModeCategoriesResponse, EModeCategoryResponse,
+    ExecuteMsg, InstantiateMsg, LiquidatableBorrowersResponse, LiquidatableBorrowersResponseElem,
+    MigrateMsg, ParamChange, PausableOperation, PauseStatusResponse, PendingChangesResponse,
+    PendingChangesResponseElem, QueryMsg, ShutdownStateResponse, WhitelistResponse,
+    WhitelistResponseElem,
 };
+use moneymarket::ownership::PendingOwnerResponse;
 use moneymarket::querier::deduct_tax;
 
+use serde::Serialize;
 use std::str::FromStr;
 
 #[test]
@@ -68,6 +79,8 @@ fn proper_initialization() {
             buffer_distribution_factor: Decimal256::percent(20),
             anc_purchase_factor: Decimal256::percent(20),
             price_timeframe: 60u64,
+            price_twap_window: None,
+            timelock_period: 0,
             dyn_rate_epoch: 8600u64,
             dyn_rate_maxchange: Decimal256::permille(5),
             dyn_rate_yr_increase_expectation: Decimal256::permille(1),
@@ -118,28 +131,31 @@ fn update_config() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // propose new owner
     let info = mock_info("owner", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: Some("owner1".to_string()),
-        oracle_contract: None,
-        liquidation_contract: None,
-        threshold_deposit_rate: None,
-        target_deposit_rate: None,
-        buffer_distribution_factor: None,
-        anc_purchase_factor: None,
-        epoch_period: None,
-        price_timeframe: None,
-        dyn_rate_epoch: None,
-        dyn_rate_maxchange: None,
-        dyn_rate_yr_increase_expectation: None,
-        dyn_rate_min: None,
-        dyn_rate_max: None,
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner1".to_string(),
+        expires_in: Some(100u64),
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending_owner_res: PendingOwnerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_owner_res,
+        PendingOwnerResponse {
+            proposed_owner: Some("owner1".to_string()),
+            expires_at: Some(mock_env().block.height + 100u64),
+        }
+    );
+
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::AcceptOwnership {};
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config_res: ConfigResponse = from_binary(&res).unwrap();
@@ -148,7 +164,6 @@ fn update_config() {
     // update left items
     let info = mock_info("owner1", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         oracle_contract: Some("oracle1".to_string()),
         liquidation_contract: Some("liquidation1".to_string()),
         threshold_deposit_rate: Some(Decimal256::permille(1)),
@@ -157,6 +172,7 @@ fn update_config() {
         anc_purchase_factor: Some(Decimal256::percent(10)),
         epoch_period: Some(100000u64),
         price_timeframe: Some(120u64),
+        price_twap_window: Some(1800u64),
         dyn_rate_epoch: Some(8600u64),
         dyn_rate_maxchange: Some(Decimal256::permille(5)),
         dyn_rate_yr_increase_expectation: Some(Decimal256::permille(1)),
@@ -168,6 +184,7 @@ fn update_config() {
             1200000000000u64,
             1000000000000000000u64,
         )),
+        timelock_period: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -188,11 +205,11 @@ fn update_config() {
     assert_eq!(Decimal256::percent(10), config_res.anc_purchase_factor);
     assert_eq!(100000u64, config_res.epoch_period);
     assert_eq!(120u64, config_res.price_timeframe);
+    assert_eq!(Some(1800u64), config_res.price_twap_window);
 
     // Unauthorized err
     let info = mock_info("owner", &[]);
     let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
         oracle_contract: None,
         liquidation_contract: None,
         threshold_deposit_rate: None,
@@ -201,11 +218,13 @@ fn update_config() {
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        price_twap_window: None,
         dyn_rate_epoch: None,
         dyn_rate_maxchange: None,
         dyn_rate_yr_increase_expectation: None,
         dyn_rate_min: None,
         dyn_rate_max: None,
+        timelock_period: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -249,6 +268,8 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -269,6 +290,7 @@ fn whitelist() {
             attr("collateral_token", "bluna"),
             attr("custody_contract", "custody"),
             attr("LTV", "0.6"),
+            attr("liquidation_threshold", "0.7"),
         ]
     );
 
@@ -292,6 +314,8 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody".to_string(),
                 max_ltv: Decimal256::percent(60),
+                liquidation_threshold: Decimal256::percent(70),
+                debt_ceiling: None,
             }]
         }
     );
@@ -303,6 +327,8 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -312,10 +338,46 @@ fn whitelist() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    // Liquidation threshold lower than max ltv
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(50),
+        debt_ceiling: None,
+    };
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    match res {
+        ContractError::InvalidLiquidationThreshold {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Max ltv cannot be raised above the liquidation threshold
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(80)),
+        liquidation_threshold: None,
+        debt_ceiling: None,
+    };
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    match res {
+        ContractError::InvalidLiquidationThreshold {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
     let msg = ExecuteMsg::UpdateWhitelist {
         collateral_token: "bluna".to_string(),
         custody_contract: Some("custody2".to_string()),
         max_ltv: Some(Decimal256::percent(30)),
+        liquidation_threshold: Some(Decimal256::percent(40)),
+        debt_ceiling: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -334,6 +396,7 @@ fn whitelist() {
             attr("collateral_token", "bluna"),
             attr("custody_contract", "custody2"),
             attr("LTV", "0.3"),
+            attr("liquidation_threshold", "0.4"),
         ]
     );
 
@@ -357,20 +420,21 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody2".to_string(),
                 max_ltv: Decimal256::percent(30),
+                liquidation_threshold: Decimal256::percent(40),
+                debt_ceiling: None,
             }]
         }
     );
 }
 
 #[test]
-fn execute_epoch_operations() {
+fn migrate_whitelist() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: Uint128::from(10000000000u128),
+        amount: Uint128::zero(),
     }]);
 
-    let mut env = mock_env();
-    let info = mock_info("owner", &[]);
+    let info = mock_info("addr0000", &[]);
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         oracle_contract: "oracle".to_string(),
@@ -379,8 +443,8 @@ fn execute_epoch_operations() {
         collector_contract: "collector".to_string(),
         stable_denom: "uusd".to_string(),
         epoch_period: 86400u64,
-        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
-        target_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
@@ -390,216 +454,70 @@ fn execute_epoch_operations() {
         dyn_rate_min: Decimal256::zero(),
         dyn_rate_max: Decimal256::one(),
     };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    let batom_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
-
-    let bluna_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
+    // Whitelist element stored before the liquidation threshold was introduced
+    #[derive(Serialize)]
+    struct LegacyWhitelistElem {
+        name: String,
+        symbol: String,
+        max_ltv: Decimal256,
+        custody_contract: CanonicalAddr,
+    }
 
-    // store whitelist elems
-    let msg = ExecuteMsg::Whitelist {
+    let collateral_token = deps.api.addr_canonicalize("bluna").unwrap();
+    let legacy_elem = LegacyWhitelistElem {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
-        collateral_token: bluna_collat_token,
-        custody_contract: "custody_bluna".to_string(),
-        max_ltv: Decimal256::percent(60),
-    };
-
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-
-    let msg = ExecuteMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: batom_collat_token,
-        custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        custody_contract: deps.api.addr_canonicalize("custody").unwrap(),
     };
-
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-
-    let msg = ExecuteMsg::ExecuteEpochOperations {};
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res {
-        Err(ContractError::EpochNotPassed(12345)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    env.block.height += 86400u64;
-
-    // If deposit_rate is bigger than threshold_deposit_rate
-    deps.querier.with_epoch_state(&[(
-        &"market".to_string(),
-        &(Uint256::from(1000000u64), Decimal256::percent(120)),
-    )]);
-
-    // (120 / 100 - 1) / 86400
-    // deposit rate = 0.000002314814814814
-    // accrued_buffer = 10,000,000,000
-    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: "collector".to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(2_000_000_000u128),
-                    }
-                )
-                .unwrap()],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::UpdateEpochState {
-                    interest_buffer: Uint256::from(8_000_000_000u128),
-                    distributed_interest: Uint256::zero(),
-                })
-                .unwrap(),
-            }))
-        ]
-    );
-
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "epoch_operations"),
-            attr("deposit_rate", "0.000002314814814814"),
-            attr("exchange_rate", "1.2"),
-            attr("aterra_supply", "1000000"),
-            attr("distributed_interest", "0"),
-            attr("anc_purchase_amount", "2000000000"),
+    deps.storage.set(
+        &[
+            to_length_prefixed(b"whitelist").as_slice(),
+            collateral_token.as_slice(),
         ]
+        .concat(),
+        &to_vec(&legacy_elem).unwrap(),
     );
 
-    // store epoch state for test purpose
-    store_epoch_state(
-        deps.as_mut().storage,
-        &EpochState {
-            last_executed_height: env.block.height,
-            prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
-            prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
-            prev_interest_buffer: Uint256::from_str("9999000000").unwrap(),
-            deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
-        },
-    )
-    .unwrap();
-
-    // If deposit rate is bigger than threshold
-    deps.querier.with_epoch_state(&[(
-        &"market".to_string(),
-        &(Uint256::from(1000000u64), Decimal256::percent(125)),
-    )]);
-
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
-
-    env.block.height += 86400u64;
+    // The legacy element cannot be read before the migration
+    assert!(read_whitelist_elem(deps.as_ref().storage, &collateral_token).is_err());
 
-    // accrued_buffer = 1,000,000
-    // interest_buffer = 9,999,000,000
-    // (125 / 120 - 1) / 86400
-    // deposit rate = 0.000000482253086419
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: "collector".to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(200_000u128),
-                    }
-                )
-                .unwrap()]
-            })),
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: "market".to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(53680u128),
-                    }
-                )
-                .unwrap()]
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::UpdateEpochState {
-                    interest_buffer: Uint256::from(9999746320u128),
-                    distributed_interest: Uint256::from(53148u128),
-                })
-                .unwrap(),
-            }))
-        ]
-    );
+    let msg = MigrateMsg {
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_current: Decimal256::permille(3),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+    let _res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
 
+    // The liquidation threshold defaults to the max_ltv
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "epoch_operations"),
-            attr("deposit_rate", "0.000000482253086419"),
-            attr("exchange_rate", "1.25"),
-            attr("aterra_supply", "1000000"),
-            attr("distributed_interest", "53148"),
-            attr("anc_purchase_amount", "200000")
-        ]
+        read_whitelist_elem(deps.as_ref().storage, &collateral_token).unwrap(),
+        WhitelistElem {
+            name: "bluna".to_string(),
+            symbol: "bluna".to_string(),
+            max_ltv: Decimal256::percent(60),
+            liquidation_threshold: Decimal256::percent(60),
+            custody_contract: deps.api.addr_canonicalize("custody").unwrap(),
+            delisting: None,
+            debt_ceiling: None,
+            is_aterra: false,
+        }
     );
 }
 
 #[test]
-fn update_epoch_state() {
+fn migrate_total_collaterals() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: Uint128::from(10000000000u128),
+        amount: Uint128::zero(),
     }]);
 
-    let info = mock_info("owner", &[]);
+    let info = mock_info("addr0000", &[]);
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         oracle_contract: "oracle".to_string(),
@@ -608,7 +526,7 @@ fn update_epoch_state() {
         collector_contract: "collector".to_string(),
         stable_denom: "uusd".to_string(),
         epoch_period: 86400u64,
-        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        threshold_deposit_rate: Decimal256::permille(3),
         target_deposit_rate: Decimal256::permille(5),
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
@@ -619,127 +537,1814 @@ fn update_epoch_state() {
         dyn_rate_min: Decimal256::zero(),
         dyn_rate_max: Decimal256::one(),
     };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    // Collaterals locked before the totals were tracked
+    let bluna = deps.api.addr_canonicalize("bluna").unwrap();
+    let beth = deps.api.addr_canonicalize("beth").unwrap();
+    let addr0000 = deps.api.addr_canonicalize("addr0000").unwrap();
+    let addr0001 = deps.api.addr_canonicalize("addr0001").unwrap();
+    store_collaterals(
+        deps.as_mut().storage,
+        &addr0000,
+        &vec![(bluna.clone(), Uint256::from(1000u64))],
+    )
+    .unwrap();
+    store_collaterals(
+        deps.as_mut().storage,
+        &addr0001,
+        &vec![
+            (bluna.clone(), Uint256::from(500u64)),
+            (beth.clone(), Uint256::from(200u64)),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        read_total_collateral(deps.as_ref().storage, &bluna),
+        Uint256::zero()
+    );
+
+    let msg = MigrateMsg {
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_current: Decimal256::permille(3),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+    let _res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+    assert_eq!(
+        read_total_collateral(deps.as_ref().storage, &bluna),
+        Uint256::from(1500u64)
+    );
+    assert_eq!(
+        read_total_collateral(deps.as_ref().storage, &beth),
+        Uint256::from(200u64)
+    );
+}
+
+#[test]
+fn timelock() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // store whitelist elems
     let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
         collateral_token: "bluna".to_string(),
-        custody_contract: "custody_bluna".to_string(),
+        custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
     };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    // enabling the timelock applies immediately
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        price_twap_window: None,
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: Some(100u64),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    let msg = ExecuteMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: "batom".to_string(),
-        custody_contract: "custody_batom".to_string(),
-        max_ltv: Decimal256::percent(60),
+    // cosmetic fields stay immediate, risk fields are queued
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: Some(100000u64),
+        price_timeframe: Some(120u64),
+        price_twap_window: None,
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
     };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr("change_id", "1"),
+            attr("executable_at", "12445"),
+        ]
+    );
 
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.epoch_period, 100000u64);
+    assert_eq!(config_res.price_timeframe, 60u64);
+    assert_eq!(config_res.timelock_period, 100u64);
 
-    // only contract itself can execute update_epoch_state
-    let msg = ExecuteMsg::UpdateEpochState {
-        interest_buffer: Uint256::from(10000000000u128),
-        distributed_interest: Uint256::from(1000000u128),
+    // invalid changes are rejected when queued
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(80)),
+        liquidation_threshold: None,
+        debt_ceiling: None,
     };
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match res {
-        Err(ContractError::Unauthorized {}) => (),
+        Err(ContractError::InvalidLiquidationThreshold {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // Assume execute epoch operation is executed
-    let mut env = mock_env();
-    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-    env.block.height += 86400u64;
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(50)),
+        liquidation_threshold: None,
+        debt_ceiling: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_whitelist"),
+            attr("collateral_token", "bluna"),
+            attr("change_id", "2"),
+            attr("executable_at", "12445"),
+        ]
+    );
 
-    deps.querier.with_epoch_state(&[(
-        &"market".to_string(),
-        &(Uint256::from(1000000u64), Decimal256::percent(120)),
-    )]);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingChanges {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending_changes_res: PendingChangesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_changes_res,
+        PendingChangesResponse {
+            changes: vec![
+                PendingChangesResponseElem {
+                    change_id: 1u64,
+                    change: ParamChange::Config {
+                        oracle_contract: None,
+                        liquidation_contract: None,
+                        threshold_deposit_rate: None,
+                        target_deposit_rate: None,
+                        price_timeframe: Some(120u64),
+                        price_twap_window: None,
+                        timelock_period: None,
+                    },
+                    executable_at: 12445u64,
+                },
+                PendingChangesResponseElem {
+                    change_id: 2u64,
+                    change: ParamChange::Whitelist {
+                        collateral_token: "bluna".to_string(),
+                        custody_contract: None,
+                        max_ltv: Some(Decimal256::percent(50)),
+                        liquidation_threshold: None,
+                        debt_ceiling: None,
+                    },
+                    executable_at: 12445u64,
+                },
+            ]
+        }
+    );
+
+    // cannot execute before the timelock passes
+    let msg = ExecuteMsg::ExecutePendingChange { change_id: 1u64 };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::TimelockNotPassed(12445u64)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
+    // anyone can execute after the timelock
+    let mut env = mock_env();
+    env.block.height += 100;
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "market".to_string(),
-            funds: vec![],
-            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
-                deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
-                target_deposit_rate: Decimal256::permille(5),
-                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
-                distributed_interest: Uint256::from(1000000u128),
-            })
-            .unwrap(),
-        }))]
+        res.attributes,
+        vec![
+            attr("action", "execute_pending_change"),
+            attr("change_id", "1"),
+        ]
     );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.price_timeframe, 120u64);
+
+    // executed changes are removed
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Pending change 1 does not exist")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the owner can cancel
+    let msg = ExecuteMsg::CancelPendingChange { change_id: 2u64 };
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "update_epoch_state"),
-            attr("deposit_rate", "0.000002314814814814"),
-            attr("aterra_supply", "1000000"),
-            attr("exchange_rate", "1.2"),
-            attr("interest_buffer", "10000000000"),
+            attr("action", "cancel_pending_change"),
+            attr("change_id", "2"),
         ]
     );
 
-    // Deposit rate increased
-    deps.querier.with_epoch_state(&[(
-        &"market".to_string(),
-        &(Uint256::from(1000000u64), Decimal256::percent(125)),
-    )]);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingChanges {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending_changes_res: PendingChangesResponse = from_binary(&res).unwrap();
+    assert_eq!(pending_changes_res.changes, vec![]);
 
-    env.block.height += 86400u64;
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Whitelist {
+            collateral_token: Some("bluna".to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(whitelist_res.elems[0].max_ltv, Decimal256::percent(60));
+
+    // e-mode categories wait for the timelock too
+    let info = mock_info("owner", &[]);
+    let msg = ExecuteMsg::UpdateEModeCategory {
+        category_id: 1u8,
+        name: "luna".to_string(),
+        max_ltv: Decimal256::percent(80),
+        liquidation_threshold: Decimal256::percent(90),
+        collaterals: vec!["bluna".to_string()],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "market".to_string(),
-            funds: vec![],
-            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
-                deposit_rate: Decimal256::from_str("0.000000482253086419").unwrap(),
-                target_deposit_rate: Decimal256::from_str("0.000001006442178229").unwrap(),
-                threshold_deposit_rate: Decimal256::from_str("0.000001006442178229").unwrap(),
-                distributed_interest: Uint256::from(1000000u128),
-            })
-            .unwrap(),
-        }))]
+        res.attributes,
+        vec![
+            attr("action", "update_emode_category"),
+            attr("category_id", "1"),
+            attr("name", "luna"),
+            attr("change_id", "3"),
+            attr("executable_at", "12545"),
+        ]
     );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EModeCategories {}).unwrap();
+    let emode_res: EModeCategoriesResponse = from_binary(&res).unwrap();
+    assert_eq!(emode_res.categories, vec![]);
+
+    // the delisting ramp cannot be shorter than the timelock
+    for ramp_blocks in [0u64, 50u64] {
+        let msg = ExecuteMsg::DelistCollateral {
+            collateral_token: "bluna".to_string(),
+            ramp_blocks,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+        match res {
+            Err(ContractError::DelistingRampTooShort(100u64)) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    // the delisting ramp starts once the change is executed
+    let msg = ExecuteMsg::DelistCollateral {
+        collateral_token: "bluna".to_string(),
+        ramp_blocks: 100u64,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "update_epoch_state"),
-            attr("deposit_rate", "0.000000482253086419"),
-            attr("aterra_supply", "1000000"),
-            attr("exchange_rate", "1.25"),
-            attr("interest_buffer", "10000000000"),
+            attr("action", "delist_collateral"),
+            attr("collateral_token", "bluna"),
+            attr("change_id", "4"),
+            attr("executable_at", "12545"),
         ]
     );
 
-    let epoch_state_response = query_epoch_state(
+    let res = query(
         deps.as_ref(),
-        Addr::unchecked("market"),
-        env.block.height,
-        None,
+        env.clone(),
+        QueryMsg::CollateralStatus {
+            start_after: None,
+            limit: None,
+        },
     )
     .unwrap();
-    let epoch_state = read_epoch_state(deps.as_ref().storage).unwrap();
+    let status_res: CollateralStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(status_res.elems[0].status, CollateralStatus::Active);
 
-    // deposit rate = 0.000000482253078703
+    env.block.height += 100;
+    let msg = ExecuteMsg::ExecutePendingChange { change_id: 3u64 };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
-        epoch_state,
-        EpochState {
-            deposit_rate: Decimal256::from_ratio(482253086419u64, 1000000000000000000u64),
-            prev_aterra_supply: epoch_state_response.aterra_supply,
-            prev_exchange_rate: epoch_state_response.exchange_rate,
+        res.attributes,
+        vec![
+            attr("action", "execute_pending_change"),
+            attr("change_id", "3"),
+            attr("LTV", "0.8"),
+            attr("liquidation_threshold", "0.9"),
+        ]
+    );
+
+    let msg = ExecuteMsg::ExecutePendingChange { change_id: 4u64 };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_pending_change"),
+            attr("change_id", "4"),
+            attr("delisting_end_height", "12645"),
+        ]
+    );
+}
+
+#[test]
+fn roles() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // only the owner can grant roles
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::RiskAdmin,
+        address: "risk0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("risk0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "grant_role"),
+            attr("role", "risk_admin"),
+            attr("address", "risk0000"),
+        ]
+    );
+
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::TreasuryAdmin,
+        address: "treasury0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RoleMembers {
+            role: Role::RiskAdmin,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let members_res: RoleMembersResponse = from_binary(&res).unwrap();
+    assert_eq!(members_res.members, vec!["risk0000".to_string()]);
+
+    // the risk admin can update risk parameters
+    let info = mock_info("risk0000", &[]);
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(50)),
+        liquidation_threshold: None,
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: Some(120u64),
+        price_twap_window: None,
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // but neither treasury nor owner fields
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: Some(Decimal256::percent(10)),
+        epoch_period: None,
+        price_timeframe: None,
+        price_twap_window: None,
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg2 = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: Some(100000u64),
+        price_timeframe: None,
+        price_twap_window: None,
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg2);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the treasury admin can update treasury fields only
+    let info = mock_info("treasury0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: Some(60u64),
+        price_twap_window: None,
+        dyn_rate_epoch: None,
+        dyn_rate_maxchange: None,
+        dyn_rate_yr_increase_expectation: None,
+        dyn_rate_min: None,
+        dyn_rate_max: None,
+        timelock_period: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.price_timeframe, 120u64);
+    assert_eq!(config_res.anc_purchase_factor, Decimal256::percent(10));
+
+    // revoked roles lose their permissions
+    let msg = ExecuteMsg::RevokeRole {
+        role: Role::RiskAdmin,
+        address: "risk0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(40)),
+        liquidation_threshold: None,
+        debt_ceiling: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("risk0000", &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn pause() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::GrantRole {
+        role: Role::Guardian,
+        address: "guardian0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // only the owner or a guardian can pause
+    let msg = ExecuteMsg::Pause {
+        operations: vec![PausableOperation::Lock, PausableOperation::Liquidate],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let guardian = mock_info("guardian0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), guardian.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause"),
+            attr("operations", "lock,liquidate")
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatusResponse {
+            lock: true,
+            liquidate: true,
+            ..PauseStatusResponse::default()
+        }
+    );
+
+    // paused operations fail
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::OperationPaused(PausableOperation::Lock)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::OperationPaused(PausableOperation::Liquidate)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the owner can unpause
+    let msg = ExecuteMsg::Unpause {
+        operations: vec![PausableOperation::Lock],
+    };
+    let res = execute(deps.as_mut(), mock_env(), guardian, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "unpause"), attr("operations", "lock")]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap();
+    let pause_status: PauseStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pause_status,
+        PauseStatusResponse {
+            liquidate: true,
+            ..PauseStatusResponse::default()
+        }
+    );
+}
+
+#[test]
+fn delist_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let mut env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::DelistCollateral {
+        collateral_token: "bluna".to_string(),
+        ramp_blocks: 100u64,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let start_height = env.block.height;
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "delist_collateral"),
+            attr("collateral_token", "bluna"),
+            attr("delisting_end_height", (start_height + 100).to_string()),
+        ]
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::CollateralDelisted {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // new locks and max_ltv updates are disabled
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::CollateralDelisted {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(50)),
+        liquidation_threshold: None,
+        debt_ceiling: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::CollateralDelisted {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // half way through the ramp
+    env.block.height += 50;
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(10u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(3000u64));
+
+    // liquidation_threshold is ramped down together with max_ltv
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(3000u64)),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidatableBorrowers {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let liquidatable_res: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+    assert_eq!(liquidatable_res.borrowers, vec![]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CollateralStatus {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let mut status_res: CollateralStatusResponse = from_binary(&res).unwrap();
+    status_res
+        .elems
+        .sort_by(|a, b| a.collateral_token.cmp(&b.collateral_token));
+    assert_eq!(
+        status_res,
+        CollateralStatusResponse {
+            elems: vec![
+                CollateralStatusElem {
+                    collateral_token: "batom".to_string(),
+                    status: CollateralStatus::Active,
+                    max_ltv: Decimal256::percent(50),
+                    total_locked: Uint256::zero(),
+                    delisting_end_height: None,
+                },
+                CollateralStatusElem {
+                    collateral_token: "bluna".to_string(),
+                    status: CollateralStatus::Delisting,
+                    max_ltv: Decimal256::percent(30),
+                    total_locked: Uint256::from(1000u64),
+                    delisting_end_height: Some(start_height + 100),
+                },
+            ]
+        }
+    );
+
+    let msg = ExecuteMsg::RemoveWhitelist {
+        collateral_token: "bluna".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::DelistingNotFinished(height)) => {
+            assert_eq!(height, start_height + 100)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RemoveWhitelist {
+        collateral_token: "batom".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::CollateralNotDelisted {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // ramp is over, but the collateral is still locked
+    env.block.height += 50;
+
+    // the remaining position is liquidatable
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidatableBorrowers {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let liquidatable_res: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        liquidatable_res.borrowers,
+        vec![LiquidatableBorrowersResponseElem {
+            borrower: "addr0000".to_string(),
+            loan_amount: Uint256::from(3000u64),
+            borrow_limit: Uint256::zero(),
+            liquidation_limit: Uint256::zero(),
+        }]
+    );
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let msg = ExecuteMsg::RemoveWhitelist {
+        collateral_token: "bluna".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CollateralStillLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::CollateralStatus {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let status_res: CollateralStatusResponse = from_binary(&res).unwrap();
+    let bluna_status = status_res
+        .elems
+        .iter()
+        .find(|elem| elem.collateral_token == "bluna")
+        .unwrap();
+    assert_eq!(bluna_status.status, CollateralStatus::Delisted);
+    assert_eq!(bluna_status.max_ltv, Decimal256::zero());
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unlock_msg,
+    )
+    .unwrap();
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "remove_whitelist"),
+            attr("collateral_token", "bluna"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::CollateralStatus {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let status_res: CollateralStatusResponse = from_binary(&res).unwrap();
+    assert_eq!(status_res.elems.len(), 1);
+    assert_eq!(status_res.elems[0].collateral_token, "batom".to_string());
+}
+
+#[test]
+fn debt_ceiling() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // bluna is isolated
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: Some(Uint256::from(5000u64)),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.attributes.last(), Some(&attr("debt_ceiling", "5000")));
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // isolated collateral cannot be combined with other collaterals
+    let bluna_msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+    };
+    let batom_msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000u64))],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        bluna_msg.clone(),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        batom_msg.clone(),
+    );
+    match res {
+        Err(ContractError::IsolatedCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        batom_msg,
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        bluna_msg.clone(),
+    );
+    match res {
+        Err(ContractError::IsolatedCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        bluna_msg,
+    )
+    .unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(10u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let borrow_limit = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, borrower: &str| {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowLimit {
+                borrower: borrower.to_string(),
+                block_time: None,
+            },
+        )
+        .unwrap();
+        from_binary::<BorrowLimitResponse>(&res)
+            .unwrap()
+            .borrow_limit
+    };
+
+    // collateral borrow limit = 1000 * 10 * 0.6 = 6000
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(5000u64));
+
+    let msg = ExecuteMsg::UpdateIsolatedDebt {
+        borrower: "addr0002".to_string(),
+        loan_amount: Uint256::from(3000u64),
+        interest_index: Decimal256::one(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(2000u64));
+    assert_eq!(borrow_limit(&deps, "addr0002"), Uint256::from(5000u64));
+
+    let msg = ExecuteMsg::UpdateIsolatedDebt {
+        borrower: "addr0000".to_string(),
+        loan_amount: Uint256::from(1500u64),
+        interest_index: Decimal256::one(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(2000u64));
+    assert_eq!(borrow_limit(&deps, "addr0002"), Uint256::from(3500u64));
+
+    // the recorded loans accrue interest with the market index;
+    // the loan of addr0002 is now 3000 * 1.5 = 4500
+    deps.querier
+        .with_global_interest_index(Decimal256::from_str("1.5").unwrap());
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(500u64));
+
+    // a loan recorded at a later index accrues from that index;
+    // addr0000 is at 1500 * 2 / 1.5 = 2000 and addr0002 at 3000 * 2 = 6000
+    let msg = ExecuteMsg::UpdateIsolatedDebt {
+        borrower: "addr0000".to_string(),
+        loan_amount: Uint256::from(1500u64),
+        interest_index: Decimal256::from_str("1.5").unwrap(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+    deps.querier
+        .with_global_interest_index(Decimal256::from_uint256(2u64));
+    assert_eq!(borrow_limit(&deps, "addr0002"), Uint256::from(3000u64));
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::zero());
+
+    // repaid loans release the ceiling
+    let msg = ExecuteMsg::UpdateIsolatedDebt {
+        borrower: "addr0002".to_string(),
+        loan_amount: Uint256::zero(),
+        interest_index: Decimal256::one(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("market", &[]), msg).unwrap();
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(5000u64));
+
+    // 0 removes the ceiling
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        liquidation_threshold: None,
+        debt_ceiling: Some(Uint256::zero()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(borrow_limit(&deps, "addr0000"), Uint256::from(6000u64));
+}
+
+#[test]
+fn emode() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    for (token, max_ltv, liquidation_threshold) in [
+        ("bluna", Decimal256::percent(60), Decimal256::percent(70)),
+        ("bstluna", Decimal256::percent(50), Decimal256::percent(60)),
+        ("batom", Decimal256::percent(60), Decimal256::percent(70)),
+    ] {
+        let msg = ExecuteMsg::Whitelist {
+            name: token.to_string(),
+            symbol: token.to_string(),
+            collateral_token: token.to_string(),
+            custody_contract: format!("custody_{}", token),
+            max_ltv,
+            liquidation_threshold,
+            debt_ceiling: None,
+        };
+
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::UpdateEModeCategory {
+        category_id: 1u8,
+        name: "luna".to_string(),
+        max_ltv: Decimal256::percent(90),
+        liquidation_threshold: Decimal256::percent(95),
+        collaterals: vec!["bluna".to_string(), "bstluna".to_string()],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let invalid_msg = ExecuteMsg::UpdateEModeCategory {
+        category_id: 1u8,
+        name: "luna".to_string(),
+        max_ltv: Decimal256::percent(90),
+        liquidation_threshold: Decimal256::percent(85),
+        collaterals: vec!["bluna".to_string(), "bstluna".to_string()],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), invalid_msg);
+    match res {
+        Err(ContractError::InvalidLiquidationThreshold {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EModeCategories {}).unwrap();
+    let categories_res: EModeCategoriesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        categories_res,
+        EModeCategoriesResponse {
+            categories: vec![EModeCategoryResponse {
+                category_id: 1u8,
+                name: "luna".to_string(),
+                max_ltv: Decimal256::percent(90),
+                liquidation_threshold: Decimal256::percent(95),
+                collaterals: vec!["bluna".to_string(), "bstluna".to_string()],
+            }]
+        }
+    );
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000u64)),
+            ("bstluna".to_string(), Uint256::from(1000u64)),
+        ],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_uint256(10u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("bstluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_uint256(10u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::zero()),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
+
+    let borrow_limit_msg = QueryMsg::BorrowLimit {
+        borrower: "addr0000".to_string(),
+        block_time: None,
+    };
+
+    // borrow_limit = 10000 * 0.6 + 10000 * 0.5 = 11000
+    let res = query(deps.as_ref(), env.clone(), borrow_limit_msg.clone()).unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(11000u64));
+
+    // batom does not belong to the category
+    let msg = ExecuteMsg::SetEMode {
+        category_id: Some(1u8),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::EModeCollateralMismatch {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_emode"),
+            attr("borrower", "addr0000"),
+            attr("category_id", "1"),
+        ]
+    );
+
+    // borrow_limit = 20000 * 0.9 = 18000
+    let res = query(deps.as_ref(), env.clone(), borrow_limit_msg).unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(18000u64));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerEMode {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let emode_res: BorrowerEModeResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        emode_res,
+        BorrowerEModeResponse {
+            borrower: "addr0000".to_string(),
+            category_id: Some(1u8),
+            active: true,
+        }
+    );
+
+    // cannot lock collaterals out of the category
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::from(1000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::EModeCollateralMismatch {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // cannot opt out while the loan relies on the boosted parameters
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(15000u64))]);
+    let msg = ExecuteMsg::SetEMode { category_id: None };
+    let res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::EModeLoanExceedsLimit(borrow_limit)) => {
+            assert_eq!(borrow_limit, 11000u128)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn execute_epoch_operations() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token,
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token,
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::EpochNotPassed(12345)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    env.block.height += 86400u64;
+
+    // If deposit_rate is bigger than threshold_deposit_rate
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // (120 / 100 - 1) / 86400
+    // deposit rate = 0.000002314814814814
+    // accrued_buffer = 10,000,000,000
+    // anc_purchase_amount = accrued_buffer * 0.2 = 2,000,000,000
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(2_000_000_000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(8_000_000_000u128),
+                    distributed_interest: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "epoch_operations"),
+            attr("deposit_rate", "0.000002314814814814"),
+            attr("exchange_rate", "1.2"),
+            attr("aterra_supply", "1000000"),
+            attr("distributed_interest", "0"),
+            attr("anc_purchase_amount", "2000000000"),
+        ]
+    );
+
+    // store epoch state for test purpose
+    store_epoch_state(
+        deps.as_mut().storage,
+        &EpochState {
+            last_executed_height: env.block.height,
+            prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
+            prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
+            prev_interest_buffer: Uint256::from_str("9999000000").unwrap(),
+            deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+        },
+    )
+    .unwrap();
+
+    // If deposit rate is bigger than threshold
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(125)),
+    )]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    env.block.height += 86400u64;
+
+    // accrued_buffer = 1,000,000
+    // interest_buffer = 9,999,000,000
+    // (125 / 120 - 1) / 86400
+    // deposit rate = 0.000000482253086419
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(200_000u128),
+                    }
+                )
+                .unwrap()]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "market".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(53680u128),
+                    }
+                )
+                .unwrap()]
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::DistributeRewards {}).unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                    interest_buffer: Uint256::from(9999746320u128),
+                    distributed_interest: Uint256::from(53148u128),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "epoch_operations"),
+            attr("deposit_rate", "0.000000482253086419"),
+            attr("exchange_rate", "1.25"),
+            attr("aterra_supply", "1000000"),
+            attr("distributed_interest", "53148"),
+            attr("anc_purchase_amount", "200000")
+        ]
+    );
+}
+
+#[test]
+fn update_epoch_state() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+
+    // only contract itself can execute update_epoch_state
+    let msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(10000000000u128),
+        distributed_interest: Uint256::from(1000000u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Assume execute epoch operation is executed
+    let mut env = mock_env();
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    env.block.height += 86400u64;
+
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
+                deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+                target_deposit_rate: Decimal256::permille(5),
+                threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+                distributed_interest: Uint256::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_epoch_state"),
+            attr("deposit_rate", "0.000002314814814814"),
+            attr("aterra_supply", "1000000"),
+            attr("exchange_rate", "1.2"),
+            attr("interest_buffer", "10000000000"),
+        ]
+    );
+
+    // Deposit rate increased
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(125)),
+    )]);
+
+    env.block.height += 86400u64;
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
+                deposit_rate: Decimal256::from_str("0.000000482253086419").unwrap(),
+                target_deposit_rate: Decimal256::from_str("0.000001006442178229").unwrap(),
+                threshold_deposit_rate: Decimal256::from_str("0.000001006442178229").unwrap(),
+                distributed_interest: Uint256::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_epoch_state"),
+            attr("deposit_rate", "0.000000482253086419"),
+            attr("aterra_supply", "1000000"),
+            attr("exchange_rate", "1.25"),
+            attr("interest_buffer", "10000000000"),
+        ]
+    );
+
+    let epoch_state_response = query_epoch_state(
+        deps.as_ref(),
+        Addr::unchecked("market"),
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    let epoch_state = read_epoch_state(deps.as_ref().storage).unwrap();
+
+    // deposit rate = 0.000000482253078703
+    assert_eq!(
+        epoch_state,
+        EpochState {
+            deposit_rate: Decimal256::from_ratio(482253086419u64, 1000000000000000000u64),
+            prev_aterra_supply: epoch_state_response.aterra_supply,
+            prev_exchange_rate: epoch_state_response.exchange_rate,
             prev_interest_buffer: Uint256::from(10000000000u128),
             last_executed_height: env.block.height,
         }
@@ -747,10 +2352,1256 @@ fn update_epoch_state() {
 }
 
 #[test]
-fn lock_collateral() {
+fn lock_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1000000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000000u64),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock_collateral"),
+            attr("borrower", "addr0000"),
+            attr(
+                "collaterals",
+                format!(
+                    "1000000{},10000000{}",
+                    bluna_collat_token, batom_collat_token
+                )
+            ),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                (batom_collat_token.clone(), Uint256::from(10000000u64)),
+                (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            ]
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AllCollaterals {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let all_collaterals_res: AllCollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        all_collaterals_res,
+        AllCollateralsResponse {
+            all_collaterals: vec![CollateralsResponse {
+                borrower: "addr0000".to_string(),
+                collaterals: vec![
+                    (batom_collat_token, Uint256::from(10000000u64)),
+                    (bluna_collat_token, Uint256::from(1000000u64)),
+                ]
+            }]
+        }
+    );
+}
+
+#[test]
+fn lock_collateral_for() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        borrow_amount: Some(Uint256::from(300000u64)),
+        to: None,
+    };
+
+    // only the custody of the collateral can lock on behalf of the borrower
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("custody_bluna", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1000000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::BorrowStableFor {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(300000u64),
+                    to: None,
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1000000bluna"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+        }
+    );
+}
+
+#[test]
+fn unlock_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Failed to unlock more than locked amount
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000001u64)),
+            ("batom".to_string(), Uint256::from(10000001u64)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockExceedsLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_oracle_price(&[
+        (
+            &("bluna".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &("batom".to_string(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+
+    // cannot unlock any tokens
+    // Failed to unlock more than locked amount
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599999400)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12599999400u64))]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+
+    // Cannot unlock 2bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(2u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Can unlock 1bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::one(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1bluna"),
+        ]
+    );
+
+    //testing for unlocking more collaterals
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(125999900u128))]);
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1u128)),
+            ("batom".to_string(), Uint256::from(1u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(1u128),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1bluna,1batom"),
+        ]
+    );
+}
+
+#[test]
+fn unlock_collateral_for() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow_limit after unlock = 1000 * 600000 * 0.6
+    // = 360,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(360000000u64))]);
+
+    let msg = ExecuteMsg::UnlockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![("bluna".to_string(), Uint256::from(400000u64))],
+    };
+
+    // only the market can unlock on behalf of the borrower
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("market", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockAndWithdrawCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(400000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "400000bluna"),
+        ]
+    );
+
+    // the remaining collateral still backs the loan
+    let msg = ExecuteMsg::UnlockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(359999400)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn liquidate_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &(batom_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // loans over the borrow limit are still safe until the liquidation limit
+    // liquidation_limit = 1000 * 1000000 * 0.7 + 2000 * 10000000 * 0.7
+    // = 14,700,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(14700000000u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(14700000001u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                (batom_collat_token, Uint256::from(9900000u64)),
+                (bluna_collat_token, Uint256::from(990000u64)),
+            ]
+        }
+    );
+}
+
+#[test]
+fn liquidate_aterra_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000000000u64), Decimal256::percent(120)),
+    )]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // the market aterra is priced at the market exchange rate
+    let msg = ExecuteMsg::Whitelist {
+        name: "aterra".to_string(),
+        symbol: "aust".to_string(),
+        collateral_token: "aterra".to_string(),
+        custody_contract: "custody_aterra".to_string(),
+        max_ltv: Decimal256::percent(80),
+        liquidation_threshold: Decimal256::percent(90),
+        debt_ceiling: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &(bluna_collat_token.clone(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            ("aterra".to_string(), Uint256::from(100000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("aterra".to_string(), Uint256::from(1000000000u64))],
+    };
+    let info = mock_info("addr0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 1.2 * 100000000 * 0.8
+    // = 696,000,000 uusd
+    // liquidation_limit = 1000 * 1000000 * 0.7 + 1.2 * 100000000 * 0.9
+    // = 808,000,000 uusd
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(696000000u64));
+
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(808000001u64)),
+        (&"addr0001".to_string(), &Uint256::from(1080000001u64)),
+    ]);
+
+    // all the aterra is burned without premium, which still leaves
+    // 688,000,001 uusd loan over a 600,000,000 uusd borrow limit
+    // for the liquidation contract to cover with bluna
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let info = mock_info("liquidator", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_aterra".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "liquidator".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100000000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "liquidator".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+
+    // only the aterra needed to get back under the borrow limit is burned,
+    // (1,080,000,001 - 960,000,000) / (1 - 0.8) = 600,000,005 uusd,
+    // and there is no stable to repay the market with
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_aterra".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                liquidator: "liquidator".to_string(),
+                borrower: "addr0001".to_string(),
+                amount: Uint256::from(500000004u64),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0001".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0001".to_string(),
+            collaterals: vec![("aterra".to_string(), Uint256::from(499999996u64))]
+        }
+    );
+
+    // the burn follows the e-mode max_ltv of the borrower
+    let msg = ExecuteMsg::UpdateEModeCategory {
+        category_id: 1u8,
+        name: "stable".to_string(),
+        max_ltv: Decimal256::percent(90),
+        liquidation_threshold: Decimal256::percent(95),
+        collaterals: vec!["aterra".to_string()],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("aterra".to_string(), Uint256::from(1000000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0002", &[]), msg).unwrap();
+
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::zero()),
+        (&"addr0001".to_string(), &Uint256::zero()),
+        (&"addr0002".to_string(), &Uint256::zero()),
+    ]);
+    let msg = ExecuteMsg::SetEMode {
+        category_id: Some(1u8),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0002", &[]), msg).unwrap();
+
+    // borrow_limit = 1.2 * 1000000000 * 0.9 = 1,080,000,000 uusd
+    // liquidation_limit = 1.2 * 1000000000 * 0.95 = 1,140,000,000 uusd
+    // (1,140,000,001 - 1,080,000,000) / (1 - 0.9) = 600,000,010 uusd is burned
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::zero()),
+        (&"addr0001".to_string(), &Uint256::zero()),
+        (&"addr0002".to_string(), &Uint256::from(1140000001u64)),
+    ]);
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0002".to_string(),
+    };
+    let res = execute(deps.as_mut(), env, mock_info("liquidator", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_aterra".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                liquidator: "liquidator".to_string(),
+                borrower: "addr0002".to_string(),
+                amount: Uint256::from(500000008u64),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn liquidate_collaterals() {
     let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "beth".to_string(),
+        symbol: "beth".to_string(),
+        collateral_token: "beth".to_string(),
+        custody_contract: "custody_beth".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001", "addr0002"] {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+        };
+        let info = mock_info(borrower, &[]);
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    // beth has no oracle price, so addr0004 cannot be priced
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("beth".to_string(), Uint256::from(1000u64))],
+    };
+    let info = mock_info("addr0004", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(10u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // liquidation_limit = 1000 * 10 * 0.7 = 7000
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(8000u64)),
+        (&"addr0001".to_string(), &Uint256::from(7000u64)),
+        (&"addr0002".to_string(), &Uint256::from(9000u64)),
+    ]);
+
+    let msg = ExecuteMsg::LiquidateCollaterals {
+        borrowers: vec![
+            "addr0000".to_string(),
+            "addr0001".to_string(),
+            "addr0004".to_string(),
+            "addr0002".to_string(),
+            "addr0003".to_string(),
+            "addr0000".to_string(),
+        ],
+    };
+    let info = mock_info("liquidator", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidate_collaterals"),
+            attr("liquidated", "addr0000"),
+            attr("skipped", "addr0001"),
+            attr("liquidation_failed", "addr0004"),
+            attr("error", "Generic error: Querier system error: Cannot parse request: No oracle price exists in: {\"price\":{\"base\":\"beth\",\"quote\":\"uusd\"}}"),
+            attr("liquidated", "addr0002"),
+            attr("skipped", "addr0003"),
+            attr("skipped", "addr0000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_error(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ExecuteMsg::LiquidateCollateralHook {
+                        borrower: "addr0000".to_string(),
+                        liquidator: "liquidator".to_string(),
+                    })
+                    .unwrap(),
+                }),
+                0,
+            ),
+            SubMsg::reply_on_error(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ExecuteMsg::LiquidateCollateralHook {
+                        borrower: "addr0002".to_string(),
+                        liquidator: "liquidator".to_string(),
+                    })
+                    .unwrap(),
+                }),
+                1,
+            ),
+        ]
+    );
+
+    // the liquidation of addr0002 fails; only its failure is recorded
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 1,
+            result: ContractResult::Err("Not enough bids".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidation_failed"),
+            attr("borrower", "addr0002"),
+            attr("error", "Not enough bids"),
+        ]
+    );
+    assert_eq!(res.messages, vec![]);
+
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 2,
+            result: ContractResult::Err("Not enough bids".to_string()),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidReplyId {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the hook can only be executed by the overseer itself
+    let msg = ExecuteMsg::LiquidateCollateralHook {
+        borrower: "addr0000".to_string(),
+        liquidator: "liquidator".to_string(),
+    };
+    let info = mock_info("liquidator", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "liquidator".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            }))
+        ]
+    );
+}
+
+#[test]
+fn write_off_bad_debt() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(400000u128),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
 
     let info = mock_info("owner", &[]);
+    let env = mock_env();
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         oracle_contract: "oracle".to_string(),
@@ -772,144 +3623,100 @@ fn lock_collateral() {
     };
 
     // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-    let batom_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
-
-    let bluna_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    // store whitelist elems
     let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
-        collateral_token: bluna_collat_token.clone(),
+        collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
     };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
-
-    let msg = ExecuteMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: batom_collat_token.clone(),
-        custody_contract: "custody_batom".to_string(),
-        max_ltv: Decimal256::percent(60),
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
     };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg);
+    let mut epoch_state = read_epoch_state(deps.as_ref().storage).unwrap();
+    epoch_state.prev_interest_buffer = Uint256::from(1000000u64);
+    store_epoch_state(deps.as_mut().storage, &epoch_state).unwrap();
 
-    let msg = ExecuteMsg::LockCollateral {
-        collaterals: vec![
-            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
-            (batom_collat_token.clone(), Uint256::from(10000000u64)),
-        ],
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0002".to_string(), &Uint256::zero()),
+    ]);
+
+    // loans backed by collaterals cannot be written off
+    let info = mock_info("addr0002", &[]);
+    let msg = ExecuteMsg::WriteOffBadDebt {
+        borrower: "addr0000".to_string(),
     };
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1000000u64),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LockCollateral {
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(10000000u64),
-                })
-                .unwrap(),
-            }))
-        ]
-    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::BorrowerHasCollaterals {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::WriteOffBadDebt {
+        borrower: "addr0002".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::NoBadDebt {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
+    // the whole interest buffer goes to the market
+    let msg = ExecuteMsg::WriteOffBadDebt {
+        borrower: "addr0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "lock_collateral"),
-            attr("borrower", "addr0000"),
-            attr(
-                "collaterals",
-                format!(
-                    "1000000{},10000000{}",
-                    bluna_collat_token, batom_collat_token
-                )
-            ),
+            attr("action", "write_off_bad_debt"),
+            attr("borrower", "addr0001"),
+            attr("loan_amount", "1000000"),
+            attr("buffer_amount", "400000"),
         ]
     );
-
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::Collaterals {
-            borrower: "addr0000".to_string(),
-        },
-    )
-    .unwrap();
-    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
     assert_eq!(
-        collaterals_res,
-        CollateralsResponse {
-            borrower: "addr0000".to_string(),
-            collaterals: vec![
-                (batom_collat_token.clone(), Uint256::from(10000000u64)),
-                (bluna_collat_token.clone(), Uint256::from(1000000u64)),
-            ]
-        }
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(400000u128),
+                }
+            )
+            .unwrap()],
+            msg: to_binary(&MarketExecuteMsg::WriteOffBadDebt {
+                borrower: "addr0001".to_string(),
+            })
+            .unwrap(),
+        }))]
     );
 
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::AllCollaterals {
-            start_after: None,
-            limit: None,
-        },
-    )
-    .unwrap();
-    let all_collaterals_res: AllCollateralsResponse = from_binary(&res).unwrap();
-    assert_eq!(
-        all_collaterals_res,
-        AllCollateralsResponse {
-            all_collaterals: vec![CollateralsResponse {
-                borrower: "addr0000".to_string(),
-                collaterals: vec![
-                    (batom_collat_token, Uint256::from(10000000u64)),
-                    (bluna_collat_token, Uint256::from(1000000u64)),
-                ]
-            }]
-        }
-    );
+    let epoch_state = read_epoch_state(deps.as_ref().storage).unwrap();
+    assert_eq!(epoch_state.prev_interest_buffer, Uint256::from(600000u64));
+    let dynrate_state = read_dynrate_state(deps.as_ref().storage).unwrap();
+    assert_eq!(dynrate_state.prev_yield_reserve, Decimal256::zero());
 }
 
 #[test]
-fn unlock_collateral() {
+fn shutdown() {
     let mut deps = mock_dependencies(&[]);
 
     let info = mock_info("owner", &[]);
-    let env = mock_env();
+    let mut env = mock_env();
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         oracle_contract: "oracle".to_string(),
@@ -933,195 +3740,259 @@ fn unlock_collateral() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    // store whitelist elems
-    let msg = ExecuteMsg::Whitelist {
-        name: "bluna".to_string(),
-        symbol: "bluna".to_string(),
-        collateral_token: "bluna".to_string(),
-        custody_contract: "custody_bluna".to_string(),
-        max_ltv: Decimal256::percent(60),
-    };
-
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-
-    let msg = ExecuteMsg::Whitelist {
-        name: "batom".to_string(),
-        symbol: "batom".to_string(),
-        collateral_token: "batom".to_string(),
-        custody_contract: "custody_batom".to_string(),
-        max_ltv: Decimal256::percent(60),
-    };
-
-    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+    for token in ["batom", "bluna"] {
+        let msg = ExecuteMsg::Whitelist {
+            name: token.to_string(),
+            symbol: token.to_string(),
+            collateral_token: token.to_string(),
+            custody_contract: format!("custody_{}", token),
+            max_ltv: Decimal256::percent(60),
+            liquidation_threshold: Decimal256::percent(70),
+            debt_ceiling: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
 
     let msg = ExecuteMsg::LockCollateral {
         collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1000000u64)),
-            ("batom".to_string(), Uint256::from(10000000u64)),
+            ("batom".to_string(), Uint256::from(1000u64)),
+            ("bluna".to_string(), Uint256::from(1000u64)),
         ],
     };
-    let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the batom oracle is broken, so its price is given at shutdown
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(10u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(5000u64)),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
 
-    // Failed to unlock more than locked amount
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1000001u64)),
-            ("batom".to_string(), Uint256::from(10000001u64)),
-        ],
+    let msg = ExecuteMsg::Shutdown {
+        repay_period: 100u64,
+        prices: Some(vec![(
+            "batom".to_string(),
+            Decimal256::from_ratio(5u64, 1u64),
+        )]),
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
     match res {
-        Err(ContractError::UnlockExceedsLocked {}) => (),
+        Err(ContractError::Unauthorized {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    deps.querier.with_oracle_price(&[
-        (
-            &("bluna".to_string(), "uusd".to_string()),
-            &(
-                Decimal256::from_ratio(1000u64, 1u64),
-                env.block.time.seconds(),
-                env.block.time.seconds(),
-            ),
-        ),
-        (
-            &("batom".to_string(), "uusd".to_string()),
-            &(
-                Decimal256::from_ratio(2000u64, 1u64),
-                env.block.time.seconds(),
-                env.block.time.seconds(),
-            ),
-        ),
-    ]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "shutdown"),
+            attr("repay_deadline", (env.block.height + 100).to_string()),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::Shutdown {}).unwrap(),
+        }))]
+    );
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::Shutdown {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // cannot unlock any tokens
-    // Failed to unlock more than locked amount
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::ShutdownState {}).unwrap();
+    let shutdown_state: ShutdownStateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        shutdown_state,
+        ShutdownStateResponse {
+            height: env.block.height,
+            repay_deadline: env.block.height + 100,
+            prices: vec![
+                ("bluna".to_string(), Decimal256::from_ratio(10u64, 1u64)),
+                ("batom".to_string(), Decimal256::from_ratio(5u64, 1u64)),
+            ],
+        }
+    );
+
+    // no new positions
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg);
     match res {
-        Err(ContractError::UnlockTooLarge(12599999400)) => (),
+        Err(ContractError::Shutdown {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("batom".to_string(), Uint256::one())],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecuteEpochOperations {},
+    );
     match res {
-        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        Err(ContractError::Shutdown {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12599999400u64))]);
+    // oracle prices are ignored from now on
+    // borrow_limit = 1000 * 5 * 0.6 + 1000 * 10 * 0.6 = 9000
+    deps.querier.with_oracle_price(&[]);
+    env.block.height += 99;
     let res = query(
         deps.as_ref(),
         env.clone(),
         QueryMsg::BorrowLimit {
             borrower: "addr0000".to_string(),
-            block_time: None,
+            block_time: Some(env.block.time.seconds()),
         },
     )
     .unwrap();
     let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
-    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(9000u64));
 
-    // Cannot unlock 2bluna
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::from(2u64))],
+    let msg = ExecuteMsg::SettleCollateral {
+        borrower: "addr0000".to_string(),
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
     match res {
-        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        Err(ContractError::RepayDeadlineNotPassed(deadline)) => {
+            assert_eq!(deadline, env.block.height + 1)
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // Can unlock 1bluna
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    // unpaid collaterals are kept for settlement past the deadline
+    env.block.height += 1;
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1u64))],
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "custody_bluna".to_string(),
-            funds: vec![],
-            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                borrower: "addr0000".to_string(),
-                amount: Uint256::one(),
-            })
-            .unwrap(),
-        }))]
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unlock_msg,
     );
+    match res {
+        Err(ContractError::RepayDeadlinePassed(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
+    // settled at the frozen prices without the oracle nor the liquidation queue;
+    // collateral_value = 1000 * 5 + 1000 * 10 = 15000 for a 5000 loan
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr0001", &[]), msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "unlock_collateral"),
+            attr("action", "settle_collateral"),
             attr("borrower", "addr0000"),
-            attr("collaterals", "1bluna"),
+            attr("loan_amount", "5000"),
+            attr("collateral_value", "15000"),
         ]
     );
-
-    //testing for unlocking more collaterals
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(125999900u128))]);
-
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1u128)),
-            ("batom".to_string(), Uint256::from(1u128)),
-        ],
-    };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(
         res.messages,
         vec![
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: "custody_bluna".to_string(),
                 funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                msg: to_binary(&CustodyExecuteMsg::SettleCollateral {
                     borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1u128),
+                    amount: Uint256::from(334u64),
                 })
                 .unwrap(),
             })),
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: "custody_batom".to_string(),
                 funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                msg: to_binary(&CustodyExecuteMsg::SettleCollateral {
                     borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1u128),
+                    amount: Uint256::from(334u64),
                 })
                 .unwrap(),
-            }))
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::SettleLoan {
+                    borrower: "addr0000".to_string(),
+                    collaterals: vec![
+                        ("bluna".to_string(), Uint256::from(334u64)),
+                        ("batom".to_string(), Uint256::from(334u64)),
+                    ],
+                })
+                .unwrap(),
+            })),
         ]
     );
+
+    // the surplus is left to the borrower once the loan is cleared
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::zero()),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
     assert_eq!(
-        res.attributes,
+        collaterals_res.collaterals,
         vec![
-            attr("action", "unlock_collateral"),
-            attr("borrower", "addr0000"),
-            attr("collaterals", "1bluna,1batom"),
+            ("bluna".to_string(), Uint256::from(666u64)),
+            ("batom".to_string(), Uint256::from(666u64)),
         ]
     );
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(666u64))],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        unlock_msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::SettleCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env, mock_info("addr0001", &[]), msg);
+    match res {
+        Err(ContractError::NothingToSettle {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
-fn liquidate_collateral() {
+fn borrower_health() {
     let mut deps = mock_dependencies(&[]);
-    deps.querier
-        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
 
     let info = mock_info("owner", &[]);
     let env = mock_env();
@@ -1148,49 +4019,35 @@ fn liquidate_collateral() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let batom_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
-
-    let bluna_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
-
     // store whitelist elems
     let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
-        collateral_token: bluna_collat_token.clone(),
+        collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
     };
 
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
     let msg = ExecuteMsg::Whitelist {
         name: "batom".to_string(),
         symbol: "batom".to_string(),
-        collateral_token: batom_collat_token.clone(),
+        collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
-        max_ltv: Decimal256::percent(60),
+        max_ltv: Decimal256::percent(50),
+        liquidation_threshold: Decimal256::percent(80),
+        debt_ceiling: None,
     };
 
-    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     let msg = ExecuteMsg::LockCollateral {
         collaterals: vec![
-            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
-            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+            ("bluna".to_string(), Uint256::from(1000u64)),
+            ("batom".to_string(), Uint256::from(2000u64)),
         ],
     };
     let info = mock_info("addr0000", &[]);
@@ -1198,94 +4055,218 @@ fn liquidate_collateral() {
 
     deps.querier.with_oracle_price(&[
         (
-            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &("bluna".to_string(), "uusd".to_string()),
             &(
-                Decimal256::from_ratio(1000u64, 1u64),
+                Decimal256::from_uint256(10u64),
                 env.block.time.seconds(),
                 env.block.time.seconds(),
             ),
         ),
         (
-            &(batom_collat_token.clone(), "uusd".to_string()),
+            &("batom".to_string(), "uusd".to_string()),
             &(
-                Decimal256::from_ratio(2000u64, 1u64),
+                Decimal256::from_uint256(5u64),
                 env.block.time.seconds(),
                 env.block.time.seconds(),
             ),
         ),
     ]);
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
+    // borrow_limit = 10000 * 0.6 + 10000 * 0.5 = 11000
+    // liquidation_limit = 10000 * 0.7 + 10000 * 0.8 = 15000
     deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
-
-    let msg = ExecuteMsg::LiquidateCollateral {
-        borrower: "addr0000".to_string(),
-    };
-    let info = mock_info("addr0001", &[]);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res {
-        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12000u64))]);
 
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowerHealth {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let mut health_res: BorrowerHealthResponse = from_binary(&res).unwrap();
+    health_res
+        .collaterals
+        .sort_by(|a, b| a.collateral_token.cmp(&b.collateral_token));
     assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: "addr0001".to_string(),
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(100000u64),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: "addr0001".to_string(),
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(10000u64),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "market".to_string(),
-                funds: vec![],
-                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
-                    borrower: "addr0000".to_string(),
-                    prev_balance: Uint256::zero(),
-                })
-                .unwrap(),
-            }))
-        ]
+        health_res,
+        BorrowerHealthResponse {
+            borrower: "addr0000".to_string(),
+            loan_amount: Uint256::from(12000u64),
+            borrow_limit: Uint256::from(11000u64),
+            liquidation_limit: Uint256::from(15000u64),
+            ltv: Decimal256::percent(60),
+            collaterals: vec![
+                CollateralHealthElem {
+                    collateral_token: "batom".to_string(),
+                    amount: Uint256::from(2000u64),
+                    price: Decimal256::from_uint256(5u64),
+                    value: Uint256::from(10000u64),
+                    // (12000 - 7000) / (2000 * 0.8)
+                    liquidation_price: Some(Decimal256::from_str("3.125").unwrap()),
+                },
+                CollateralHealthElem {
+                    collateral_token: "bluna".to_string(),
+                    amount: Uint256::from(1000u64),
+                    price: Decimal256::from_uint256(10u64),
+                    value: Uint256::from(10000u64),
+                    // (12000 - 8000) / (1000 * 0.7)
+                    liquidation_price: Some(Decimal256::from_str("5.714285714285714285").unwrap()),
+                },
+            ],
+        }
     );
 
+    // loan covered by either collateral alone cannot be liquidated by a single price drop
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(6000u64))]);
+
     let res = query(
         deps.as_ref(),
         env,
-        QueryMsg::Collaterals {
+        QueryMsg::BorrowerHealth {
             borrower: "addr0000".to_string(),
         },
     )
     .unwrap();
-    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    let health_res: BorrowerHealthResponse = from_binary(&res).unwrap();
+    assert_eq!(health_res.ltv, Decimal256::percent(30));
+    assert!(health_res
+        .collaterals
+        .iter()
+        .all(|elem| elem.liquidation_price.is_none()));
+}
+
+#[test]
+fn liquidatable_borrowers() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        dyn_rate_epoch: 86400u64,
+        dyn_rate_maxchange: Decimal256::from_str("0.03").unwrap(),
+        dyn_rate_yr_increase_expectation: Decimal256::from_str("0.01").unwrap(),
+        dyn_rate_min: Decimal256::zero(),
+        dyn_rate_max: Decimal256::one(),
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(70),
+        debt_ceiling: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    for borrower in ["addr0000", "addr0001", "addr0002"] {
+        let msg = ExecuteMsg::LockCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+        };
+        let info = mock_info(borrower, &[]);
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_uint256(10u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+
+    // borrow_limit = 10000 * 0.6 = 6000
+    // liquidation_limit = 10000 * 0.7 = 7000
+    deps.querier.with_loan_amount(&[
+        (&"addr0000".to_string(), &Uint256::from(7001u64)),
+        (&"addr0001".to_string(), &Uint256::from(7000u64)),
+        (&"addr0002".to_string(), &Uint256::from(9000u64)),
+    ]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LiquidatableBorrowers {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let mut liquidatable_res: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+    assert!(liquidatable_res.last_scanned.is_some());
+    liquidatable_res
+        .borrowers
+        .sort_by(|a, b| a.borrower.cmp(&b.borrower));
     assert_eq!(
-        collaterals_res,
-        CollateralsResponse {
-            borrower: "addr0000".to_string(),
-            collaterals: vec![
-                (batom_collat_token, Uint256::from(9900000u64)),
-                (bluna_collat_token, Uint256::from(990000u64)),
-            ]
+        liquidatable_res.borrowers,
+        vec![
+            LiquidatableBorrowersResponseElem {
+                borrower: "addr0000".to_string(),
+                loan_amount: Uint256::from(7001u64),
+                borrow_limit: Uint256::from(6000u64),
+                liquidation_limit: Uint256::from(7000u64),
+            },
+            LiquidatableBorrowersResponseElem {
+                borrower: "addr0002".to_string(),
+                loan_amount: Uint256::from(9000u64),
+                borrow_limit: Uint256::from(6000u64),
+                liquidation_limit: Uint256::from(7000u64),
+            },
+        ]
+    );
+
+    // walk the borrowers one page at a time
+    let mut start_after: Option<String> = None;
+    let mut scanned: u32 = 0;
+    let mut borrowers: Vec<String> = vec![];
+    loop {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::LiquidatableBorrowers {
+                start_after,
+                limit: Some(1u32),
+            },
+        )
+        .unwrap();
+        let page: LiquidatableBorrowersResponse = from_binary(&res).unwrap();
+        if page.last_scanned.is_none() {
+            assert!(page.borrowers.is_empty());
+            break;
         }
+
+        scanned += 1;
+        borrowers.extend(page.borrowers.into_iter().map(|elem| elem.borrower));
+        start_after = page.last_scanned;
+    }
+
+    borrowers.sort();
+    assert_eq!(scanned, 3u32);
+    assert_eq!(
+        borrowers,
+        vec!["addr0000".to_string(), "addr0002".to_string()]
     );
 }
 
@@ -1327,6 +4308,8 @@ fn dynamic_rate_model() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -1337,6 +4320,8 @@ fn dynamic_rate_model() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        liquidation_threshold: Decimal256::percent(60),
+        debt_ceiling: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -1547,6 +4532,8 @@ fn validate_deposit_rates(deps: DepsMut, rate: Decimal256) {
             buffer_distribution_factor: Decimal256::percent(20),
             anc_purchase_factor: Decimal256::percent(20),
             price_timeframe: 60u64,
+            price_twap_window: None,
+            timelock_period: 0,
             dyn_rate_epoch: 8600u64,
             dyn_rate_maxchange: Decimal256::permille(5),
             dyn_rate_yr_increase_expectation: Decimal256::permille(1),
@@ -0,0 +1,170 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use cosmwasm_std::{
+    attr, Addr, Api, CanonicalAddr, Deps, Order, Response, StdError, StdResult, Storage,
+};
+use cosmwasm_storage::{Bucket, ReadonlyBucket};
+
+static PREFIX_ROLE_MEMBER: &[u8] = b"role_member";
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Admin roles the owner can grant next to its own permissions;
+/// the owner keeps every permission regardless of the roles
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Risk parameters such as `max_ltv`, `safe_ratio` and fees
+    RiskAdmin,
+    /// Oracle feeder registration
+    FeederAdmin,
+    /// Collector and reserve parameters
+    TreasuryAdmin,
+    /// Pausing and confirming frozen oracle prices
+    Guardian,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::RiskAdmin => "risk_admin",
+            Role::FeederAdmin => "feeder_admin",
+            Role::TreasuryAdmin => "treasury_admin",
+            Role::Guardian => "guardian",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembersResponse {
+    pub role: Role,
+    pub members: Vec<String>,
+}
+
+pub fn has_role(storage: &dyn Storage, role: &Role, addr: &CanonicalAddr) -> StdResult<bool> {
+    let role_bucket: ReadonlyBucket<bool> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_ROLE_MEMBER, role.as_str().as_bytes()]);
+    Ok(role_bucket.may_load(addr.as_slice())?.unwrap_or(false))
+}
+
+/// Whether the sender is the owner or a member of the role
+pub fn is_owner_or_role(
+    storage: &dyn Storage,
+    owner: &CanonicalAddr,
+    role: &Role,
+    sender: &CanonicalAddr,
+) -> StdResult<bool> {
+    Ok(sender == owner || has_role(storage, role, sender)?)
+}
+
+/// Add the address to the role members.
+/// The caller must have checked the sender is the current owner
+pub fn grant_role<T>(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    role: Role,
+    address: Addr,
+) -> StdResult<Response<T>>
+where
+    T: Clone + fmt::Debug + PartialEq + JsonSchema,
+{
+    let address_raw = api.addr_canonicalize(address.as_str())?;
+    if has_role(storage, &role, &address_raw)? {
+        return Err(StdError::generic_err(format!(
+            "Address already has role: {}",
+            role
+        )));
+    }
+
+    let mut role_bucket: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_ROLE_MEMBER, role.as_str().as_bytes()]);
+    role_bucket.save(address_raw.as_slice(), &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "grant_role"),
+        attr("role", role.as_str()),
+        attr("address", address),
+    ]))
+}
+
+/// Remove the address from the role members.
+/// The caller must have checked the sender is the current owner
+pub fn revoke_role<T>(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    role: Role,
+    address: Addr,
+) -> StdResult<Response<T>>
+where
+    T: Clone + fmt::Debug + PartialEq + JsonSchema,
+{
+    let address_raw = api.addr_canonicalize(address.as_str())?;
+    if !has_role(storage, &role, &address_raw)? {
+        return Err(StdError::generic_err(format!(
+            "Address does not have role: {}",
+            role
+        )));
+    }
+
+    let mut role_bucket: Bucket<bool> =
+        Bucket::multilevel(storage, &[PREFIX_ROLE_MEMBER, role.as_str().as_bytes()]);
+    role_bucket.remove(address_raw.as_slice());
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_role"),
+        attr("role", role.as_str()),
+        attr("address", address),
+    ]))
+}
+
+pub fn query_role_members(
+    deps: Deps,
+    role: Role,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<RoleMembersResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let role_bucket: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+        deps.storage,
+        &[PREFIX_ROLE_MEMBER, role.as_str().as_bytes()],
+    );
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    let members = role_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string())
+        })
+        .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(RoleMembersResponse { role, members })
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
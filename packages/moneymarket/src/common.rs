@@ -1,4 +1,5 @@
 use cosmwasm_std::{Addr, Api, StdResult};
+use serde::{Deserialize, Deserializer};
 
 pub fn optional_addr_validate(api: &dyn Api, addr: Option<String>) -> StdResult<Option<Addr>> {
     let addr = if let Some(addr) = addr {
@@ -9,3 +10,13 @@ pub fn optional_addr_validate(api: &dyn Api, addr: Option<String>) -> StdResult<
 
     Ok(addr)
 }
+
+/// Deserialize an update field which can also clear the value;
+/// a missing field is `None`, `null` is `Some(None)`
+pub fn deserialize_clearable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -37,13 +37,25 @@ pub enum ExecuteMsg {
 
     /// Update config
     UpdateConfig {
-        owner: Option<String>,
         liquidation_contract: Option<String>,
     },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
     /// Make specified amount of tokens unspendable
     LockCollateral { borrower: String, amount: Uint256 },
     /// Make specified amount of collateral tokens spendable
     UnlockCollateral { borrower: String, amount: Uint256 },
+    /// Unlock specified amount of collateral tokens
+    /// and transfer them back to the borrower
+    UnlockAndWithdrawCollateral { borrower: String, amount: Uint256 },
     /// Claim bAsset rewards and distribute claimed rewards
     /// to market and overseer contracts
     DistributeRewards {},
@@ -55,6 +67,10 @@ pub enum ExecuteMsg {
         amount: Uint256,
     },
 
+    /// Seize locked collateral of a loan left unpaid after the shutdown
+    /// deadline and transfer it to the market
+    SettleCollateral { borrower: String, amount: Uint256 },
+
     ////////////////////
     /// User operations
     ////////////////////
@@ -70,6 +86,12 @@ pub enum ExecuteMsg {
 pub enum Cw20HookMsg {
     /// Deposit collateral token
     DepositCollateral {},
+    /// Deposit collateral token and lock it in the overseer,
+    /// optionally borrowing `borrow_amount` against it
+    DepositAndLock {
+        borrow_amount: Option<Uint256>,
+        to: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -80,6 +102,7 @@ pub struct MigrateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
     Borrower {
         address: String,
     },
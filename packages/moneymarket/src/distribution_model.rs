@@ -16,18 +16,28 @@ pub struct InstantiateMsg {
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     UpdateConfig {
-        owner: Option<String>,
         emission_cap: Option<Decimal256>,
         emission_floor: Option<Decimal256>,
         increment_multiplier: Option<Decimal256>,
         decrement_multiplier: Option<Decimal256>,
     },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
     AncEmissionRate {
         deposit_rate: Decimal256,
         target_deposit_rate: Decimal256,
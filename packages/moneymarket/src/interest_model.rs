@@ -14,16 +14,26 @@ pub struct InstantiateMsg {
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     UpdateConfig {
-        owner: Option<String>,
         base_rate: Option<Decimal256>,
         interest_multiplier: Option<Decimal256>,
     },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
     BorrowRate {
         market_balance: Uint256,
         total_liabilities: Decimal256,
@@ -0,0 +1,49 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+
+pub use crate::interest_model::{BorrowRateResponse, QueryMsg};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    pub base_rate: Decimal256,
+    /// Multiplier applied to the utilization ratio below the kink
+    pub interest_multiplier: Decimal256,
+    /// Utilization ratio where the jump multiplier takes over
+    pub kink: Decimal256,
+    /// Multiplier applied to the utilization ratio above the kink
+    pub jump_multiplier: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    UpdateConfig {
+        base_rate: Option<Decimal256>,
+        interest_multiplier: Option<Decimal256>,
+        kink: Option<Decimal256>,
+        jump_multiplier: Option<Decimal256>,
+    },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub base_rate: Decimal256,
+    pub interest_multiplier: Decimal256,
+    pub kink: Decimal256,
+    pub jump_multiplier: Decimal256,
+}
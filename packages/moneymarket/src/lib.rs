@@ -1,12 +1,15 @@
+pub mod access;
 pub mod common;
 pub mod custody;
 pub mod distribution_model;
 pub mod interest_model;
+pub mod kinked_interest_model;
 pub mod liquidation;
 pub mod liquidation_queue;
 pub mod market;
 pub mod oracle;
 pub mod overseer;
+pub mod ownership;
 pub mod querier;
 pub mod tokens;
 
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cw20::Cw20ReceiveMsg;
 
+use crate::access::Role;
 use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -33,7 +34,6 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     UpdateConfig {
-        owner: Option<String>,
         oracle_contract: Option<String>,
         stable_denom: Option<String>,
         safe_ratio: Option<Decimal256>,
@@ -42,6 +42,26 @@ pub enum ExecuteMsg {
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
     },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Grant an admin role to the address
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Revoke an admin role from the address
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
     SubmitBid {
         collateral_token: String,
         premium_rate: Decimal256,
@@ -66,6 +86,12 @@ pub enum Cw20HookMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     LiquidationAmount {
         borrow_amount: Uint256,
         borrow_limit: Uint256,
@@ -5,6 +5,7 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::Uint128;
 use cw20::Cw20ReceiveMsg;
 
+use crate::access::Role;
 use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -37,7 +38,6 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     UpdateConfig {
-        owner: Option<String>,
         oracle_contract: Option<String>,
         safe_ratio: Option<Decimal256>,
         bid_fee: Option<Decimal256>,
@@ -47,6 +47,26 @@ pub enum ExecuteMsg {
         waiting_period: Option<u64>,
         overseer: Option<String>,
     },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Grant an admin role to the address
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Revoke an admin role from the address
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
     /// Owner operation to whitelist a new collateral
     WhitelistCollateral {
         collateral_token: String,
@@ -96,6 +116,12 @@ pub enum Cw20HookMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     LiquidationAmount {
         borrow_amount: Uint256,
         borrow_limit: Uint256,
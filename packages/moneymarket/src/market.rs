@@ -1,7 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::access::Role;
+use crate::tokens::TokensHuman;
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::Binary;
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -44,10 +47,33 @@ pub enum ExecuteMsg {
 
     /// Update config values
     UpdateConfig {
-        owner_addr: Option<String>,
         max_borrow_factor: Option<Decimal256>,
         interest_model: Option<String>,
         distribution_model: Option<String>,
+        /// Fee rate charged on flash loans, accrued to the reserves
+        flash_loan_fee: Option<Decimal256>,
+        /// Share of the ANC emission going to the staked aterra
+        depositor_reward_ratio: Option<Decimal256>,
+    },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Grant an admin role to the address
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Revoke an admin role from the address
+    RevokeRole {
+        role: Role,
+        address: String,
     },
 
     ////////////////////
@@ -59,6 +85,33 @@ pub enum ExecuteMsg {
         prev_balance: Uint256,
     },
 
+    /// Borrow stable on behalf of the borrower, right after
+    /// its collaterals got locked through the custody
+    BorrowStableFor {
+        borrower: String,
+        borrow_amount: Uint256,
+        to: Option<String>,
+    },
+
+    /// Write off the loan of a borrower left without collaterals;
+    /// the attached stable covers the loan first, then the reserves,
+    /// and the rest is socialized through the aterra exchange rate
+    WriteOffBadDebt {
+        borrower: String,
+    },
+
+    /// Clear the loan of a borrower whose collaterals got settled after
+    /// shutdown; the settled collaterals sent to the market are paid out
+    /// to depositors on redemption, settled aterra is burned
+    SettleLoan {
+        borrower: String,
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    },
+
+    /// Stop interest accrual, deposits and borrows for good,
+    /// and release the reserves to depositors
+    Shutdown {},
+
     /// Execute epoch operations
     /// 1. send reserve to collector contract
     /// 2. update anc_emission_rate state
@@ -81,13 +134,39 @@ pub enum ExecuteMsg {
         to: Option<String>,
     },
 
-    /// Repay stable asset to decrease liability
-    RepayStable {},
+    /// Repay stable asset to decrease liability of the borrower,
+    /// the sender itself when not given
+    RepayStable {
+        borrower: Option<String>,
+    },
+
+    /// Repay stable asset, then unlock and withdraw
+    /// the given collaterals to the borrower
+    RepayStableAndUnlock {
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    },
 
-    /// Claim distributed ANC rewards
+    /// Claim distributed ANC rewards of both
+    /// the loan and the staked aterra
     ClaimRewards {
         to: Option<String>,
     },
+
+    /// Unstake aterra, which stops receiving the depositor ANC rewards
+    UnstakeATerra {
+        amount: Uint256,
+    },
+
+    /// Send `amount` stable to the sender along with `msg`; the sender
+    /// must have returned `amount` plus the fee by the end of its callback
+    FlashLoan {
+        amount: Uint256,
+        msg: Binary,
+    },
+
+    /// Internal operation run right after the flash loan callback,
+    /// checking the loan and its fee got returned
+    AssertFlashLoanRepaid {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -96,12 +175,26 @@ pub enum Cw20HookMsg {
     /// Return stable coins to a user
     /// according to exchange rate
     RedeemStable {},
+    /// Burn aterra at the current exchange rate to decrease
+    /// liability of the borrower, the sender itself when not given
+    RepayStable { borrower: Option<String> },
+    /// Burn aterra seized from the collaterals of the borrower against its
+    /// loan; aterra left once the loan is repaid goes back to the borrower
+    RepayStableFromLiquidation { borrower: String },
+    /// Stake aterra to receive the depositor ANC rewards
+    StakeATerra {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     State {
         block_height: Option<u64>,
     },
@@ -117,6 +210,14 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    DepositorInfo {
+        depositor: String,
+        block_height: Option<u64>,
+    },
+    BadDebts {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -131,6 +232,8 @@ pub struct ConfigResponse {
     pub distributor_contract: String,
     pub stable_denom: String,
     pub max_borrow_factor: Decimal256,
+    pub flash_loan_fee: Decimal256,
+    pub depositor_reward_ratio: Decimal256,
 }
 
 // We define a custom struct for each query response
@@ -164,12 +267,38 @@ pub struct BorrowerInfoResponse {
     pub pending_rewards: Decimal256,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositorInfoResponse {
+    pub depositor: String,
+    pub staked_amount: Uint256,
+    pub reward_index: Decimal256,
+    pub pending_rewards: Decimal256,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadDebtsResponse {
+    pub bad_debts: Vec<BadDebtsResponseElem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadDebtsResponseElem {
+    pub bad_debt_id: u64,
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub buffer_amount: Uint256,
+    pub reserve_amount: Uint256,
+    pub socialized_amount: Uint256,
+    pub height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct MigrateMsg {}
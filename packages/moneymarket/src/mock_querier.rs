@@ -130,7 +130,8 @@ impl WasmMockQuerier {
                 contract_addr: _,
                 msg,
             }) => match from_binary(msg).unwrap() {
-                OracleQueryMsg::Price { base, quote } => {
+                OracleQueryMsg::Price { base, quote }
+                | OracleQueryMsg::Twap { base, quote, .. } => {
                     match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
                         Some(v) => {
                             SystemResult::Ok(ContractResult::from(to_binary(&PriceResponse {
@@ -3,6 +3,9 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Decimal256;
 
+use crate::access::Role;
+use crate::common::deserialize_clearable;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub owner: String,
@@ -12,33 +15,114 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    UpdateConfig {
-        owner: Option<String>,
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
     },
-    RegisterFeeder {
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Grant an admin role to the address
+    GrantRole { role: Role, address: String },
+    /// Revoke an admin role from the address
+    RevokeRole { role: Role, address: String },
+    /// Add a feeder to the set of feeders of the asset
+    RegisterFeeder { asset: String, feeder: String },
+    /// Remove a feeder and its submitted price from the asset
+    DeregisterFeeder { asset: String, feeder: String },
+    /// Update price aggregation settings of the asset
+    UpdateAssetConfig {
         asset: String,
-        feeder: String,
+        /// Minimum # of fresh feeder submissions to aggregate a price
+        quorum: Option<u32>,
+        /// Maximum age (seconds) of a feeder submission to be aggregated;
+        /// `null` clears it
+        #[serde(
+            default,
+            deserialize_with = "deserialize_clearable",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_staleness: Option<Option<u64>>,
+        /// Maximum change ratio of the price per update before it gets frozen;
+        /// `null` clears it
+        #[serde(
+            default,
+            deserialize_with = "deserialize_clearable",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_price_change: Option<Option<Decimal256>>,
+        /// Prices below this bound get frozen; `null` clears it
+        #[serde(
+            default,
+            deserialize_with = "deserialize_clearable",
+            skip_serializing_if = "Option::is_none"
+        )]
+        min_price: Option<Option<Decimal256>>,
+        /// Prices above this bound get frozen; `null` clears it
+        #[serde(
+            default,
+            deserialize_with = "deserialize_clearable",
+            skip_serializing_if = "Option::is_none"
+        )]
+        max_price: Option<Option<Decimal256>>,
     },
     FeedPrice {
         prices: Vec<(String, Decimal256)>, // (asset, price)
     },
+    /// Accept the frozen price of the asset and resume price queries.
+    /// Only the owner or a guardian can execute
+    ConfirmPrice { asset: String },
 }
 
+/// Moves the feeder of each asset into its set of feeders
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the first registered feeder of the asset
     Feeder {
         asset: String,
     },
+    Feeders {
+        asset: String,
+    },
+    FeederPrices {
+        asset: String,
+    },
+    AssetConfig {
+        asset: String,
+    },
+    FrozenPrice {
+        asset: String,
+    },
     Price {
         base: String,
         quote: String,
     },
+    /// Prices of the assets which are not frozen
     Prices {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Time-weighted average price over the last `window_seconds`.
+    /// Returns PriceResponse
+    Twap {
+        base: String,
+        quote: String,
+        window_seconds: u64,
+    },
 }
 
 // We define a custom struct for each query response
@@ -55,6 +139,47 @@ pub struct FeederResponse {
     pub feeder: String,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedersResponse {
+    pub asset: String,
+    pub feeders: Vec<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederPricesResponseElem {
+    pub feeder: String,
+    pub price: Decimal256,
+    pub last_updated_time: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederPricesResponse {
+    pub asset: String,
+    pub prices: Vec<FeederPricesResponseElem>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetConfigResponse {
+    pub asset: String,
+    pub quorum: u32,
+    pub max_staleness: Option<u64>,
+    pub max_price_change: Option<Decimal256>,
+    pub min_price: Option<Decimal256>,
+    pub max_price: Option<Decimal256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenPriceResponse {
+    pub asset: String,
+    pub price: Decimal256,
+    pub last_updated_time: u64,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PriceResponse {
@@ -1,6 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
+use crate::access::Role;
 use crate::tokens::TokensHuman;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 
@@ -70,7 +72,6 @@ pub enum ExecuteMsg {
 
     /// Update Configs
     UpdateConfig {
-        owner_addr: Option<String>,
         oracle_contract: Option<String>,
         liquidation_contract: Option<String>,
         threshold_deposit_rate: Option<Decimal256>,
@@ -79,25 +80,88 @@ pub enum ExecuteMsg {
         anc_purchase_factor: Option<Decimal256>,
         epoch_period: Option<u64>,
         price_timeframe: Option<u64>,
+        /// Value collaterals with the oracle TWAP over this window (seconds);
+        /// 0 goes back to the spot price
+        price_twap_window: Option<u64>,
         dyn_rate_epoch: Option<u64>,
         dyn_rate_maxchange: Option<Decimal256>,
         dyn_rate_yr_increase_expectation: Option<Decimal256>,
         dyn_rate_min: Option<Decimal256>,
         dyn_rate_max: Option<Decimal256>,
+        /// # of blocks risk-sensitive changes wait before they can be executed
+        timelock_period: Option<u64>,
+    },
+    /// Cancel a parameter change waiting for the timelock
+    CancelPendingChange {
+        change_id: u64,
+    },
+    /// Shut the protocol down for good: collateral prices are frozen,
+    /// interest stops and depositors redeem a pro-rata share of the market
+    Shutdown {
+        /// # of blocks borrowers can still repay to reclaim their collaterals
+        repay_period: u64,
+        /// Prices used instead of the oracle for the given collaterals
+        prices: Option<Vec<(String, Decimal256)>>,
+    },
+    /// Resume paused operations
+    Unpause {
+        operations: Vec<PausableOperation>,
+    },
+    /// Propose a new owner, who takes over once it accepts;
+    /// the proposal expires after `expires_in` blocks when given
+    ProposeNewOwner {
+        owner: String,
+        expires_in: Option<u64>,
+    },
+    /// Accept the ownership proposed to the sender
+    AcceptOwnership {},
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Grant an admin role to the address
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Revoke an admin role from the address
+    RevokeRole {
+        role: Role,
+        address: String,
     },
     /// Create new custody contract for the given collateral token
     Whitelist {
-        name: String,             // bAsset name
-        symbol: String,           // bAsset symbol
-        collateral_token: String, // bAsset token contract
-        custody_contract: String, // bAsset custody contract
-        max_ltv: Decimal256,      // Loan To Value ratio
+        name: String,                      // bAsset name
+        symbol: String,                    // bAsset symbol
+        collateral_token: String,          // bAsset token contract
+        custody_contract: String,          // bAsset custody contract
+        max_ltv: Decimal256,               // Loan To Value ratio
+        liquidation_threshold: Decimal256, // LTV ratio at which loans become liquidatable
+        debt_ceiling: Option<Uint256>,     // Isolates the collateral when set
     },
     /// Update registered whitelist info
     UpdateWhitelist {
-        collateral_token: String,         // bAsset token contract
-        custody_contract: Option<String>, // bAsset custody contract
-        max_ltv: Option<Decimal256>,      // Loan To Value ratio
+        collateral_token: String,                  // bAsset token contract
+        custody_contract: Option<String>,          // bAsset custody contract
+        max_ltv: Option<Decimal256>,               // Loan To Value ratio
+        liquidation_threshold: Option<Decimal256>, // LTV ratio at which loans become liquidatable
+        debt_ceiling: Option<Uint256>,             // 0 removes the ceiling
+    },
+    /// Start retiring a collateral; new locks are disabled and
+    /// max_ltv and liquidation_threshold are ramped down to zero over `ramp_blocks`
+    DelistCollateral {
+        collateral_token: String,
+        ramp_blocks: u64,
+    },
+    /// Remove a fully delisted collateral no borrower holds anymore
+    RemoveWhitelist {
+        collateral_token: String,
+    },
+    /// Register or update an e-mode category of correlated collaterals
+    UpdateEModeCategory {
+        category_id: u8,
+        name: String,
+        max_ltv: Decimal256,
+        liquidation_threshold: Decimal256,
+        collaterals: Vec<String>,
     },
 
     /// Claims all staking rewards from the bAsset contracts
@@ -111,6 +175,44 @@ pub enum ExecuteMsg {
         distributed_interest: Uint256,
     },
 
+    ////////////////////
+    /// Guardian operations
+    ////////////////////
+    /// Halt operations across the market, overseer, custodies and
+    /// liquidation queue; the owner can also pause
+    Pause {
+        operations: Vec<PausableOperation>,
+    },
+
+    ////////////////////
+    /// Market operations
+    ////////////////////
+    /// Record the loan of the borrower against its isolated collateral
+    UpdateIsolatedDebt {
+        borrower: String,
+        loan_amount: Uint256,
+        /// Market global interest index the loan amount was computed at
+        interest_index: Decimal256,
+    },
+    /// Unlock collaterals of a borrower who just repaid,
+    /// and have the custodies send them back to the borrower
+    UnlockCollateralFor {
+        borrower: String,
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    },
+
+    ////////////////////
+    /// Custody operations
+    ////////////////////
+    /// Lock collaterals deposited to the custody on behalf of the borrower,
+    /// and borrow against them when `borrow_amount` is given
+    LockCollateralFor {
+        borrower: String,
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+        borrow_amount: Option<Uint256>,
+        to: Option<String>,
+    },
+
     ////////////////////
     /// User operations
     ////////////////////
@@ -120,6 +222,10 @@ pub enum ExecuteMsg {
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Opt into an e-mode category, or out of it with None
+    SetEMode {
+        category_id: Option<u8>,
+    },
 
     /////////////////////////////
     /// Permissionless operations
@@ -127,21 +233,136 @@ pub enum ExecuteMsg {
     LiquidateCollateral {
         borrower: String,
     },
+    /// Liquidate every unsafe borrower of the list; safe borrowers
+    /// are skipped and a failed liquidation does not revert the others
+    LiquidateCollaterals {
+        borrowers: Vec<String>,
+    },
+    /// Internal operation used by LiquidateCollaterals, so each
+    /// liquidation observes the market balance left by the previous one
+    LiquidateCollateralHook {
+        borrower: String,
+        liquidator: String,
+    },
+
+    /// Write off the loan of a borrower without collaterals, covered by
+    /// the interest buffer, then the market reserves, then depositors
+    WriteOffBadDebt {
+        borrower: String,
+    },
+    /// Hand the collaterals of a borrower who did not repay before the
+    /// shutdown deadline to the market, valued at the frozen prices
+    SettleCollateral {
+        borrower: String,
+    },
+    /// Apply a parameter change once its timelock has passed
+    ExecutePendingChange {
+        change_id: u64,
+    },
 
     FundReserve {},
 }
 
+/// Operation the guardian can pause; repaying is never pausable
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableOperation {
+    Deposit,
+    Redeem,
+    Borrow,
+    Lock,
+    Unlock,
+    Withdraw,
+    Liquidate,
+    SubmitBid,
+}
+
+impl PausableOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PausableOperation::Deposit => "deposit",
+            PausableOperation::Redeem => "redeem",
+            PausableOperation::Borrow => "borrow",
+            PausableOperation::Lock => "lock",
+            PausableOperation::Unlock => "unlock",
+            PausableOperation::Withdraw => "withdraw",
+            PausableOperation::Liquidate => "liquidate",
+            PausableOperation::SubmitBid => "submit_bid",
+        }
+    }
+}
+
+impl fmt::Display for PausableOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Risk-sensitive parameter change applied after the timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamChange {
+    Config {
+        oracle_contract: Option<String>,
+        liquidation_contract: Option<String>,
+        threshold_deposit_rate: Option<Decimal256>,
+        target_deposit_rate: Option<Decimal256>,
+        price_timeframe: Option<u64>,
+        price_twap_window: Option<u64>,
+        timelock_period: Option<u64>,
+    },
+    Whitelist {
+        collateral_token: String,
+        custody_contract: Option<String>,
+        max_ltv: Option<Decimal256>,
+        liquidation_threshold: Option<Decimal256>,
+        debt_ceiling: Option<Uint256>,
+    },
+    EModeCategory {
+        category_id: u8,
+        name: String,
+        max_ltv: Decimal256,
+        liquidation_threshold: Decimal256,
+        collaterals: Vec<String>,
+    },
+    /// The delisting ramp starts once the change is executed
+    DelistCollateral {
+        collateral_token: String,
+        ramp_blocks: u64,
+    },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    PendingOwner {},
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     EpochState {},
     DynrateState {},
+    EModeCategories {},
+    BorrowerEMode {
+        borrower: String,
+    },
     Whitelist {
         collateral_token: Option<String>,
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    CollateralStatus {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    PendingChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    PauseStatus {},
+    ShutdownState {},
     Collaterals {
         borrower: String,
     },
@@ -153,6 +374,15 @@ pub enum QueryMsg {
         borrower: String,
         block_time: Option<u64>,
     },
+    BorrowerHealth {
+        borrower: String,
+    },
+    /// Scan a page of borrowers and return the ones whose loan
+    /// exceeds their liquidation limit
+    LiquidatableBorrowers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -170,6 +400,8 @@ pub struct ConfigResponse {
     pub stable_denom: String,
     pub epoch_period: u64,
     pub price_timeframe: u64,
+    pub price_twap_window: Option<u64>,
+    pub timelock_period: u64,
     pub dyn_rate_epoch: u64,
     pub dyn_rate_maxchange: Decimal256,
     pub dyn_rate_yr_increase_expectation: Decimal256,
@@ -183,8 +415,10 @@ pub struct WhitelistResponseElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
     pub custody_contract: String,
     pub collateral_token: String,
+    pub debt_ceiling: Option<Uint256>,
 }
 
 // We define a custom struct for each query response
@@ -193,6 +427,57 @@ pub struct WhitelistResponse {
     pub elems: Vec<WhitelistResponseElem>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CollateralStatus {
+    Active,
+    /// New locks are disabled and max_ltv and liquidation_threshold are being ramped down
+    Delisting,
+    /// max_ltv reached zero; removable once nothing is locked
+    Delisted,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralStatusElem {
+    pub collateral_token: String,
+    pub status: CollateralStatus,
+    pub max_ltv: Decimal256, // current max_ltv, including the delisting ramp
+    pub total_locked: Uint256,
+    pub delisting_end_height: Option<u64>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralStatusResponse {
+    pub elems: Vec<CollateralStatusElem>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EModeCategoryResponse {
+    pub category_id: u8,
+    pub name: String,
+    pub max_ltv: Decimal256,
+    pub liquidation_threshold: Decimal256,
+    pub collaterals: Vec<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EModeCategoriesResponse {
+    pub categories: Vec<EModeCategoryResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerEModeResponse {
+    pub borrower: String,
+    pub category_id: Option<u8>,
+    /// false when the collaterals do not all belong to the category
+    pub active: bool,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollateralsResponse {
@@ -210,4 +495,100 @@ pub struct AllCollateralsResponse {
 pub struct BorrowLimitResponse {
     pub borrower: String,
     pub borrow_limit: Uint256,
+    /// The borrower holds an isolated collateral,
+    /// so the market reports its loan back to the overseer
+    pub isolated: bool,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerHealthResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    /// Loans bigger than this amount can be liquidated
+    pub liquidation_limit: Uint256,
+    /// loan_amount / total collateral value
+    pub ltv: Decimal256,
+    pub collaterals: Vec<CollateralHealthElem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollateralHealthElem {
+    pub collateral_token: String,
+    pub amount: Uint256,
+    pub price: Decimal256,
+    /// amount * price in stable denom
+    pub value: Uint256,
+    /// Price at which the loan becomes liquidatable, with the other collaterals held constant.
+    /// None when the loan stays safe even if the collateral price drops to zero
+    pub liquidation_price: Option<Decimal256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidatableBorrowersResponse {
+    pub borrowers: Vec<LiquidatableBorrowersResponseElem>,
+    /// Last borrower scanned in the page; use it as `start_after` of the next page
+    pub last_scanned: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidatableBorrowersResponseElem {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    pub liquidation_limit: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChangesResponse {
+    pub changes: Vec<PendingChangesResponseElem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingChangesResponseElem {
+    pub change_id: u64,
+    pub change: ParamChange,
+    /// Block height from which anyone can execute the change
+    pub executable_at: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseStatusResponse {
+    pub deposit: bool,
+    pub redeem: bool,
+    pub borrow: bool,
+    pub lock: bool,
+    pub unlock: bool,
+    pub withdraw: bool,
+    pub liquidate: bool,
+    pub submit_bid: bool,
+}
+
+impl PauseStatusResponse {
+    pub fn is_paused(&self, operation: &PausableOperation) -> bool {
+        match operation {
+            PausableOperation::Deposit => self.deposit,
+            PausableOperation::Redeem => self.redeem,
+            PausableOperation::Borrow => self.borrow,
+            PausableOperation::Lock => self.lock,
+            PausableOperation::Unlock => self.unlock,
+            PausableOperation::Withdraw => self.withdraw,
+            PausableOperation::Liquidate => self.liquidate,
+            PausableOperation::SubmitBid => self.submit_bid,
+        }
+    }
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShutdownStateResponse {
+    pub height: u64,
+    /// Borrowers can repay and unlock until this height,
+    /// then their collaterals get settled
+    pub repay_deadline: u64,
+    pub prices: Vec<(String, Decimal256)>,
 }
@@ -0,0 +1,136 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use cosmwasm_std::{
+    attr, Addr, Api, CanonicalAddr, Deps, Env, Response, StdError, StdResult, Storage,
+};
+use cosmwasm_storage::{singleton, singleton_read};
+
+static KEY_OWNERSHIP_PROPOSAL: &[u8] = b"ownership_proposal";
+
+/// Owner proposed by the current owner, waiting for its acceptance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    pub proposed_owner: CanonicalAddr,
+    /// Last block height the proposal can be accepted at
+    pub expires_at: Option<u64>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub proposed_owner: Option<String>,
+    pub expires_at: Option<u64>,
+}
+
+pub fn store_ownership_proposal(
+    storage: &mut dyn Storage,
+    proposal: &OwnershipProposal,
+) -> StdResult<()> {
+    singleton(storage, KEY_OWNERSHIP_PROPOSAL).save(proposal)
+}
+
+pub fn read_ownership_proposal(storage: &dyn Storage) -> StdResult<Option<OwnershipProposal>> {
+    singleton_read(storage, KEY_OWNERSHIP_PROPOSAL).may_load()
+}
+
+pub fn remove_ownership_proposal(storage: &mut dyn Storage) {
+    singleton::<OwnershipProposal>(storage, KEY_OWNERSHIP_PROPOSAL).remove()
+}
+
+/// Propose a new owner, replacing any pending proposal;
+/// `expires_in` is the number of blocks the proposal stays valid.
+/// The caller must have checked the sender is the current owner
+pub fn propose_new_owner<T>(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    owner: Addr,
+    expires_in: Option<u64>,
+) -> StdResult<Response<T>>
+where
+    T: Clone + fmt::Debug + PartialEq + JsonSchema,
+{
+    let expires_at = expires_in.map(|blocks| env.block.height + blocks);
+    store_ownership_proposal(
+        storage,
+        &OwnershipProposal {
+            proposed_owner: api.addr_canonicalize(owner.as_str())?,
+            expires_at,
+        },
+    )?;
+
+    let mut res = Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("proposed_owner", owner),
+    ]);
+    if let Some(expires_at) = expires_at {
+        res = res.add_attribute("expires_at", expires_at.to_string());
+    }
+
+    Ok(res)
+}
+
+/// Drop the pending proposal.
+/// The caller must have checked the sender is the current owner
+pub fn cancel_ownership_proposal<T>(storage: &mut dyn Storage) -> StdResult<Response<T>>
+where
+    T: Clone + fmt::Debug + PartialEq + JsonSchema,
+{
+    if read_ownership_proposal(storage)?.is_none() {
+        return Err(StdError::generic_err("No ownership proposal pending"));
+    }
+
+    remove_ownership_proposal(storage);
+
+    Ok(Response::new().add_attributes(vec![attr("action", "cancel_ownership_proposal")]))
+}
+
+/// Consume the pending proposal when the sender is the proposed owner,
+/// and return the new owner for the caller to store in its config
+pub fn accept_ownership(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    sender: &Addr,
+) -> StdResult<CanonicalAddr> {
+    let proposal = match read_ownership_proposal(storage)? {
+        Some(proposal) => proposal,
+        None => return Err(StdError::generic_err("No ownership proposal pending")),
+    };
+
+    if api.addr_canonicalize(sender.as_str())? != proposal.proposed_owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(expires_at) = proposal.expires_at {
+        if env.block.height > expires_at {
+            return Err(StdError::generic_err(format!(
+                "Ownership proposal expired at height: {}",
+                expires_at
+            )));
+        }
+    }
+
+    remove_ownership_proposal(storage);
+
+    Ok(proposal.proposed_owner)
+}
+
+pub fn query_pending_owner(deps: Deps) -> StdResult<PendingOwnerResponse> {
+    match read_ownership_proposal(deps.storage)? {
+        Some(proposal) => Ok(PendingOwnerResponse {
+            proposed_owner: Some(
+                deps.api
+                    .addr_humanize(&proposal.proposed_owner)?
+                    .to_string(),
+            ),
+            expires_at: proposal.expires_at,
+        }),
+        None => Ok(PendingOwnerResponse {
+            proposed_owner: None,
+            expires_at: None,
+        }),
+    }
+}
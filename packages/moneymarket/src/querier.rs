@@ -4,12 +4,13 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Deps, QueryRequest,
-    StdError, StdResult, Uint128, WasmQuery,
+    StdError, StdResult, WasmQuery,
 };
-use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::TerraQuerier;
 
 use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+use crate::overseer::{PauseStatusResponse, QueryMsg as OverseerQueryMsg};
 
 pub fn query_all_balances(deps: Deps, account_addr: Addr) -> StdResult<Vec<Coin>> {
     // load price form the oracle
@@ -36,17 +37,15 @@ pub fn query_token_balance(
     account_addr: Addr,
 ) -> StdResult<Uint256> {
     // load balance form the token contract
-    let balance: Uint128 = deps
-        .querier
-        .query(&QueryRequest::Wasm(WasmQuery::Smart {
+    let balance: Cw20BalanceResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: contract_addr.to_string(),
             msg: to_binary(&Cw20QueryMsg::Balance {
                 address: account_addr.to_string(),
             })?,
-        }))
-        .unwrap_or_else(|_| Uint128::zero());
+        }))?;
 
-    Ok(balance.into())
+    Ok(balance.balance.into())
 }
 
 pub fn query_supply(deps: Deps, contract_addr: Addr) -> StdResult<Uint256> {
@@ -110,6 +109,38 @@ pub fn query_price(
             msg: to_binary(&OracleQueryMsg::Price { base, quote })?,
         }))?;
 
+    assert_price_time_constraints(&oracle_price, time_contraints)?;
+
+    Ok(oracle_price)
+}
+
+pub fn query_twap(
+    deps: Deps,
+    oracle_addr: Addr,
+    base: String,
+    quote: String,
+    window_seconds: u64,
+    time_contraints: Option<TimeConstraints>,
+) -> StdResult<PriceResponse> {
+    let oracle_price: PriceResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: oracle_addr.to_string(),
+            msg: to_binary(&OracleQueryMsg::Twap {
+                base,
+                quote,
+                window_seconds,
+            })?,
+        }))?;
+
+    assert_price_time_constraints(&oracle_price, time_contraints)?;
+
+    Ok(oracle_price)
+}
+
+fn assert_price_time_constraints(
+    oracle_price: &PriceResponse,
+    time_contraints: Option<TimeConstraints>,
+) -> StdResult<()> {
     if let Some(time_contraints) = time_contraints {
         let valid_update_time = time_contraints.block_time - time_contraints.valid_timeframe;
         if oracle_price.last_updated_base < valid_update_time
@@ -119,5 +150,16 @@ pub fn query_price(
         }
     }
 
-    Ok(oracle_price)
+    Ok(())
+}
+
+pub fn query_pause_status(deps: Deps, overseer_addr: Addr) -> StdResult<PauseStatusResponse> {
+    // load pause flags from the overseer
+    let pause_status: PauseStatusResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer_addr.to_string(),
+            msg: to_binary(&OverseerQueryMsg::PauseStatus {})?,
+        }))?;
+
+    Ok(pause_status)
 }
@@ -1,10 +1,23 @@
+use crate::access::{
+    grant_role, has_role, is_owner_or_role, query_role_members, revoke_role, Role,
+    RoleMembersResponse,
+};
 use crate::mock_querier::mock_dependencies;
 use crate::oracle::PriceResponse;
-use crate::querier::{compute_tax, deduct_tax, query_price, query_tax_rate, TimeConstraints};
+use crate::ownership::{
+    accept_ownership, cancel_ownership_proposal, propose_new_owner, query_pending_owner,
+    PendingOwnerResponse,
+};
+use crate::querier::{
+    compute_tax, deduct_tax, query_price, query_tax_rate, query_twap, TimeConstraints,
+};
 use crate::tokens::{Tokens, TokensHuman, TokensMath, TokensToRaw};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Addr, Api, CanonicalAddr, Coin, Decimal, StdError, Uint128};
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{
+    attr, Addr, Api, CanonicalAddr, Coin, Decimal, Empty, Response, StdError, Uint128,
+};
 
 #[test]
 fn tax_rate_querier() {
@@ -118,6 +131,37 @@ fn oracle_price_querier() {
         Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Price is too old"),
         _ => panic!("DO NOT ENTER HERE"),
     }
+
+    let twap = query_twap(
+        deps.as_ref(),
+        Addr::unchecked("oracle"),
+        "terra123123".to_string(),
+        "uusd".to_string(),
+        3600u64,
+        Some(TimeConstraints {
+            block_time: 150u64,
+            valid_timeframe: 60u64,
+        }),
+    )
+    .unwrap();
+    assert_eq!(twap.rate, Decimal256::from_ratio(131, 2));
+
+    let res = query_twap(
+        deps.as_ref(),
+        Addr::unchecked("oracle"),
+        "terra123123".to_string(),
+        "uusd".to_string(),
+        3600u64,
+        Some(TimeConstraints {
+            block_time: 500u64,
+            valid_timeframe: 60u64,
+        }),
+    );
+
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Price is too old"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 }
 
 #[test]
@@ -300,3 +344,198 @@ fn token_math_invalid_token_2() {
 
     let _ = tokens_1_raw.sub(tokens_2_raw);
 }
+
+#[test]
+fn ownership_proposal() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+
+    // nothing to accept or cancel yet
+    let res = accept_ownership(
+        &mut deps.storage,
+        &deps.api,
+        &env,
+        &Addr::unchecked("owner0001"),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err("No ownership proposal pending"))
+    );
+    assert!(cancel_ownership_proposal::<Empty>(&mut deps.storage).is_err());
+
+    let _res: Response = propose_new_owner(
+        &mut deps.storage,
+        &deps.api,
+        &env,
+        Addr::unchecked("owner0001"),
+        Some(10u64),
+    )
+    .unwrap();
+    assert_eq!(
+        query_pending_owner(deps.as_ref()).unwrap(),
+        PendingOwnerResponse {
+            proposed_owner: Some("owner0001".to_string()),
+            expires_at: Some(env.block.height + 10u64),
+        }
+    );
+
+    // only the proposed owner can accept
+    let res = accept_ownership(
+        &mut deps.storage,
+        &deps.api,
+        &env,
+        &Addr::unchecked("addr0000"),
+    );
+    assert_eq!(res, Err(StdError::generic_err("unauthorized")));
+
+    // cannot accept after the expiry
+    let expires_at = env.block.height + 10u64;
+    env.block.height += 11u64;
+    let res = accept_ownership(
+        &mut deps.storage,
+        &deps.api,
+        &env,
+        &Addr::unchecked("owner0001"),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(format!(
+            "Ownership proposal expired at height: {}",
+            expires_at
+        )))
+    );
+
+    // cancel and propose again without expiry
+    let _res: Response = cancel_ownership_proposal(&mut deps.storage).unwrap();
+    assert_eq!(
+        query_pending_owner(deps.as_ref()).unwrap(),
+        PendingOwnerResponse {
+            proposed_owner: None,
+            expires_at: None,
+        }
+    );
+
+    let _res: Response = propose_new_owner(
+        &mut deps.storage,
+        &deps.api,
+        &env,
+        Addr::unchecked("owner0002"),
+        None,
+    )
+    .unwrap();
+    let new_owner = accept_ownership(
+        &mut deps.storage,
+        &deps.api,
+        &env,
+        &Addr::unchecked("owner0002"),
+    )
+    .unwrap();
+    assert_eq!(new_owner, deps.api.addr_canonicalize("owner0002").unwrap());
+
+    // the proposal is consumed
+    assert_eq!(
+        query_pending_owner(deps.as_ref()).unwrap().proposed_owner,
+        None
+    );
+}
+
+#[test]
+fn roles() {
+    let mut deps = mock_dependencies(&[]);
+
+    let owner = deps.api.addr_canonicalize("owner0000").unwrap();
+    let risk_admin = deps.api.addr_canonicalize("addr0000").unwrap();
+
+    let res: Response = grant_role(
+        &mut deps.storage,
+        &deps.api,
+        Role::RiskAdmin,
+        Addr::unchecked("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "grant_role"),
+            attr("role", "risk_admin"),
+            attr("address", "addr0000"),
+        ]
+    );
+
+    // cannot grant the same role twice
+    let res = grant_role::<Empty>(
+        &mut deps.storage,
+        &deps.api,
+        Role::RiskAdmin,
+        Addr::unchecked("addr0000"),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            "Address already has role: risk_admin"
+        ))
+    );
+
+    let _res: Response = grant_role(
+        &mut deps.storage,
+        &deps.api,
+        Role::RiskAdmin,
+        Addr::unchecked("addr0001"),
+    )
+    .unwrap();
+
+    // roles are independent from each other
+    assert!(has_role(&deps.storage, &Role::RiskAdmin, &risk_admin).unwrap());
+    assert!(!has_role(&deps.storage, &Role::Guardian, &risk_admin).unwrap());
+    assert!(is_owner_or_role(&deps.storage, &owner, &Role::Guardian, &owner).unwrap());
+    assert!(!is_owner_or_role(&deps.storage, &owner, &Role::Guardian, &risk_admin).unwrap());
+
+    assert_eq!(
+        query_role_members(deps.as_ref(), Role::RiskAdmin, None, None).unwrap(),
+        RoleMembersResponse {
+            role: Role::RiskAdmin,
+            members: vec!["addr0000".to_string(), "addr0001".to_string()],
+        }
+    );
+    assert_eq!(
+        query_role_members(
+            deps.as_ref(),
+            Role::RiskAdmin,
+            Some(Addr::unchecked("addr0000")),
+            None
+        )
+        .unwrap()
+        .members,
+        vec!["addr0001".to_string()]
+    );
+
+    let res: Response = revoke_role(
+        &mut deps.storage,
+        &deps.api,
+        Role::RiskAdmin,
+        Addr::unchecked("addr0000"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "revoke_role"),
+            attr("role", "risk_admin"),
+            attr("address", "addr0000"),
+        ]
+    );
+    assert!(!has_role(&deps.storage, &Role::RiskAdmin, &risk_admin).unwrap());
+
+    let res = revoke_role::<Empty>(
+        &mut deps.storage,
+        &deps.api,
+        Role::FeederAdmin,
+        Addr::unchecked("addr0000"),
+    );
+    assert_eq!(
+        res,
+        Err(StdError::generic_err(
+            "Address does not have role: feeder_admin"
+        ))
+    );
+}